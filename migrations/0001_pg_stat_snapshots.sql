@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS pg_stat_snapshot (
+    id BIGSERIAL PRIMARY KEY,
+    taken_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS pg_stat_snapshot_statement (
+    snapshot_id BIGINT NOT NULL REFERENCES pg_stat_snapshot (id) ON DELETE CASCADE,
+    queryid BIGINT NOT NULL,
+    query TEXT NOT NULL,
+    calls BIGINT NOT NULL,
+    total_exec_time DOUBLE PRECISION NOT NULL,
+    rows BIGINT NOT NULL,
+    PRIMARY KEY (snapshot_id, queryid)
+);
+
+CREATE TABLE IF NOT EXISTS pg_stat_snapshot_table (
+    snapshot_id BIGINT NOT NULL REFERENCES pg_stat_snapshot (id) ON DELETE CASCADE,
+    schemaname TEXT NOT NULL,
+    relname TEXT NOT NULL,
+    seq_scan BIGINT NOT NULL,
+    idx_scan BIGINT NOT NULL,
+    n_tup_ins BIGINT NOT NULL,
+    n_tup_upd BIGINT NOT NULL,
+    n_tup_del BIGINT NOT NULL,
+    n_live_tup BIGINT NOT NULL,
+    n_dead_tup BIGINT NOT NULL,
+    total_bytes BIGINT NOT NULL,
+    PRIMARY KEY (snapshot_id, schemaname, relname)
+);
+
+CREATE INDEX IF NOT EXISTS pg_stat_snapshot_taken_at_idx ON pg_stat_snapshot (taken_at);
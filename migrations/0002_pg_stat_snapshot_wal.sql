@@ -0,0 +1 @@
+ALTER TABLE pg_stat_snapshot ADD COLUMN IF NOT EXISTS wal_position BIGINT;
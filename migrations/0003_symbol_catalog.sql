@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS symbol_catalog_snapshot (
+    id BIGSERIAL PRIMARY KEY,
+    taken_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    symbol_count INTEGER NOT NULL
+);
+
+-- Full catalog as of each snapshot; a snapshot is only stored when the
+-- catalog differs from the previous one.
+CREATE TABLE IF NOT EXISTS symbol_catalog_entry (
+    snapshot_id BIGINT NOT NULL REFERENCES symbol_catalog_snapshot (id) ON DELETE CASCADE,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    fields JSONB NOT NULL,
+    PRIMARY KEY (snapshot_id, exchange, symbol)
+);
+
+CREATE TABLE IF NOT EXISTS symbol_change (
+    id BIGSERIAL PRIMARY KEY,
+    snapshot_id BIGINT NOT NULL REFERENCES symbol_catalog_snapshot (id) ON DELETE CASCADE,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    -- added, removed or changed; field is set only for changed
+    kind TEXT NOT NULL,
+    field TEXT,
+    old_value TEXT,
+    new_value TEXT
+);
+
+CREATE INDEX IF NOT EXISTS symbol_change_symbol_idx ON symbol_change (symbol);
+CREATE INDEX IF NOT EXISTS symbol_change_field_idx ON symbol_change (field);
@@ -0,0 +1,18 @@
+-- Notes and tags attached to a bot (by symbol), an order or a time range.
+CREATE TABLE IF NOT EXISTS journal_entry (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    bot_symbol TEXT,
+    order_id TEXT,
+    range_start TIMESTAMPTZ,
+    range_end TIMESTAMPTZ,
+    note TEXT NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    CHECK (num_nonnulls(bot_symbol, order_id, range_start) = 1),
+    CHECK ((range_start IS NULL) = (range_end IS NULL)),
+    CHECK (range_end >= range_start)
+);
+
+CREATE INDEX IF NOT EXISTS journal_entry_bot_symbol_idx ON journal_entry (bot_symbol);
+CREATE INDEX IF NOT EXISTS journal_entry_order_id_idx ON journal_entry (order_id);
+CREATE INDEX IF NOT EXISTS journal_entry_tags_idx ON journal_entry USING GIN (tags);
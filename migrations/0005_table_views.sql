@@ -0,0 +1,13 @@
+-- Named column, sort and filter presets for the generic table pages, at
+-- most one of them the page's default.
+CREATE TABLE IF NOT EXISTS table_view (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    page TEXT NOT NULL,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT false,
+    UNIQUE (page, name)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS table_view_default_idx ON table_view (page) WHERE is_default;
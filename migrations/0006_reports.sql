@@ -0,0 +1,20 @@
+-- Daily and weekly summaries, rendered when their period ends. `tables`
+-- holds the table sizes at that time, to report growth against the
+-- previous report of the same period.
+CREATE TABLE IF NOT EXISTS report (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    period TEXT NOT NULL CHECK (period IN ('daily', 'weekly')),
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    html TEXT NOT NULL,
+    markdown TEXT NOT NULL,
+    tables JSONB NOT NULL DEFAULT '[]',
+    -- NULL until posted to the webhook, or when none is configured.
+    delivered_at TIMESTAMPTZ,
+    delivery_error TEXT,
+    UNIQUE (period, period_start),
+    CHECK (period_end > period_start)
+);
+
+CREATE INDEX IF NOT EXISTS report_period_start_idx ON report (period_start);
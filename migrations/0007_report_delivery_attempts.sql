@@ -0,0 +1,11 @@
+-- Failed webhook deliveries are retried at `next_delivery_at`, backing off
+-- after each attempt, until `delivery_attempts` reaches the service's cap.
+ALTER TABLE report
+    ADD COLUMN IF NOT EXISTS delivery_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_delivery_at TIMESTAMPTZ;
+
+UPDATE report SET delivery_attempts = 1, next_delivery_at = now()
+WHERE delivered_at IS NULL AND delivery_error IS NOT NULL AND next_delivery_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS report_next_delivery_at_idx ON report (next_delivery_at)
+    WHERE delivered_at IS NULL AND delivery_error IS NOT NULL;
@@ -0,0 +1,667 @@
+use crate::api::models::{Balance, Bot, Currency, EventOrder, MsgEvent, MsgSend, Symbol};
+use crate::config::GraphQlConfig;
+use crate::core::app_state::AppState;
+use crate::core::error::AppError;
+use crate::repositories::LinkFilter;
+use async_graphql::{ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Result};
+use tracing::error;
+
+/// Rows a list field returns without a `limit`.
+const DEFAULT_LIMIT: i32 = 20;
+const MAX_LIMIT: i32 = 1000;
+
+pub type TradingSchema = async_graphql::Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn schema(config: &GraphQlConfig) -> TradingSchema {
+    TradingSchema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        .finish()
+}
+
+/// The source's state, attached to every request by the handler.
+fn state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<AppState>()
+}
+
+fn service_error(e: AppError) -> async_graphql::Error {
+    error!("Service error: {}", e);
+    "Service error".into()
+}
+
+fn row_limit(limit: i32) -> Result<i64> {
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(format!("limit must be between 1 and {}", MAX_LIMIT).into());
+    }
+    Ok(limit.into())
+}
+
+/// Cost of a list field: its row limit times that of one row. Nested lists
+/// multiply quickly, so the cost saturates, and at a value low enough that
+/// async-graphql adding up sibling fields cannot overflow either.
+fn list_cost(limit: i32, child_complexity: usize) -> usize {
+    const MAX_COST: usize = u32::MAX as usize;
+    (limit.clamp(0, MAX_LIMIT) as usize)
+        .saturating_mul(child_complexity)
+        .min(MAX_COST)
+}
+
+/// The given, non-empty keys, without duplicates.
+fn keys<'a>(values: impl IntoIterator<Item = Option<&'a String>>) -> Vec<String> {
+    let mut keys: Vec<String> = values
+        .into_iter()
+        .flatten()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Symbols and currencies are stored upper case.
+fn upper_keys<'a>(values: impl IntoIterator<Item = Option<&'a String>>) -> Vec<String> {
+    keys(values).iter().map(|v| v.to_uppercase()).collect()
+}
+
+/// Links on the order ids and client oids of one row.
+fn id_filter(
+    exchange: Option<&String>,
+    order_ids: Vec<String>,
+    client_oids: Vec<String>,
+    limit: i64,
+) -> LinkFilter {
+    LinkFilter {
+        exchange: exchange.cloned(),
+        order_ids,
+        client_oids,
+        limit,
+        ..Default::default()
+    }
+}
+
+async fn find_symbol(ctx: &Context<'_>, exchange: &str, symbol: &str) -> Result<Option<Symbol>> {
+    let symbols = state(ctx)
+        .symbol_service
+        .get_all_symbols()
+        .await
+        .map_err(service_error)?;
+    Ok(symbols
+        .into_iter()
+        .find(|s| s.exchange == exchange && s.symbol == symbol))
+}
+
+async fn find_currency(
+    ctx: &Context<'_>,
+    exchange: &str,
+    currency: &str,
+) -> Result<Option<Currency>> {
+    let currencies = state(ctx)
+        .currency_service
+        .get_currencies()
+        .await
+        .map_err(service_error)?;
+    Ok(currencies
+        .into_iter()
+        .find(|c| c.exchange == exchange && c.currency == currency))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Bots on `exchange`, narrowed to those trading `symbol` or referencing
+    /// `clientOid` or `orderId` when given.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn bots(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        symbol: Option<String>,
+        client_oid: Option<String>,
+        order_id: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Bot>> {
+        let filter = LinkFilter {
+            exchange,
+            order_ids: keys([order_id.as_ref()]),
+            client_oids: keys([client_oid.as_ref()]),
+            symbols: upper_keys([symbol.as_ref()]),
+            limit: row_limit(limit)?,
+            ..Default::default()
+        };
+        let bot_service = &state(ctx).bot_service;
+        if filter.has_keys() {
+            return bot_service
+                .get_linked_bots(&filter)
+                .await
+                .map_err(service_error);
+        }
+
+        let bots = bot_service.get_bots().await.map_err(service_error)?;
+        Ok(bots
+            .into_iter()
+            .filter(|bot| filter.exchange.is_none() || bot.exchange == filter.exchange)
+            .take(filter.limit as usize)
+            .collect())
+    }
+
+    /// Events of orders by `orderId`, `clientOid` or `symbol`; at least one
+    /// is required.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        order_id: Option<String>,
+        client_oid: Option<String>,
+        symbol: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        let filter = LinkFilter {
+            exchange,
+            order_ids: keys([order_id.as_ref()]),
+            client_oids: keys([client_oid.as_ref()]),
+            symbols: upper_keys([symbol.as_ref()]),
+            limit: row_limit(limit)?,
+            ..Default::default()
+        };
+        if !filter.has_keys() {
+            return Err("orders needs orderId, clientOid or symbol".into());
+        }
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    /// Balance changes by `orderId`, `symbol` or `currency`; at least one is
+    /// required.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn balances(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        order_id: Option<String>,
+        symbol: Option<String>,
+        currency: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Balance>> {
+        let filter = LinkFilter {
+            exchange,
+            order_ids: keys([order_id.as_ref()]),
+            symbols: upper_keys([symbol.as_ref()]),
+            currencies: upper_keys([currency.as_ref()]),
+            limit: row_limit(limit)?,
+            ..Default::default()
+        };
+        if !filter.has_keys() {
+            return Err("balances needs orderId, symbol or currency".into());
+        }
+        state(ctx)
+            .balance_service
+            .get_linked_balances(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    /// Sent orders by `orderId`, `clientOid` or `symbol`; at least one is
+    /// required.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgsends(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        order_id: Option<String>,
+        client_oid: Option<String>,
+        symbol: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgSend>> {
+        let filter = LinkFilter {
+            exchange,
+            order_ids: keys([order_id.as_ref()]),
+            client_oids: keys([client_oid.as_ref()]),
+            symbols: upper_keys([symbol.as_ref()]),
+            limit: row_limit(limit)?,
+            ..Default::default()
+        };
+        if !filter.has_keys() {
+            return Err("msgsends needs orderId, clientOid or symbol".into());
+        }
+        state(ctx)
+            .msgsend_service
+            .get_linked_msgsends(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    /// Exchange responses by `orderId` or `clientOid`; at least one is
+    /// required.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgevents(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        order_id: Option<String>,
+        client_oid: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgEvent>> {
+        let filter = LinkFilter {
+            exchange,
+            order_ids: keys([order_id.as_ref()]),
+            client_oids: keys([client_oid.as_ref()]),
+            limit: row_limit(limit)?,
+            ..Default::default()
+        };
+        if !filter.has_keys() {
+            return Err("msgevents needs orderId or clientOid".into());
+        }
+        state(ctx)
+            .msgevent_service
+            .get_linked_msgevents(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    /// Symbols on `exchange`, narrowed to `symbol` or to pairs with
+    /// `currency` as base or quote.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn symbols(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        symbol: Option<String>,
+        currency: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Symbol>> {
+        let limit = row_limit(limit)? as usize;
+        let symbol = symbol.map(|s| s.to_uppercase());
+        let currency = currency.map(|c| c.to_uppercase());
+        let symbols = state(ctx)
+            .symbol_service
+            .get_all_symbols()
+            .await
+            .map_err(service_error)?;
+        Ok(symbols
+            .into_iter()
+            .filter(|s| exchange.as_ref().is_none_or(|e| *e == s.exchange))
+            .filter(|s| symbol.as_ref().is_none_or(|sym| *sym == s.symbol))
+            .filter(|s| {
+                currency
+                    .as_ref()
+                    .is_none_or(|c| *c == s.base_currency || *c == s.quote_currency)
+            })
+            .take(limit)
+            .collect())
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn currencies(
+        &self,
+        ctx: &Context<'_>,
+        exchange: Option<String>,
+        currency: Option<String>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Currency>> {
+        let limit = row_limit(limit)? as usize;
+        let currency = currency.map(|c| c.to_uppercase());
+        let currencies = state(ctx)
+            .currency_service
+            .get_currencies()
+            .await
+            .map_err(service_error)?;
+        Ok(currencies
+            .into_iter()
+            .filter(|c| exchange.as_ref().is_none_or(|e| *e == c.exchange))
+            .filter(|c| currency.as_ref().is_none_or(|cur| *cur == c.currency))
+            .take(limit)
+            .collect())
+    }
+}
+
+impl Bot {
+    /// Entries are only known by client oid, exits by either id.
+    fn link(&self, limit: i64) -> LinkFilter {
+        id_filter(
+            self.exchange.as_ref(),
+            keys([
+                self.exit_tp_order_id.as_ref(),
+                self.exit_sl_order_id.as_ref(),
+            ]),
+            keys([
+                self.entry_client_oid.as_ref(),
+                self.exit_tp_client_oid.as_ref(),
+                self.exit_sl_client_oid.as_ref(),
+            ]),
+            limit,
+        )
+    }
+}
+
+#[ComplexObject]
+impl Bot {
+    /// Events of the entry, TP and SL orders.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgsends(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgSend>> {
+        state(ctx)
+            .msgsend_service
+            .get_linked_msgsends(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgevents(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgEvent>> {
+        state(ctx)
+            .msgevent_service
+            .get_linked_msgevents(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    /// Balance changes of the bot's orders. The entry's order id is looked
+    /// up through its events first.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn balance_changes(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Balance>> {
+        let state = state(ctx);
+        let orders = state
+            .order_service
+            .get_linked_event_orders(&self.link(MAX_LIMIT.into()))
+            .await
+            .map_err(service_error)?;
+        let filter = id_filter(
+            self.exchange.as_ref(),
+            keys(orders.iter().map(|o| Some(&o.order_id))),
+            Vec::new(),
+            row_limit(limit)?,
+        );
+        state
+            .balance_service
+            .get_linked_balances(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    async fn symbol_info(&self, ctx: &Context<'_>) -> Result<Option<Symbol>> {
+        match (&self.exchange, &self.symbol) {
+            (Some(exchange), Some(symbol)) => find_symbol(ctx, exchange, symbol).await,
+            _ => Ok(None),
+        }
+    }
+}
+
+impl EventOrder {
+    fn link(&self, limit: i64) -> LinkFilter {
+        id_filter(
+            Some(&self.exchange),
+            keys([Some(&self.order_id)]),
+            keys([self.client_oid.as_ref()]),
+            limit,
+        )
+    }
+}
+
+#[ComplexObject]
+impl EventOrder {
+    /// Bots this order is the entry, TP or SL of.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn bots(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Bot>> {
+        state(ctx)
+            .bot_service
+            .get_linked_bots(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgsends(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgSend>> {
+        state(ctx)
+            .msgsend_service
+            .get_linked_msgsends(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgevents(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgEvent>> {
+        state(ctx)
+            .msgevent_service
+            .get_linked_msgevents(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn balance_changes(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Balance>> {
+        let filter = id_filter(
+            Some(&self.exchange),
+            keys([Some(&self.order_id)]),
+            Vec::new(),
+            row_limit(limit)?,
+        );
+        state(ctx)
+            .balance_service
+            .get_linked_balances(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    async fn symbol_info(&self, ctx: &Context<'_>) -> Result<Option<Symbol>> {
+        find_symbol(ctx, &self.exchange, &self.symbol).await
+    }
+}
+
+#[ComplexObject]
+impl Balance {
+    /// Events of the order that caused the change.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        let filter = id_filter(
+            Some(&self.exchange),
+            keys([self.order_id.as_ref()]),
+            Vec::new(),
+            row_limit(limit)?,
+        );
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&filter)
+            .await
+            .map_err(service_error)
+    }
+
+    async fn currency_info(&self, ctx: &Context<'_>) -> Result<Option<Currency>> {
+        find_currency(ctx, &self.exchange, &self.currency).await
+    }
+
+    async fn symbol_info(&self, ctx: &Context<'_>) -> Result<Option<Symbol>> {
+        match &self.symbol {
+            Some(symbol) => find_symbol(ctx, &self.exchange, symbol).await,
+            None => Ok(None),
+        }
+    }
+}
+
+impl MsgSend {
+    fn link(&self, limit: i64) -> LinkFilter {
+        id_filter(
+            Some(&self.exchange),
+            keys([self.args_order_id.as_ref()]),
+            keys([self.args_client_oid.as_ref()]),
+            limit,
+        )
+    }
+}
+
+#[ComplexObject]
+impl MsgSend {
+    /// Events of the order this message placed or cancelled.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    /// The exchange's responses to this message.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgevents(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgEvent>> {
+        state(ctx)
+            .msgevent_service
+            .get_linked_msgevents(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    async fn symbol_info(&self, ctx: &Context<'_>) -> Result<Option<Symbol>> {
+        match &self.args_symbol {
+            Some(symbol) => find_symbol(ctx, &self.exchange, symbol).await,
+            None => Ok(None),
+        }
+    }
+}
+
+impl MsgEvent {
+    fn link(&self, limit: i64) -> LinkFilter {
+        id_filter(
+            Some(&self.exchange),
+            keys([self.order_id.as_ref()]),
+            keys([self.client_oid.as_ref()]),
+            limit,
+        )
+    }
+}
+
+#[ComplexObject]
+impl MsgEvent {
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    /// The messages this event answers.
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn msgsends(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<MsgSend>> {
+        state(ctx)
+            .msgsend_service
+            .get_linked_msgsends(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+}
+
+impl Symbol {
+    fn link(&self, limit: i64) -> LinkFilter {
+        LinkFilter {
+            exchange: Some(self.exchange.clone()),
+            symbols: vec![self.symbol.clone()],
+            limit,
+            ..Default::default()
+        }
+    }
+}
+
+#[ComplexObject]
+impl Symbol {
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn bots(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<Bot>> {
+        state(ctx)
+            .bot_service
+            .get_linked_bots(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    #[graphql(complexity = "list_cost(limit, child_complexity)")]
+    async fn orders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_LIMIT")] limit: i32,
+    ) -> Result<Vec<EventOrder>> {
+        state(ctx)
+            .order_service
+            .get_linked_event_orders(&self.link(row_limit(limit)?))
+            .await
+            .map_err(service_error)
+    }
+
+    async fn base_currency_info(&self, ctx: &Context<'_>) -> Result<Option<Currency>> {
+        find_currency(ctx, &self.exchange, &self.base_currency).await
+    }
+
+    async fn quote_currency_info(&self, ctx: &Context<'_>) -> Result<Option<Currency>> {
+        find_currency(ctx, &self.exchange, &self.quote_currency).await
+    }
+}
@@ -1,7 +1,9 @@
+use async_graphql::SimpleObject;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Ticker {
     pub exchange: String,
     pub symbol: String,
@@ -12,7 +14,8 @@ pub struct Ticker {
     pub maker_coefficient: Option<String>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct EventOrder {
     pub exchange: String,
     pub status: String,
@@ -39,7 +42,8 @@ pub struct EventOrder {
     pub ts: i64,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct Symbol {
     pub exchange: String,
     pub symbol: String,
@@ -65,7 +69,35 @@ pub struct Symbol {
     pub st: bool,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+impl Symbol {
+    /// Column names, as used by universe rules.
+    pub const FIELDS: &[&str] = &[
+        "exchange",
+        "symbol",
+        "symbol_name",
+        "base_currency",
+        "quote_currency",
+        "fee_currency",
+        "market",
+        "base_min_size",
+        "quote_min_size",
+        "base_max_size",
+        "quote_max_size",
+        "base_increment",
+        "quote_increment",
+        "price_increment",
+        "price_limit_rate",
+        "min_funds",
+        "is_margin_enabled",
+        "enable_trading",
+        "fee_category",
+        "maker_fee_coefficient",
+        "taker_fee_coefficient",
+        "st",
+        "updated_at",
+    ];
+}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
 pub struct Currency {
     pub exchange: String,
     pub currency: String,
@@ -119,6 +151,102 @@ pub struct PgStatTableSize {
     pub total_size: String,
     pub table_size: String,
     pub indexes_size: String,
+    pub total_bytes: i64,
+}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PgStatSnapshot {
+    pub id: i64,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgStatSnapshotStatement {
+    pub queryid: i64,
+    pub query: String,
+    pub calls: i64,
+    pub total_exec_time: f64,
+    pub rows: i64,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgStatSnapshotTable {
+    pub schemaname: String,
+    pub relname: String,
+    pub seq_scan: i64,
+    pub idx_scan: i64,
+    pub n_tup_ins: i64,
+    pub n_tup_upd: i64,
+    pub n_tup_del: i64,
+    pub n_live_tup: i64,
+    pub n_dead_tup: i64,
+    pub total_bytes: i64,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgReplicationStat {
+    pub application_name: String,
+    pub client_addr: Option<String>,
+    pub state: Option<String>,
+    pub sync_state: Option<String>,
+    pub write_lag_secs: Option<f64>,
+    pub flush_lag_secs: Option<f64>,
+    pub replay_lag_secs: Option<f64>,
+    pub replay_lag_bytes: Option<i64>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgReplicationSlot {
+    pub slot_name: String,
+    pub slot_type: String,
+    pub active: bool,
+    pub wal_status: Option<String>,
+    pub retained_bytes: Option<i64>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgWalStatus {
+    pub server: String,
+    pub in_recovery: bool,
+    pub current_lsn: Option<String>,
+    pub replay_delay_secs: Option<f64>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PgArchiverStatus {
+    pub archive_mode: String,
+    pub archived_count: i64,
+    pub last_archived_wal: Option<String>,
+    pub last_archived_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_count: i64,
+    pub last_failed_wal: Option<String>,
+    pub last_failed_time: Option<chrono::DateTime<chrono::Utc>>,
+}
+/// One element of `EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON)` output.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PgExplain {
+    #[serde(rename = "Plan")]
+    pub plan: PgPlanNode,
+    #[serde(rename = "Planning Time")]
+    pub planning_time: f64,
+    #[serde(rename = "Execution Time")]
+    pub execution_time: f64,
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PgPlanNode {
+    #[serde(rename = "Node Type")]
+    pub node_type: String,
+    #[serde(rename = "Relation Name")]
+    pub relation_name: Option<String>,
+    #[serde(rename = "Index Name")]
+    pub index_name: Option<String>,
+    #[serde(rename = "Plan Rows")]
+    pub plan_rows: f64,
+    #[serde(rename = "Actual Rows", default)]
+    pub actual_rows: f64,
+    #[serde(rename = "Actual Loops", default)]
+    pub actual_loops: f64,
+    #[serde(rename = "Actual Total Time", default)]
+    pub actual_total_time: f64,
+    #[serde(rename = "Shared Hit Blocks", default)]
+    pub shared_hit_blocks: i64,
+    #[serde(rename = "Shared Read Blocks", default)]
+    pub shared_read_blocks: i64,
+    #[serde(rename = "Plans", default)]
+    pub plans: Vec<PgPlanNode>,
 }
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Event {
@@ -141,6 +269,13 @@ pub struct PositionRatio {
     pub total_debt: String,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+/// A row of a table without a key, with its `ctid` to page on.
+#[derive(Debug, FromRow)]
+pub struct StreamRow<T> {
+    #[sqlx(flatten)]
+    pub row: T,
+    pub row_id: String,
+}
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct PositionDebt {
     pub exchange: String,
@@ -157,8 +292,44 @@ pub struct PositionAsset {
     pub asset_hold: String,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-
+/// A borrow or repay reconstructed from the order flow. `size` is in base
+/// currency; a repay in the quote currency also carries the match `price`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LoanEvent {
+    pub exchange: String,
+    pub kind: String,
+    pub currency: String,
+    pub size: Option<String>,
+    pub price: Option<String>,
+    pub symbol: Option<String>,
+    pub side: Option<String>,
+    pub client_oid: Option<String>,
+    pub loan_apply_id: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+/// One series over one time bucket. `key` is the metric for position ratios
+/// and the currency for debts and assets.
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct PositionBucket {
+    pub bucket: chrono::DateTime<chrono::Utc>,
+    pub exchange: String,
+    pub key: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub avg: Option<f64>,
+    pub last: Option<f64>,
+    pub samples: i64,
+}
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LastPrice {
+    pub exchange: String,
+    pub symbol: String,
+    pub price: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct Balance {
     pub exchange: String,
     pub account_id: String,
@@ -176,7 +347,8 @@ pub struct Balance {
     pub trade_id: Option<String>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct MsgSend {
     pub exchange: String,
     pub args_symbol: Option<String>,
@@ -192,7 +364,8 @@ pub struct MsgSend {
     pub args_order_id: Option<String>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct Bot {
     pub exchange: Option<String>,
     pub entry_client_oid: Option<String>,
@@ -207,7 +380,113 @@ pub struct Bot {
     pub balance: Option<String>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+/// A note attached to exactly one of a bot, an order or a time range.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub bot_symbol: Option<String>,
+    pub order_id: Option<String>,
+    pub range_start: Option<chrono::DateTime<chrono::Utc>>,
+    pub range_end: Option<chrono::DateTime<chrono::Utc>>,
+    pub note: String,
+    pub tags: Vec<String>,
+}
+#[derive(Debug)]
+pub struct NewJournalEntry {
+    pub bot_symbol: Option<String>,
+    pub order_id: Option<String>,
+    pub range: Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)>,
+    pub note: String,
+    pub tags: Vec<String>,
+}
+/// A stored report without its bodies, for listing.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ReportEntry {
+    pub id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub period: String,
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub delivery_error: Option<String>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Report {
+    pub id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub period: String,
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub html: String,
+    pub markdown: String,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub delivery_error: Option<String>,
+}
+#[derive(Debug)]
+pub struct NewReport {
+    pub period: String,
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub html: String,
+    pub markdown: String,
+    pub tables: Vec<TableBytes>,
+}
+/// Size of a table when a report was generated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableBytes {
+    pub schemaname: String,
+    pub relname: String,
+    pub total_bytes: i64,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ErrorCount {
+    pub exchange: String,
+    pub msg: Option<String>,
+    pub count: i64,
+    pub last_at: chrono::DateTime<chrono::Utc>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DebtRatioRange {
+    pub exchange: String,
+    pub min_ratio: Option<f64>,
+    pub max_ratio: Option<f64>,
+    pub avg_ratio: Option<f64>,
+    pub samples: i64,
+}
+/// Rate limit hits of an exchange, from one table.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RateLimitIncidents {
+    pub exchange: String,
+    /// `msgevent` or `errors`.
+    pub source: String,
+    pub count: i64,
+    pub first_at: chrono::DateTime<chrono::Utc>,
+    pub last_at: chrono::DateTime<chrono::Utc>,
+}
+/// A saved table page preset; `query` is the page's query string.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TableView {
+    pub id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub page: String,
+    pub name: String,
+    pub query: String,
+    pub is_default: bool,
+}
+/// Latest `orderevent` of an order referenced by a bot.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct BotOrderEvent {
+    pub exchange: String,
+    pub client_oid: Option<String>,
+    pub order_id: String,
+    pub type_: Option<String>,
+    pub status: Option<String>,
+    pub filled_size: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct MsgEvent {
     pub exchange: String,
     pub msg: Option<String>,
@@ -223,3 +502,123 @@ pub struct MsgEvent {
     pub out_time: f64,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SymbolCatalogSnapshot {
+    pub id: i64,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub symbol_count: i32,
+}
+/// One `symbol` row with every column except `updated_at`, keyed by column.
+#[derive(Debug, FromRow)]
+pub struct SymbolCatalogEntry {
+    pub exchange: String,
+    pub symbol: String,
+    pub fields: sqlx::types::Json<serde_json::Map<String, serde_json::Value>>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SymbolChange {
+    pub id: i64,
+    pub snapshot_id: i64,
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub exchange: String,
+    pub symbol: String,
+    pub kind: String,
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+/// A change detected by the catalog diff, before it is stored.
+#[derive(Debug)]
+pub struct NewSymbolChange {
+    pub exchange: String,
+    pub symbol: String,
+    pub kind: &'static str,
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SymbolVolume {
+    pub exchange: String,
+    pub symbol: String,
+    pub quote_volume: f64,
+}
+/// A symbol's fee inputs: base rates and coefficients from `ticker`, the
+/// symbol's own coefficients as a fallback.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FeeSchedule {
+    pub exchange: String,
+    pub symbol: String,
+    pub fee_category: i16,
+    pub fee_currency: String,
+    pub quote_currency: String,
+    pub taker_fee_rate: Option<String>,
+    pub maker_fee_rate: Option<String>,
+    pub taker_coefficient: Option<String>,
+    pub maker_coefficient: Option<String>,
+    pub taker_fee_coefficient: String,
+    pub maker_fee_coefficient: String,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ChargedFee {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: String,
+    pub liquidity: Option<String>,
+    pub trade_id: String,
+    pub match_size: Option<String>,
+    pub match_price: Option<String>,
+    /// Signed quote-currency change of the settlement.
+    pub available_change: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MsgSendOutcome {
+    pub exchange: String,
+    pub args_symbol: Option<String>,
+    pub args_side: Option<String>,
+    pub args_size: Option<String>,
+    pub args_funds: Option<String>,
+    pub args_price: Option<String>,
+    pub args_type: Option<String>,
+    pub args_client_oid: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub response_code: Option<String>,
+    pub response_msg: Option<String>,
+    /// Last match price of the symbol when the order was sent.
+    pub reference_price: Option<String>,
+}
+/// Per-exchange activity: the latest position ratio, bot count, errors and
+/// events within the summary window, and the last update across the
+/// exchange's tables.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ExchangeActivity {
+    pub exchange: String,
+    pub total_asset: Option<f64>,
+    pub total_debt: Option<String>,
+    pub bots: i64,
+    pub errors: i64,
+    pub events: i64,
+    pub last_update: Option<chrono::DateTime<chrono::Utc>>,
+}
+/// Rows matching a search term, by table.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResults {
+    pub term: String,
+    pub orders: Vec<EventOrder>,
+    pub msgsends: Vec<MsgSend>,
+    pub msgevents: Vec<MsgEvent>,
+    pub balances: Vec<Balance>,
+    pub bots: Vec<Bot>,
+    pub symbols: Vec<Symbol>,
+}
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+            && self.msgsends.is_empty()
+            && self.msgevents.is_empty()
+            && self.balances.is_empty()
+            && self.bots.is_empty()
+            && self.symbols.is_empty()
+    }
+}
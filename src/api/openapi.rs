@@ -0,0 +1,150 @@
+use crate::handlers::{bots, exchanges, fees, orders, position, search, sources, symbol};
+use actix_web::{Route, web};
+use utoipa::{Modify, OpenApi};
+
+/// A path and the route it is served by.
+pub type ApiRoute = (&'static str, fn() -> Route);
+
+/// The JSON API routes, each of which [`ApiDoc`] documents.
+pub const API_ROUTES: &[ApiRoute] = &[
+    ("/api/exchanges", || {
+        web::get().to(exchanges::exchanges_json)
+    }),
+    ("/api/search", || web::get().to(search::search_json)),
+    ("/api/sources", || web::get().to(sources::sources_json)),
+    ("/api/orders/validate", || {
+        web::get().to(orders::validate_order)
+    }),
+    ("/api/positions/history", || {
+        web::get().to(position::position_history)
+    }),
+    ("/api/symbols/changes", || {
+        web::get().to(symbol::symbol_changes_json)
+    }),
+    ("/api/bots/performance", || {
+        web::get().to(bots::bot_performance_json)
+    }),
+    ("/api/fees/calculate", || {
+        web::get().to(fees::fee_calculator)
+    }),
+];
+
+/// Registers [`API_ROUTES`].
+pub fn api_routes(cfg: &mut web::ServiceConfig) {
+    for (path, route) in API_ROUTES {
+        cfg.route(path, route());
+    }
+}
+
+/// Contract of the JSON API under `/api`, served at `/api/openapi.json` and
+/// browsable at `/api/docs/`. Source prefixes serve the same routes.
+#[derive(OpenApi)]
+#[openapi(
+    info(description = "Read-only JSON API over the trading bot's database."),
+    paths(
+        exchanges::exchanges_json,
+        sources::sources_json,
+        search::search_json,
+        bots::bot_performance_json,
+        orders::validate_order,
+        fees::fee_calculator,
+        position::position_history,
+        symbol::symbol_changes_json,
+    ),
+    modifiers(&NoLicense)
+)]
+pub struct ApiDoc;
+
+/// The crate declares no license, which would otherwise be emitted as one
+/// with an empty name.
+struct NoLicense;
+
+impl Modify for NoLicense {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .info
+            .license
+            .take_if(|license| license.name.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{API_ROUTES, ApiDoc};
+    use actix_web::App;
+    use actix_web::http::{Method, StatusCode};
+    use actix_web::test::{TestRequest, call_service, init_service};
+    use serde_json::Value;
+    use utoipa::OpenApi;
+
+    fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    refs.push(r);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_api_route_is_documented() {
+        let spec = ApiDoc::openapi();
+        for (route, _) in API_ROUTES {
+            assert!(
+                spec.paths.paths.contains_key(*route),
+                "{} is routed but missing from the OpenAPI spec",
+                route
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn every_documented_operation_is_routed() {
+        // Without app data every handler fails, but not with 404.
+        let app = init_service(App::new().configure(crate::routes)).await;
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let uri = path.replace(['{', '}'], "");
+            let methods = [
+                (Method::GET, item.get.is_some()),
+                (Method::POST, item.post.is_some()),
+                (Method::PUT, item.put.is_some()),
+                (Method::DELETE, item.delete.is_some()),
+            ];
+            for (method, _) in methods.into_iter().filter(|(_, documented)| *documented) {
+                let req = TestRequest::default()
+                    .method(method.clone())
+                    .uri(&uri)
+                    .to_request();
+                let status = call_service(&app, req).await.status();
+                assert_ne!(
+                    status,
+                    StatusCode::NOT_FOUND,
+                    "{} {} is documented but not routed",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        for r in refs {
+            let name = r
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected reference {}", r));
+            assert!(
+                spec["components"]["schemas"].get(name).is_some(),
+                "{} is referenced but has no schema",
+                name
+            );
+        }
+    }
+}
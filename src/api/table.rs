@@ -0,0 +1,712 @@
+use crate::api::models::{
+    Balance, Error, Event, MsgEvent, MsgSend, PositionAsset, PositionDebt, PositionRatio, Symbol,
+};
+use crate::core::{display, exchange, source};
+use crate::repositories::{TABLE_PAGE_LIMIT, TableFilter, TableSort};
+use crate::services::journal_service::AnnotatedOrder;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    /// Exact decimal kept as the exchange sent it, sorted numerically.
+    Decimal,
+    Float,
+    Integer,
+    Bool,
+    Timestamp,
+    /// Exchange name, shown by its configured display name.
+    Exchange,
+    /// Epoch time in any unit from seconds to nanoseconds, shown as a
+    /// timestamp.
+    Epoch,
+}
+
+/// How a model field is shown in a generic table.
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnType,
+    pub nullable: bool,
+    pub sortable: bool,
+    /// Path the value links to, `{}` replaced by the url-encoded value.
+    pub link: Option<&'static str>,
+    /// Not selected by the page's query but added to its rows afterwards,
+    /// so filtered in memory.
+    pub derived: bool,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: ColumnType) -> Self {
+        Self {
+            name,
+            kind,
+            nullable: false,
+            sortable: true,
+            link: None,
+            derived: false,
+        }
+    }
+
+    pub const fn nullable(self) -> Self {
+        Self {
+            nullable: true,
+            ..self
+        }
+    }
+
+    pub const fn unsortable(self) -> Self {
+        Self {
+            sortable: false,
+            ..self
+        }
+    }
+
+    pub const fn link(self, path: &'static str) -> Self {
+        Self {
+            link: Some(path),
+            ..self
+        }
+    }
+
+    pub const fn derived(self) -> Self {
+        Self {
+            derived: true,
+            ..self
+        }
+    }
+
+    /// Type shown in the column header's tooltip.
+    pub fn describe(&self) -> String {
+        let kind = match self.kind {
+            ColumnType::Text => "text",
+            ColumnType::Decimal => "decimal",
+            ColumnType::Float => "float",
+            ColumnType::Integer => "integer",
+            ColumnType::Bool => "bool",
+            ColumnType::Timestamp => "timestamp",
+            ColumnType::Exchange => "exchange",
+            ColumnType::Epoch => "epoch time",
+        };
+        if self.nullable {
+            format!("{}, nullable", kind)
+        } else {
+            kind.to_string()
+        }
+    }
+
+    fn cell(&self, value: &Value) -> Cell {
+        let number = match value {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        };
+        let text = match (value, self.kind) {
+            (Value::Null, _) => String::new(),
+            (Value::String(s), ColumnType::Timestamp) => DateTime::parse_from_rfc3339(s)
+                .map(|t| display::timestamp(&t.with_timezone(&Utc)))
+                .unwrap_or_else(|_| s.clone()),
+            (_, ColumnType::Epoch) => number
+                .and_then(display::from_epoch)
+                .map(|t| display::timestamp(&t))
+                .unwrap_or_else(|| raw_text(value)),
+            (Value::String(s), ColumnType::Exchange) => exchange::display_name(s).to_string(),
+            (_, ColumnType::Decimal | ColumnType::Float | ColumnType::Integer) => {
+                display::number(&raw_text(value))
+            }
+            _ => raw_text(value),
+        };
+        let link = self
+            .link
+            .filter(|_| !text.is_empty())
+            .map(|path| source::url(&path.replace("{}", &url_encode(&text))));
+
+        Cell {
+            sort: match (value, self.kind) {
+                (Value::Null, _) => SortKey::Null,
+                (Value::Number(n), _) => SortKey::Number(n.as_f64().unwrap_or(f64::NAN)),
+                (
+                    Value::String(s),
+                    ColumnType::Decimal | ColumnType::Integer | ColumnType::Epoch,
+                ) => number.map_or_else(|| SortKey::Text(s.clone()), SortKey::Number),
+                (Value::String(s), _) => SortKey::Text(s.clone()),
+                (other, _) => SortKey::Text(other.to_string()),
+            },
+            text,
+            link,
+        }
+    }
+}
+
+/// A page rendered through the generic table. Its route also keys the
+/// page's saved views.
+#[derive(Debug, Clone, Copy)]
+pub struct TablePage {
+    pub path: &'static str,
+    pub title: &'static str,
+    /// Columns of the page's rows, to check saved views against.
+    pub columns: &'static [Column],
+    /// Rows come from a query capped at [`TABLE_PAGE_LIMIT`] rows, which
+    /// runs the filters and sort itself so they cover the whole table.
+    /// Other pages list every row and are filtered and sorted in memory.
+    pub limited: bool,
+}
+
+/// A model listed through the generic table. Cells are read from the
+/// model's serialized fields, so the column names are the field names.
+pub trait TableRow: Serialize {
+    const COLUMNS: &'static [Column];
+
+    /// Link for the row's cell in `column` that depends on more than the
+    /// cell's own value, overriding the column's link.
+    fn link(&self, _column: &str) -> Option<String> {
+        None
+    }
+}
+
+pub struct Cell {
+    pub text: String,
+    pub link: Option<String>,
+    sort: SortKey,
+}
+
+#[derive(PartialEq, PartialOrd)]
+enum SortKey {
+    Number(f64),
+    Text(String),
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// `?columns=a,b&sort=a&order=desc&filter=a:x`, shared by every table page.
+/// Columns are shown in the order listed.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableQuery {
+    pub columns: Option<String>,
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// `column:text` pairs, comma separated. Keeps the rows whose value in
+    /// each column contains the text, case-insensitive. Limited pages match
+    /// the stored value, others the cell as shown.
+    pub filter: Option<String>,
+    /// Saved view to show. Empty shows the full table, skipping the page's
+    /// default view.
+    pub view: Option<String>,
+}
+
+impl TableQuery {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        actix_web::web::Query::<Self>::from_query(query)
+            .map(|q| q.into_inner())
+            .map_err(|e| e.to_string())
+    }
+
+    /// No columns, sort or filter given.
+    pub fn is_empty(&self) -> bool {
+        [&self.columns, &self.sort, &self.filter]
+            .iter()
+            .all(|v| v.as_deref().is_none_or(|v| v.trim().is_empty()))
+    }
+
+    /// Columns, sort and filter as a query string, without the `?`.
+    pub fn to_query_string(&self) -> String {
+        let mut pairs = Vec::new();
+        if let Some(columns) = &self.columns {
+            pairs.push(format!("columns={}", url_encode(columns)));
+        }
+        if let Some(sort) = &self.sort {
+            pairs.push(format!(
+                "sort={}&order={}",
+                url_encode(sort),
+                self.order.as_str()
+            ));
+        }
+        if let Some(filter) = &self.filter {
+            pairs.push(format!("filter={}", url_encode(filter)));
+        }
+        pairs.join("&")
+    }
+
+    /// Rejects what [`Table::build`] would for a table of `columns`.
+    pub fn check(&self, columns: &'static [Column]) -> Result<(), String> {
+        Selection::new(columns, self).map(|_| ())
+    }
+
+    /// The filters and sort a limited page's query runs. Those on derived
+    /// columns are left to [`Table::build`].
+    pub fn table_filter(&self, columns: &'static [Column]) -> Result<TableFilter, String> {
+        let Selection { sort, filters, .. } = Selection::new(columns, self)?;
+
+        Ok(TableFilter {
+            contains: filters
+                .into_iter()
+                .filter(|(c, _)| !c.derived)
+                .map(|(c, text)| (c.name, text))
+                .collect(),
+            sort: sort.filter(|c| !c.derived).map(|c| TableSort {
+                column: c.name,
+                numeric: matches!(c.kind, Decimal | Float | Integer | Epoch),
+                descending: self.order == SortOrder::Desc,
+            }),
+            limit: TABLE_PAGE_LIMIT,
+        })
+    }
+}
+
+/// The columns, sort column and filters a query picks out of `all`.
+struct Selection {
+    columns: Vec<&'static Column>,
+    sort: Option<&'static Column>,
+    filters: Vec<(&'static Column, String)>,
+}
+
+impl Selection {
+    /// Unknown or unsortable columns and malformed filters are rejected.
+    fn new(all: &'static [Column], query: &TableQuery) -> Result<Self, String> {
+        let find = |name: &str| {
+            all.iter()
+                .find(|c| c.name == name)
+                .ok_or_else(|| format!("unknown column {}", name))
+        };
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let columns = match non_empty(&query.columns) {
+            Some(names) => names
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(find)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => all.iter().collect(),
+        };
+
+        let sort = match non_empty(&query.sort) {
+            Some(name) => {
+                let column = find(&name)?;
+                if !column.sortable {
+                    return Err(format!("column {} is not sortable", name));
+                }
+                Some(column)
+            }
+            None => None,
+        };
+
+        let filters = match non_empty(&query.filter) {
+            Some(filter) => filter
+                .split(',')
+                .filter(|f| !f.trim().is_empty())
+                .map(|f| {
+                    let (name, text) = f
+                        .split_once(':')
+                        .ok_or_else(|| format!("filter {} is not column:text", f))?;
+                    Ok((find(name.trim())?, text.trim().to_lowercase()))
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            columns,
+            sort,
+            filters,
+        })
+    }
+}
+
+pub struct Table {
+    pub all_columns: &'static [Column],
+    pub columns: Vec<&'static Column>,
+    pub rows: Vec<Vec<Cell>>,
+    /// The query the table was built from, with defaults left out.
+    pub query: TableQuery,
+    /// A limited page's query returned its full [`TABLE_PAGE_LIMIT`] rows.
+    pub truncated: bool,
+    /// Filters on derived columns ran on a truncated page's rows only.
+    pub partially_filtered: bool,
+}
+
+impl Table {
+    /// Rows in the model's order unless sorted. Unknown or unsortable
+    /// columns and malformed filters are rejected. A `limited` page's rows
+    /// were already filtered and sorted by [`TableQuery::table_filter`].
+    pub fn build<T: TableRow>(
+        rows: &[T],
+        query: &TableQuery,
+        limited: bool,
+    ) -> Result<Self, String> {
+        let Selection {
+            columns,
+            sort,
+            filters,
+        } = Selection::new(T::COLUMNS, query)?;
+        let in_memory = |c: &&Column| !limited || c.derived;
+        let memory_sort = sort.filter(in_memory);
+        let memory_filters: Vec<_> = filters.iter().filter(|(c, _)| in_memory(c)).collect();
+        let truncated = limited && rows.len() as i64 >= TABLE_PAGE_LIMIT;
+
+        let mut keyed: Vec<(Option<SortKey>, Vec<Cell>)> = rows
+            .iter()
+            .filter_map(|row| {
+                let value = serde_json::to_value(row).unwrap_or(Value::Null);
+                let cell = |c: &Column| {
+                    let mut cell = c.cell(value.get(c.name).unwrap_or(&Value::Null));
+                    if let Some(link) = row.link(c.name) {
+                        cell.link = Some(link);
+                    }
+                    cell
+                };
+                let keep = memory_filters
+                    .iter()
+                    .all(|(c, text)| cell(c).text.to_lowercase().contains(text.as_str()));
+                keep.then(|| {
+                    let key = memory_sort.map(|c| cell(c).sort);
+                    (key, columns.iter().map(|c| cell(c)).collect())
+                })
+            })
+            .collect();
+
+        if memory_sort.is_some() {
+            keyed.sort_by(|(a, _), (b, _)| {
+                let ordering = a.partial_cmp(b).unwrap_or(Ordering::Equal);
+                match (query.order, a, b) {
+                    // Nulls stay last either way.
+                    (_, Some(SortKey::Null), _) | (_, _, Some(SortKey::Null)) => ordering,
+                    (SortOrder::Desc, _, _) => ordering.reverse(),
+                    (SortOrder::Asc, _, _) => ordering,
+                }
+            });
+        }
+
+        let mut table = Self {
+            all_columns: T::COLUMNS,
+            columns,
+            rows: keyed.into_iter().map(|(_, cells)| cells).collect(),
+            query: TableQuery {
+                columns: None,
+                sort: sort.map(|c| c.name.to_string()),
+                order: query.order,
+                filter: (!filters.is_empty()).then(|| {
+                    filters
+                        .iter()
+                        .map(|(c, text)| format!("{}:{}", c.name, text))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }),
+                view: None,
+            },
+            truncated,
+            partially_filtered: truncated && !memory_filters.is_empty(),
+        };
+        table.query.columns = table.columns_param(&table.shown_names());
+        Ok(table)
+    }
+
+    pub fn is_shown(&self, name: &str) -> bool {
+        self.columns.iter().any(|c| c.name == name)
+    }
+
+    pub fn hidden_columns(&self) -> Vec<&'static Column> {
+        self.all_columns
+            .iter()
+            .filter(|c| !self.is_shown(c.name))
+            .collect()
+    }
+
+    fn shown_names(&self) -> Vec<&'static str> {
+        self.columns.iter().map(|c| c.name).collect()
+    }
+
+    /// `None` when `names` are all the columns in the model's order.
+    fn columns_param(&self, names: &[&str]) -> Option<String> {
+        let all = self.all_columns.iter().map(|c| c.name);
+        (!names.iter().copied().eq(all)).then(|| names.join(","))
+    }
+
+    fn link_with(&self, names: &[&str], sort: Option<(&str, SortOrder)>) -> String {
+        let query = TableQuery {
+            columns: self.columns_param(names),
+            sort: sort
+                .filter(|(s, _)| names.contains(s))
+                .map(|(s, _)| s.to_string()),
+            order: sort.map_or(SortOrder::Asc, |(_, o)| o),
+            filter: self.query.filter.clone(),
+            view: None,
+        };
+        format!("?{}", query.to_query_string())
+    }
+
+    fn current_sort(&self) -> Option<(&str, SortOrder)> {
+        self.query.sort.as_deref().map(|s| (s, self.query.order))
+    }
+
+    /// Link sorting by `name`, flipping the order if already sorted by it.
+    pub fn sort_link(&self, name: &str) -> String {
+        let order = match self.current_sort() {
+            Some((sorted, SortOrder::Asc)) if sorted == name => SortOrder::Desc,
+            _ => SortOrder::Asc,
+        };
+        self.link_with(&self.shown_names(), Some((name, order)))
+    }
+
+    /// Link hiding `name`, or showing it as the last column.
+    pub fn toggle_link(&self, name: &str) -> String {
+        let mut names = self.shown_names();
+        if self.is_shown(name) {
+            names.retain(|c| *c != name);
+        } else if let Some(column) = self.all_columns.iter().find(|c| c.name == name) {
+            names.push(column.name);
+        }
+        self.link_with(&names, self.current_sort())
+    }
+
+    /// Link swapping `name` with its left or right neighbour, `None` at
+    /// the edge.
+    pub fn move_link(&self, name: &str, left: bool) -> Option<String> {
+        let mut names = self.shown_names();
+        let index = names.iter().position(|c| *c == name)?;
+        let other = if left {
+            index.checked_sub(1)?
+        } else {
+            Some(index + 1).filter(|i| *i < names.len())?
+        };
+        names.swap(index, other);
+        Some(self.link_with(&names, self.current_sort()))
+    }
+
+    pub fn sort_marker(&self, name: &str) -> &'static str {
+        match self.current_sort() {
+            Some((sorted, SortOrder::Asc)) if sorted == name => " ▲",
+            Some((sorted, SortOrder::Desc)) if sorted == name => " ▼",
+            _ => "",
+        }
+    }
+}
+
+fn raw_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn url_encode(value: &str) -> String {
+    value.bytes().fold(String::new(), |mut out, b| {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' | b':' => {
+                out.push(b as char)
+            }
+            _ => {
+                let _ = write!(out, "%{:02X}", b);
+            }
+        }
+        out
+    })
+}
+
+use ColumnType::{Bool, Decimal, Epoch, Exchange, Float, Integer, Text, Timestamp};
+
+const SEARCH: &str = "/search?q={}";
+
+impl TableRow for Error {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("msg", Text).unsortable(),
+    ];
+}
+
+impl TableRow for Event {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("msg", Text).unsortable(),
+    ];
+}
+
+impl TableRow for MsgEvent {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("msg", Text).nullable().unsortable(),
+        Column::new("code", Text).nullable(),
+        Column::new("borrow_size", Decimal).nullable(),
+        Column::new("client_oid", Text).nullable().link(SEARCH),
+        Column::new("order_id", Text).nullable().link(SEARCH),
+        Column::new("loan_apply_id", Text).nullable().link(SEARCH),
+        Column::new("limit_rate", Float).nullable(),
+        Column::new("reset_rate", Float).nullable(),
+        Column::new("remaining_rate", Float).nullable(),
+        Column::new("in_time", Epoch),
+        Column::new("out_time", Epoch),
+    ];
+}
+
+impl TableRow for MsgSend {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("args_symbol", Text).nullable().link(SEARCH),
+        Column::new("args_side", Text).nullable(),
+        Column::new("args_size", Decimal).nullable(),
+        Column::new("args_funds", Decimal).nullable(),
+        Column::new("args_price", Decimal).nullable(),
+        Column::new("args_time_in_force", Text).nullable(),
+        Column::new("args_type", Text).nullable(),
+        Column::new("args_auto_borrow", Bool).nullable(),
+        Column::new("args_auto_repay", Bool).nullable(),
+        Column::new("args_client_oid", Text).nullable().link(SEARCH),
+        Column::new("args_order_id", Text).nullable().link(SEARCH),
+    ];
+}
+
+impl TableRow for Balance {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("account_id", Text),
+        Column::new("total", Decimal),
+        Column::new("available", Decimal),
+        Column::new("available_change", Decimal),
+        Column::new("currency", Text),
+        Column::new("hold_value", Decimal),
+        Column::new("hold_change", Decimal),
+        Column::new("relation_event", Text),
+        Column::new("relation_event_id", Text).link(SEARCH),
+        Column::new("event_time", Epoch),
+        Column::new("symbol", Text).nullable().link(SEARCH),
+        Column::new("order_id", Text).nullable().link(SEARCH),
+        Column::new("trade_id", Text).nullable().link(SEARCH),
+    ];
+}
+
+impl TableRow for PositionAsset {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("asset_symbol", Text),
+        Column::new("asset_total", Decimal),
+        Column::new("asset_available", Decimal),
+        Column::new("asset_hold", Decimal),
+    ];
+}
+
+impl TableRow for PositionDebt {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("debt_symbol", Text),
+        Column::new("debt_value", Decimal),
+    ];
+}
+
+impl TableRow for PositionRatio {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("debt_ratio", Float),
+        Column::new("total_asset", Float),
+        Column::new("margin_coefficient_total_asset", Decimal),
+        Column::new("total_debt", Decimal),
+    ];
+}
+
+impl TableRow for AnnotatedOrder {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("notes", Text).nullable().unsortable().derived(),
+        Column::new("exchange", Exchange),
+        Column::new("status", Text),
+        Column::new("type_", Text),
+        Column::new("symbol", Text).link(SEARCH),
+        Column::new("side", Text),
+        Column::new("order_type", Text),
+        Column::new("price", Decimal).nullable(),
+        Column::new("size", Decimal).nullable(),
+        Column::new("fee_type", Text).nullable(),
+        Column::new("liquidity", Text).nullable(),
+        Column::new("order_id", Text).link(SEARCH),
+        Column::new("client_oid", Text).nullable().link(SEARCH),
+        Column::new("origin_size", Decimal).nullable(),
+        Column::new("filled_size", Decimal).nullable(),
+        Column::new("match_size", Decimal).nullable(),
+        Column::new("match_price", Decimal).nullable(),
+        Column::new("canceled_size", Decimal).nullable(),
+        Column::new("old_size", Decimal).nullable(),
+        Column::new("remain_size", Decimal).nullable(),
+        Column::new("remain_funds", Decimal).nullable(),
+        Column::new("trade_id", Text).nullable(),
+        Column::new("order_time", Epoch),
+        Column::new("ts", Epoch),
+    ];
+
+    /// Order ids link to the exchange's order page where configured, notes
+    /// to the order's journal entries.
+    fn link(&self, column: &str) -> Option<String> {
+        let order = &self.order;
+        match column {
+            "order_id" => exchange::order_link(&order.exchange, &order.symbol, &order.order_id),
+            "notes" => self.notes.as_ref().map(|_| {
+                source::url(&format!(
+                    "/journal?order_id={}",
+                    url_encode(&order.order_id)
+                ))
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl TableRow for Symbol {
+    const COLUMNS: &'static [Column] = &[
+        Column::new("updated_at", Timestamp),
+        Column::new("exchange", Exchange),
+        Column::new("symbol", Text).link(SEARCH),
+        Column::new("symbol_name", Text),
+        Column::new("base_currency", Text),
+        Column::new("quote_currency", Text),
+        Column::new("fee_currency", Text),
+        Column::new("market", Text),
+        Column::new("base_min_size", Decimal),
+        Column::new("quote_min_size", Decimal),
+        Column::new("base_max_size", Decimal),
+        Column::new("quote_max_size", Decimal),
+        Column::new("base_increment", Decimal),
+        Column::new("quote_increment", Decimal),
+        Column::new("price_increment", Decimal),
+        Column::new("price_limit_rate", Decimal),
+        Column::new("min_funds", Decimal).nullable(),
+        Column::new("is_margin_enabled", Bool),
+        Column::new("enable_trading", Bool),
+        Column::new("fee_category", Integer),
+        Column::new("maker_fee_coefficient", Decimal),
+        Column::new("taker_fee_coefficient", Decimal),
+        Column::new("st", Bool),
+    ];
+}
@@ -1,9 +1,27 @@
 use crate::api::models::{
-    Balance, Bot, Currency, Error, Event, EventOrder, MsgEvent, MsgSend, PgConnection,
-    PgStatStatements, PgStatTableSize, PgTableIndex, PgTableInfo, PositionAsset, PositionDebt,
-    PositionRatio, Symbol, Ticker,
+    Currency, JournalEntry, PgArchiverStatus, PgConnection, PgReplicationSlot, PgReplicationStat,
+    PgStatSnapshot, PgStatStatements, PgStatTableSize, PgTableIndex, PgTableInfo, PgWalStatus,
+    Report, ReportEntry, SearchResults, Symbol, SymbolCatalogSnapshot, SymbolChange, TableView,
+    Ticker,
 };
+use crate::api::table::{Table, TablePage};
+use crate::config::{MarginConfig, UniverseConfig};
+use crate::core::cache::CacheStats;
+use crate::core::display::{DisplaySettings, Locale};
+use crate::core::exchange::SelectedExchange;
+use crate::core::source::SourceOverview;
+use crate::services::bot_service::{BotsWithStats, PerformanceReport};
+use crate::services::exchange_service::ExchangeOverview;
+use crate::services::fee_service::{EffectiveFee, FeeComparison};
+use crate::services::journal_service::{JournalIndex, JournalQuery};
+use crate::services::loan_service::LoanLedger;
+use crate::services::margin_service::ExchangeRisk;
+use crate::services::order_validation_service::ValidationRetrospective;
+use crate::services::pg_service::{ExplainResult, PgSnapshotDiff};
+use crate::services::report_service::ReportContent;
+use crate::services::symbol_service::{UniverseSummary, Verdict};
 use askama::Template;
+use chrono_tz::Tz;
 
 #[derive(Template)]
 #[template(path = "tickers.html")]
@@ -11,13 +29,6 @@ pub struct TickersTemplate {
     pub tickers: Vec<(usize, Ticker)>,
     pub elapsed_ms: u128,
 }
-#[derive(Template)]
-#[template(path = "symbols.html")]
-pub struct SymbolsTemplate {
-    pub symbols: Vec<(usize, Symbol)>,
-    pub elapsed_ms: u128,
-}
-
 #[derive(Template)]
 #[template(path = "currencies.html")]
 pub struct CurrenciesTemplate {
@@ -35,67 +46,179 @@ pub struct PgTemplate {
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "events/events.html")]
-pub struct EventsTemplate {
-    pub events: Vec<Event>,
+#[template(path = "pg/snapshots.html")]
+pub struct PgSnapshotsTemplate {
+    pub snapshots: Vec<PgStatSnapshot>,
+    pub diff: Option<PgSnapshotDiff>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "pg/explain.html")]
+pub struct PgExplainTemplate {
+    pub detailed: bool,
+    /// Whether `EXPLAIN ANALYZE` ran; the plain listing only shows the SQL.
+    pub analyzed: bool,
+    pub results: Vec<ExplainResult>,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "errors/errors.html")]
-pub struct ErrorsTemplate {
-    pub errors: Vec<Error>,
+#[template(path = "pg/replication.html")]
+pub struct PgReplicationTemplate {
+    pub servers: Vec<PgWalStatus>,
+    pub replicas: Vec<PgReplicationStat>,
+    pub slots: Vec<PgReplicationSlot>,
+    pub archiver: Option<PgArchiverStatus>,
+    pub wal_rate: Option<f64>,
+    pub errors: Vec<String>,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "events/msgevents.html")]
-pub struct MsgEventTemplate {
-    pub msgevents: Vec<MsgEvent>,
+#[template(path = "bots/bots.html")]
+pub struct BotsTemplate {
+    pub stats: BotsWithStats,
+    pub journal: JournalIndex,
+    pub exchange: SelectedExchange,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "events/msgsend.html")]
-pub struct MsgSendTemplate {
-    pub msgsend: Vec<MsgSend>,
+#[template(path = "bots/performance.html")]
+pub struct BotPerformanceTemplate {
+    pub report: PerformanceReport,
+    pub exchange: SelectedExchange,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "bots/bots.html")]
-pub struct BotsTemplate {
-    pub bots: Vec<(usize, Bot)>,
-    pub init_balance: f64,
-    pub final_balance: f64,
+#[template(path = "position/margin.html")]
+pub struct MarginTemplate {
+    pub risks: Vec<ExchangeRisk>,
+    pub config: MarginConfig,
+    pub shock_pct: f64,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "position/positionratio.html")]
-pub struct PositinRatioTemplate {
-    pub position_ratio: Vec<PositionRatio>,
+#[template(path = "exchanges/exchanges.html")]
+pub struct ExchangesTemplate {
+    pub overview: ExchangeOverview,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "position/positiondebt.html")]
-pub struct PositionDebtTemplate {
-    pub position_debt: Vec<PositionDebt>,
+#[template(path = "search/search.html")]
+pub struct SearchTemplate {
+    pub results: SearchResults,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "position/positionasset.html")]
-pub struct PositionAssetTemplate {
-    pub position_asset: Vec<PositionAsset>,
+#[template(path = "sources/sources.html")]
+pub struct SourcesTemplate {
+    pub sources: Vec<SourceOverview>,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "orders/eventorders.html")]
-pub struct EventOrderTemplate {
-    pub event_orders: Vec<EventOrder>,
+#[template(path = "journal/journal.html")]
+pub struct JournalTemplate {
+    pub entries: Vec<JournalEntry>,
+    pub query: JournalQuery,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
-#[template(path = "balance/balance.html")]
-pub struct BalanceTemplate {
-    pub balances: Vec<Balance>,
+#[template(path = "loans/loans.html")]
+pub struct LoansTemplate {
+    pub ledger: LoanLedger,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "table/table.html")]
+pub struct TableTemplate {
+    pub page: TablePage,
+    pub table: Table,
+    pub views: Vec<TableView>,
+    /// Name of the saved view shown, if any.
+    pub active: Option<String>,
+    pub exchange: SelectedExchange,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "display/display.html")]
+pub struct DisplayTemplate {
+    pub settings: DisplaySettings,
+    pub default: DisplaySettings,
+    pub timezones: &'static [Tz],
+    pub locales: &'static [Locale],
+    /// Page to return to after saving.
+    pub back: String,
     pub elapsed_ms: u128,
 }
 #[derive(Template)]
 #[template(path = "index/index.html")]
 pub struct IndexTemplate {}
+#[derive(Template)]
+#[template(path = "cache/cache.html")]
+pub struct CacheTemplate {
+    pub caches: Vec<CacheStats>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "symbols/changes.html")]
+pub struct SymbolChangesTemplate {
+    pub latest: Option<SymbolCatalogSnapshot>,
+    pub changes: Vec<SymbolChange>,
+    pub symbol: String,
+    pub field: String,
+    pub kind: String,
+    pub kinds: &'static [&'static str],
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "symbols/universes.html")]
+pub struct UniversesTemplate {
+    pub universes: Vec<UniverseSummary>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "symbols/universe.html")]
+pub struct UniverseTemplate {
+    pub universe: UniverseConfig,
+    pub members: Vec<(usize, Symbol)>,
+    pub excluded: Vec<(Symbol, Verdict)>,
+    /// Symbol entered in the "why excluded" form.
+    pub explain: String,
+    pub explanation: Option<(Symbol, Verdict)>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "fees/fees.html")]
+pub struct FeesTemplate {
+    pub fees: Vec<EffectiveFee>,
+    pub comparisons: Vec<FeeComparison>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "orders/validation.html")]
+pub struct OrderValidationTemplate {
+    pub retrospective: ValidationRetrospective,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "reports/reports.html")]
+pub struct ReportsTemplate {
+    pub reports: Vec<ReportEntry>,
+    pub elapsed_ms: u128,
+}
+#[derive(Template)]
+#[template(path = "reports/report.html")]
+pub struct ReportTemplate {
+    pub report: Report,
+    pub has_webhook: bool,
+    pub elapsed_ms: u128,
+}
+/// A report's stored HTML, shown inside [`ReportTemplate`].
+#[derive(Template)]
+#[template(path = "reports/content.html")]
+pub struct ReportHtmlTemplate<'a> {
+    pub content: &'a ReportContent,
+}
+#[derive(Template)]
+#[template(path = "reports/content.md")]
+pub struct ReportMarkdownTemplate<'a> {
+    pub content: &'a ReportContent,
+}
@@ -1,12 +1,83 @@
+use crate::api::models::Symbol;
+use crate::core::display::Locale;
+use crate::services::report_service::ReportPeriod;
 use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 
+/// Repository names accepted in `DB_POOL_ASSIGNMENTS`.
+const REPOSITORY_NAMES: &[&str] = &[
+    "balance",
+    "bots",
+    "currency",
+    "errors",
+    "events",
+    "exchanges",
+    "fees",
+    "journal",
+    "loans",
+    "margin",
+    "msgevent",
+    "msgsend",
+    "orders",
+    "pg",
+    "position",
+    "reports",
+    "stream",
+    "symbol",
+    "symbol_changes",
+    "ticker",
+    "views",
+];
+
+/// Used when `UNIVERSES_FILE` is not set, and for those of them the file
+/// does not define; the rules the `/tradeable` page has always applied.
+const DEFAULT_UNIVERSES: &str = r#"[
+    {
+        "name": "tradeable",
+        "description": "Margin-enabled USDT pairs the bot trades",
+        "rules": [
+            { "field": "is_margin_enabled", "op": "eq", "value": true },
+            { "field": "enable_trading", "op": "eq", "value": true },
+            { "field": "fee_category", "op": "eq", "value": 1 },
+            { "field": "quote_currency", "op": "eq", "value": "USDT" },
+            { "field": "base_currency", "op": "not_in", "value": ["USDC", "KCS"] }
+        ]
+    }
+]"#;
+
+/// Used when `EXCHANGES_FILE` is not set.
+const DEFAULT_EXCHANGES: &str = r#"[
+    {
+        "name": "kucoin",
+        "display_name": "KuCoin",
+        "fee_currency": "KCS",
+        "order_url": "https://www.kucoin.com/trade/{symbol}"
+    }
+]"#;
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub logging: LoggingConfig,
+    pub snapshots: SnapshotConfig,
+    pub cache: CacheConfig,
+    pub universes: Vec<UniverseConfig>,
+    pub margin: MarginConfig,
+    pub loans: LoanConfig,
+    pub bots: BotConfig,
+    pub exchanges: ExchangeConfig,
+    pub sources: SourceConfig,
+    pub display: DisplayConfig,
+    pub graphql: GraphQlConfig,
+    pub stream: StreamConfig,
+    pub reports: ReportConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +95,130 @@ pub struct DatabaseConfig {
     pub acquire_timeout: Duration,
     pub idle_timeout: Duration,
     pub max_lifetime: Duration,
+    pub statement_timeout: Option<Duration>,
+    /// Optional hot standby; without it the replica pool is the primary.
+    pub replica: Option<PoolConfig>,
+    /// Separate pool for heavy queries, by default against the primary.
+    pub analytics: PoolConfig,
+    /// Repository name to pool, overriding the per-repository default.
+    pub assignments: HashMap<String, PoolName>,
+    pub health_check_interval: Duration,
+}
+
+/// Trading databases served by this instance. The default source is the
+/// `DATABASE_URL` one and is also served without a route prefix.
+#[derive(Debug, Clone)]
+pub struct SourceConfig {
+    pub default_name: String,
+    pub extra: Vec<DataSource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DataSource {
+    pub name: String,
+    pub database: DatabaseConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub statement_timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolName {
+    Primary,
+    Replica,
+    Analytics,
+}
+
+impl FromStr for PoolName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "primary" => Ok(PoolName::Primary),
+            "replica" => Ok(PoolName::Replica),
+            "analytics" => Ok(PoolName::Analytics),
+            _ => Err(anyhow::anyhow!("Unknown pool {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for PoolName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PoolName::Primary => "primary",
+            PoolName::Replica => "replica",
+            PoolName::Analytics => "analytics",
+        })
+    }
+}
+
+/// A named set of symbols. A symbol is a member when it is listed in
+/// `include`, or when it passes every rule and `min_liquidity`; `exclude`
+/// overrides both.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UniverseConfig {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub rules: Vec<UniverseRule>,
+    /// Symbols such as `BTC-USDT`, bypassing the rules.
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Minimum quote volume the bot itself matched over the last 24 hours;
+    /// the exchange tables carry no market volume.
+    pub min_liquidity: Option<f64>,
+}
+
+/// A predicate over one `Symbol` column. Numeric comparisons parse string
+/// columns such as `base_min_size`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UniverseRule {
+    pub field: String,
+    pub op: RuleOp,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOp {
+    Eq,
+    Ne,
+    In,
+    NotIn,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl fmt::Display for RuleOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RuleOp::Eq => "=",
+            RuleOp::Ne => "!=",
+            RuleOp::In => "in",
+            RuleOp::NotIn => "not in",
+            RuleOp::Gt => ">",
+            RuleOp::Gte => ">=",
+            RuleOp::Lt => "<",
+            RuleOp::Lte => "<=",
+        })
+    }
+}
+
+impl fmt::Display for UniverseRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op, self.value)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,12 +227,130 @@ pub struct LoggingConfig {
     pub format: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    /// `None` disables the background `pg_stat_statements` sampler.
+    pub interval: Option<Duration>,
+    pub retention: Duration,
+    /// `None` disables the symbol catalog change tracker.
+    pub symbol_interval: Option<Duration>,
+    pub symbol_retention: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Per-dataset TTLs; `None` disables caching of that dataset.
+    pub symbols_ttl: Option<Duration>,
+    pub tickers_ttl: Option<Duration>,
+    pub currencies_ttl: Option<Duration>,
+    /// Install NOTIFY triggers on the cached tables at startup.
+    pub install_triggers: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarginConfig {
+    /// Debt ratios at which the exchange calls for margin and liquidates.
+    pub margin_call_ratio: f64,
+    pub liquidation_ratio: f64,
+    /// Default price shock of the stress test, in percent either way.
+    pub stress_shock_pct: f64,
+    /// Currency positions are valued in, via `<currency>-<quote>` prices.
+    pub quote_currency: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BotConfig {
+    /// A bot waiting on its entry fill or on exit placement longer than
+    /// this is reported as stuck.
+    pub stuck_after: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExchangeConfig {
+    pub exchanges: Vec<ExchangeInfo>,
+    /// Window the per-exchange error rate is computed over.
+    pub summary_window: Duration,
+    /// An exchange without any update for this long is reported as stale.
+    pub stale_after: Duration,
+}
+
+/// Display metadata for an exchange, matched against the `exchange` column.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeInfo {
+    pub name: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub fee_currency: Option<String>,
+    /// Link for an order, with `{symbol}` and `{order_id}` placeholders.
+    #[serde(default)]
+    pub order_url: Option<String>,
+}
+
+/// Defaults for visitors who have not picked a timezone or locale.
+#[derive(Debug, Clone)]
+pub struct DisplayConfig {
+    pub timezone: Tz,
+    pub locale: Locale,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphQlConfig {
+    /// Deepest field nesting a query may use.
+    pub max_depth: usize,
+    /// Highest cost a query may have; list fields cost their `limit` times
+    /// the cost of their selection.
+    pub max_complexity: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// `None` disables the poller, leaving `/ws` with heartbeats and
+    /// replays only.
+    pub poll_interval: Option<Duration>,
+    /// Bot states are derived from the whole bots table, so they are read
+    /// less often than new rows.
+    pub bot_poll_interval: Duration,
+    pub heartbeat: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+    /// Periods reported on once they end; empty disables the scheduler.
+    pub periods: Vec<ReportPeriod>,
+    /// How often the scheduler looks for an ended period.
+    pub check_interval: Duration,
+    /// Receives each new report as JSON with its Markdown in `text`.
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoanConfig {
+    /// Daily interest rate by currency, for the estimated interest cost.
+    pub daily_rates: HashMap<String, f64>,
+    pub default_daily_rate: f64,
+}
+
 impl AppConfig {
     pub fn from_env() -> Result<Self> {
+        let database = DatabaseConfig::from_env()?;
+
         Ok(AppConfig {
             server: ServerConfig::from_env()?,
-            database: DatabaseConfig::from_env()?,
             logging: LoggingConfig::from_env()?,
+            snapshots: SnapshotConfig::from_env()?,
+            cache: CacheConfig::from_env()?,
+            universes: UniverseConfig::from_env()?,
+            margin: MarginConfig::from_env()?,
+            loans: LoanConfig::from_env()?,
+            bots: BotConfig::from_env()?,
+            exchanges: ExchangeConfig::from_env()?,
+            sources: SourceConfig::from_env(&database)?,
+            display: DisplayConfig::from_env()?,
+            graphql: GraphQlConfig::from_env()?,
+            stream: StreamConfig::from_env()?,
+            reports: ReportConfig::from_env()?,
+            database,
         })
     }
 
@@ -74,8 +387,39 @@ impl DatabaseConfig {
             .parse()
             .context("Invalid DB_MAX_LIFETIME_SECS value")?;
 
+        let url = get_env("DATABASE_URL")?;
+
+        let replica = match get_env("DATABASE_REPLICA_URL")
+            .ok()
+            .filter(|u| !u.is_empty())
+        {
+            Some(replica_url) => Some(PoolConfig {
+                url: replica_url,
+                max_connections: get_env_with_default("DB_REPLICA_MAX_CONNECTIONS", "10")?
+                    .parse()
+                    .context("Invalid DB_REPLICA_MAX_CONNECTIONS value")?,
+                statement_timeout: get_timeout_ms("DB_REPLICA_STATEMENT_TIMEOUT_MS", "0")?,
+            }),
+            None => None,
+        };
+
+        let analytics = PoolConfig {
+            url: get_env("DATABASE_ANALYTICS_URL")
+                .ok()
+                .filter(|u| !u.is_empty())
+                .unwrap_or_else(|| url.clone()),
+            max_connections: get_env_with_default("DB_ANALYTICS_MAX_CONNECTIONS", "2")?
+                .parse()
+                .context("Invalid DB_ANALYTICS_MAX_CONNECTIONS value")?,
+            statement_timeout: get_timeout_ms("DB_ANALYTICS_STATEMENT_TIMEOUT_MS", "60000")?,
+        };
+
+        let health_check_secs: u64 = get_env_with_default("DB_HEALTH_CHECK_INTERVAL_SECS", "10")?
+            .parse()
+            .context("Invalid DB_HEALTH_CHECK_INTERVAL_SECS value")?;
+
         Ok(DatabaseConfig {
-            url: get_env("DATABASE_URL")?,
+            url,
             max_connections: get_env_with_default("DB_MAX_CONNECTIONS", "10")?
                 .parse()
                 .context("Invalid DB_MAX_CONNECTIONS value")?,
@@ -85,8 +429,120 @@ impl DatabaseConfig {
             acquire_timeout: Duration::from_secs(acquire_timeout_secs),
             idle_timeout: Duration::from_secs(idle_timeout_secs),
             max_lifetime: Duration::from_secs(max_lifetime_secs),
+            statement_timeout: get_timeout_ms("DB_STATEMENT_TIMEOUT_MS", "0")?,
+            replica,
+            analytics,
+            assignments: parse_assignments(&get_env_with_default("DB_POOL_ASSIGNMENTS", "")?)?,
+            health_check_interval: Duration::from_secs(health_check_secs.max(1)),
         })
     }
+
+    pub fn primary(&self) -> PoolConfig {
+        PoolConfig {
+            url: self.url.clone(),
+            max_connections: self.max_connections,
+            statement_timeout: self.statement_timeout,
+        }
+    }
+
+    /// The same pool settings against another database, without a replica
+    /// and with analytics queries on the database itself.
+    pub fn for_url(&self, url: &str) -> Self {
+        DatabaseConfig {
+            url: url.to_string(),
+            replica: None,
+            analytics: PoolConfig {
+                url: url.to_string(),
+                ..self.analytics.clone()
+            },
+            ..self.clone()
+        }
+    }
+}
+
+impl SourceConfig {
+    pub fn from_env(database: &DatabaseConfig) -> Result<Self> {
+        let default_name = get_env_with_default("DATA_SOURCE_NAME", "default")?;
+
+        let mut names = HashSet::from([default_name.clone()]);
+        let extra = get_env_with_default("DATA_SOURCES", "")?
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, url) = pair
+                    .split_once('=')
+                    .with_context(|| format!("Invalid DATA_SOURCES entry {}", pair))?;
+                let name = name.trim();
+                if !is_source_name(name) || !names.insert(name.to_string()) {
+                    anyhow::bail!("Invalid or duplicate data source name {:?}", name);
+                }
+                Ok(DataSource {
+                    name: name.to_string(),
+                    database: database.for_url(url.trim()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !is_source_name(&default_name) {
+            anyhow::bail!("Invalid DATA_SOURCE_NAME {:?}", default_name);
+        }
+
+        Ok(SourceConfig {
+            default_name,
+            extra,
+        })
+    }
+}
+
+/// Source names appear in route prefixes.
+fn is_source_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// Repositories that write, and so only run on the primary.
+const WRITE_REPOSITORIES: &[&str] = &["journal", "reports", "symbol_changes", "views"];
+
+/// Parses `repo=pool` pairs, e.g. `orders=analytics,symbol=primary`.
+fn parse_assignments(value: &str) -> Result<HashMap<String, PoolName>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (repository, pool) = pair
+                .split_once('=')
+                .with_context(|| format!("Invalid DB_POOL_ASSIGNMENTS entry {}", pair))?;
+            let repository = repository.trim();
+            if !REPOSITORY_NAMES.contains(&repository) {
+                anyhow::bail!("Unknown repository {} in DB_POOL_ASSIGNMENTS", repository);
+            }
+            let pool: PoolName = pool.trim().parse()?;
+            if WRITE_REPOSITORIES.contains(&repository) && pool != PoolName::Primary {
+                anyhow::bail!(
+                    "Repository {} writes and must use the primary pool in DB_POOL_ASSIGNMENTS",
+                    repository
+                );
+            }
+            // Server statistics and snapshots are the primary's.
+            if repository == "pg" && pool == PoolName::Replica {
+                anyhow::bail!("Repository pg must not use the replica pool in DB_POOL_ASSIGNMENTS");
+            }
+            Ok((repository.to_string(), pool))
+        })
+        .collect()
+}
+
+/// Milliseconds from env, `0` meaning no timeout.
+fn get_timeout_ms(key: &str, default: &str) -> Result<Option<Duration>> {
+    let ms: u64 = get_env_with_default(key, default)?
+        .parse()
+        .with_context(|| format!("Invalid {} value", key))?;
+
+    Ok((ms > 0).then(|| Duration::from_millis(ms)))
 }
 
 impl LoggingConfig {
@@ -98,6 +554,329 @@ impl LoggingConfig {
     }
 }
 
+impl SnapshotConfig {
+    pub fn from_env() -> Result<Self> {
+        let interval_secs: u64 = get_env_with_default("PG_SNAPSHOT_INTERVAL_SECS", "300")?
+            .parse()
+            .context("Invalid PG_SNAPSHOT_INTERVAL_SECS value")?;
+
+        let retention_days: u64 = get_env_with_default("PG_SNAPSHOT_RETENTION_DAYS", "14")?
+            .parse()
+            .context("Invalid PG_SNAPSHOT_RETENTION_DAYS value")?;
+
+        let symbol_interval_secs: u64 =
+            get_env_with_default("SYMBOL_SNAPSHOT_INTERVAL_SECS", "300")?
+                .parse()
+                .context("Invalid SYMBOL_SNAPSHOT_INTERVAL_SECS value")?;
+
+        let symbol_retention_days: u64 =
+            get_env_with_default("SYMBOL_SNAPSHOT_RETENTION_DAYS", "90")?
+                .parse()
+                .context("Invalid SYMBOL_SNAPSHOT_RETENTION_DAYS value")?;
+
+        Ok(SnapshotConfig {
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+            retention: Duration::from_secs(retention_days * 24 * 60 * 60),
+            symbol_interval: (symbol_interval_secs > 0)
+                .then(|| Duration::from_secs(symbol_interval_secs)),
+            symbol_retention: Duration::from_secs(symbol_retention_days * 24 * 60 * 60),
+        })
+    }
+}
+
+impl CacheConfig {
+    pub fn from_env() -> Result<Self> {
+        Ok(CacheConfig {
+            symbols_ttl: get_ttl("CACHE_TTL_SYMBOLS_SECS", "300")?,
+            tickers_ttl: get_ttl("CACHE_TTL_TICKERS_SECS", "60")?,
+            currencies_ttl: get_ttl("CACHE_TTL_CURRENCIES_SECS", "3600")?,
+            install_triggers: get_env_with_default("CACHE_INSTALL_TRIGGERS", "false")?
+                .parse()
+                .context("Invalid CACHE_INSTALL_TRIGGERS value")?,
+        })
+    }
+}
+
+impl MarginConfig {
+    pub fn from_env() -> Result<Self> {
+        let config = MarginConfig {
+            margin_call_ratio: get_env_with_default("MARGIN_CALL_DEBT_RATIO", "0.9")?
+                .parse()
+                .context("Invalid MARGIN_CALL_DEBT_RATIO value")?,
+            liquidation_ratio: get_env_with_default("LIQUIDATION_DEBT_RATIO", "0.97")?
+                .parse()
+                .context("Invalid LIQUIDATION_DEBT_RATIO value")?,
+            stress_shock_pct: get_env_with_default("MARGIN_STRESS_SHOCK_PCT", "10")?
+                .parse()
+                .context("Invalid MARGIN_STRESS_SHOCK_PCT value")?,
+            quote_currency: get_env_with_default("MARGIN_QUOTE_CURRENCY", "USDT")?,
+        };
+
+        if !(0.0 < config.margin_call_ratio && config.margin_call_ratio < config.liquidation_ratio)
+        {
+            anyhow::bail!(
+                "MARGIN_CALL_DEBT_RATIO must be positive and below LIQUIDATION_DEBT_RATIO"
+            );
+        }
+        if !(0.0 < config.stress_shock_pct && config.stress_shock_pct < 100.0) {
+            anyhow::bail!("MARGIN_STRESS_SHOCK_PCT must be between 0 and 100");
+        }
+
+        Ok(config)
+    }
+}
+
+impl BotConfig {
+    pub fn from_env() -> Result<Self> {
+        let stuck_after_secs: u64 = get_env_with_default("BOT_STUCK_AFTER_SECS", "3600")?
+            .parse()
+            .context("Invalid BOT_STUCK_AFTER_SECS value")?;
+
+        Ok(BotConfig {
+            stuck_after: Duration::from_secs(stuck_after_secs),
+        })
+    }
+}
+
+impl LoanConfig {
+    pub fn from_env() -> Result<Self> {
+        Ok(LoanConfig {
+            daily_rates: parse_rates(&get_env_with_default("LOAN_DAILY_INTEREST_RATES", "")?)?,
+            default_daily_rate: get_env_with_default("LOAN_DAILY_INTEREST_RATE", "0.0002")?
+                .parse()
+                .context("Invalid LOAN_DAILY_INTEREST_RATE value")?,
+        })
+    }
+
+    pub fn daily_rate(&self, currency: &str) -> f64 {
+        self.daily_rates
+            .get(currency)
+            .copied()
+            .unwrap_or(self.default_daily_rate)
+    }
+}
+
+/// Parses `currency=rate` pairs, e.g. `USDT=0.0003,BTC=0.0001`.
+fn parse_rates(value: &str) -> Result<HashMap<String, f64>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (currency, rate) = pair
+                .split_once('=')
+                .with_context(|| format!("Invalid LOAN_DAILY_INTEREST_RATES entry {}", pair))?;
+            let rate = rate
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid rate in LOAN_DAILY_INTEREST_RATES {}", pair))?;
+            Ok((currency.trim().to_string(), rate))
+        })
+        .collect()
+}
+
+impl ExchangeConfig {
+    pub fn from_env() -> Result<Self> {
+        let path = get_env_with_default("EXCHANGES_FILE", "")?;
+        let (source, json) = if path.is_empty() {
+            (
+                "built-in exchanges".to_string(),
+                DEFAULT_EXCHANGES.to_string(),
+            )
+        } else {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read EXCHANGES_FILE {}", path))?;
+            (path, json)
+        };
+
+        let exchanges: Vec<ExchangeInfo> =
+            serde_json::from_str(&json).with_context(|| format!("Invalid {}", source))?;
+
+        let mut names = HashSet::new();
+        for exchange in &exchanges {
+            if exchange.name.is_empty() || !names.insert(exchange.name.as_str()) {
+                anyhow::bail!("Empty or duplicate exchange name {:?}", exchange.name);
+            }
+        }
+
+        let summary_window_secs: u64 =
+            get_env_with_default("EXCHANGE_SUMMARY_WINDOW_SECS", "86400")?
+                .parse()
+                .context("Invalid EXCHANGE_SUMMARY_WINDOW_SECS value")?;
+        if summary_window_secs == 0 {
+            anyhow::bail!("EXCHANGE_SUMMARY_WINDOW_SECS must be positive");
+        }
+
+        let stale_after_secs: u64 = get_env_with_default("EXCHANGE_STALE_AFTER_SECS", "900")?
+            .parse()
+            .context("Invalid EXCHANGE_STALE_AFTER_SECS value")?;
+
+        Ok(ExchangeConfig {
+            exchanges,
+            summary_window: Duration::from_secs(summary_window_secs),
+            stale_after: Duration::from_secs(stale_after_secs),
+        })
+    }
+}
+
+impl DisplayConfig {
+    pub fn from_env() -> Result<Self> {
+        let timezone = get_env_with_default("DISPLAY_TIMEZONE", "UTC")?;
+        let locale = get_env_with_default("DISPLAY_LOCALE", "en")?;
+
+        Ok(DisplayConfig {
+            timezone: timezone
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid DISPLAY_TIMEZONE {}", timezone))?,
+            locale: Locale::parse(&locale)
+                .with_context(|| format!("Invalid DISPLAY_LOCALE {}", locale))?,
+        })
+    }
+}
+
+impl GraphQlConfig {
+    pub fn from_env() -> Result<Self> {
+        Ok(GraphQlConfig {
+            max_depth: get_env_with_default("GRAPHQL_MAX_DEPTH", "8")?
+                .parse()
+                .context("Invalid GRAPHQL_MAX_DEPTH value")?,
+            max_complexity: get_env_with_default("GRAPHQL_MAX_COMPLEXITY", "10000")?
+                .parse()
+                .context("Invalid GRAPHQL_MAX_COMPLEXITY value")?,
+        })
+    }
+}
+
+impl StreamConfig {
+    pub fn from_env() -> Result<Self> {
+        let poll_interval_ms: u64 = get_env_with_default("WS_POLL_INTERVAL_MS", "1000")?
+            .parse()
+            .context("Invalid WS_POLL_INTERVAL_MS value")?;
+
+        let bot_poll_interval_secs: u64 = get_env_with_default("WS_BOT_POLL_INTERVAL_SECS", "30")?
+            .parse()
+            .context("Invalid WS_BOT_POLL_INTERVAL_SECS value")?;
+        if bot_poll_interval_secs == 0 {
+            anyhow::bail!("WS_BOT_POLL_INTERVAL_SECS must be positive");
+        }
+
+        let heartbeat_secs: u64 = get_env_with_default("WS_HEARTBEAT_SECS", "15")?
+            .parse()
+            .context("Invalid WS_HEARTBEAT_SECS value")?;
+        if heartbeat_secs == 0 {
+            anyhow::bail!("WS_HEARTBEAT_SECS must be positive");
+        }
+
+        Ok(StreamConfig {
+            poll_interval: (poll_interval_ms > 0).then(|| Duration::from_millis(poll_interval_ms)),
+            bot_poll_interval: Duration::from_secs(bot_poll_interval_secs),
+            heartbeat: Duration::from_secs(heartbeat_secs),
+        })
+    }
+}
+
+impl ReportConfig {
+    pub fn from_env() -> Result<Self> {
+        let periods = get_env_with_default("REPORT_PERIODS", "daily,weekly")?
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                p.parse()
+                    .with_context(|| format!("Invalid REPORT_PERIODS entry {:?}", p))
+            })
+            .collect::<Result<Vec<ReportPeriod>>>()?;
+
+        let check_interval_secs: u64 = get_env_with_default("REPORT_CHECK_INTERVAL_SECS", "300")?
+            .parse()
+            .context("Invalid REPORT_CHECK_INTERVAL_SECS value")?;
+        if check_interval_secs == 0 {
+            anyhow::bail!("REPORT_CHECK_INTERVAL_SECS must be positive");
+        }
+
+        let webhook_url = get_env_with_default("REPORT_WEBHOOK_URL", "")?;
+
+        Ok(ReportConfig {
+            periods,
+            check_interval: Duration::from_secs(check_interval_secs),
+            webhook_url: (!webhook_url.is_empty()).then_some(webhook_url),
+        })
+    }
+}
+
+impl ExchangeInfo {
+    pub fn order_link(&self, symbol: &str, order_id: &str) -> Option<String> {
+        self.order_url.as_ref().map(|url| {
+            url.replace("{symbol}", symbol)
+                .replace("{order_id}", order_id)
+        })
+    }
+}
+
+impl UniverseConfig {
+    pub fn from_env() -> Result<Vec<Self>> {
+        let path = get_env_with_default("UNIVERSES_FILE", "")?;
+        let (source, json) = if path.is_empty() {
+            (
+                "built-in universes".to_string(),
+                DEFAULT_UNIVERSES.to_string(),
+            )
+        } else {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read UNIVERSES_FILE {}", path))?;
+            (path, json)
+        };
+
+        let mut universes: Vec<UniverseConfig> =
+            serde_json::from_str(&json).with_context(|| format!("Invalid {}", source))?;
+        let builtin: Vec<UniverseConfig> =
+            serde_json::from_str(DEFAULT_UNIVERSES).context("Invalid built-in universes")?;
+        for universe in builtin {
+            if !universes.iter().any(|u| u.name == universe.name) {
+                universes.push(universe);
+            }
+        }
+
+        let mut names = HashSet::new();
+        for universe in &universes {
+            if universe.name.is_empty() || !names.insert(universe.name.as_str()) {
+                anyhow::bail!("Empty or duplicate universe name {:?}", universe.name);
+            }
+            for rule in &universe.rules {
+                rule.validate()
+                    .with_context(|| format!("Invalid rule in universe {}", universe.name))?;
+            }
+        }
+
+        Ok(universes)
+    }
+}
+
+impl UniverseRule {
+    fn validate(&self) -> Result<()> {
+        if !Symbol::FIELDS.contains(&self.field.as_str()) {
+            anyhow::bail!("Unknown symbol field {}", self.field);
+        }
+        match self.op {
+            RuleOp::In | RuleOp::NotIn if !self.value.is_array() => {
+                anyhow::bail!("{} needs an array value", self)
+            }
+            RuleOp::Gt | RuleOp::Gte | RuleOp::Lt | RuleOp::Lte if !self.value.is_number() => {
+                anyhow::bail!("{} needs a numeric value", self)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn get_ttl(key: &str, default: &str) -> Result<Option<Duration>> {
+    let secs: u64 = get_env_with_default(key, default)?
+        .parse()
+        .with_context(|| format!("Invalid {} value", key))?;
+
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
 fn get_env_with_default(key: &str, default: &str) -> Result<String> {
     match env::var(key) {
         Ok(val) => Ok(val.trim().to_string()),
@@ -1,13 +1,21 @@
+use crate::config::{AppConfig, PoolName};
+use crate::core::cache::{Cache, CacheRegistry};
+use crate::core::db::DbPools;
 use crate::repositories::{
+    CachedCurrencyRepository, CachedSymbolRepository, CachedTickerRepository,
     PostgresBalanceRepository, PostgresBotRepository, PostgresCurrencyRepository,
     PostgresErrorRepository, PostgresEventOrderRepository, PostgresEventRepository,
-    PostgresMsgEventRepository, PostgresMsgSendRepository, PostgresPgRepository,
-    PostgresPositionRepository, PostgresSymbolRepository, PostgresTickerRepository,
+    PostgresExchangeRepository, PostgresFeeRepository, PostgresJournalRepository,
+    PostgresLoanRepository, PostgresMarginRepository, PostgresMsgEventRepository,
+    PostgresMsgSendRepository, PostgresPgRepository, PostgresPositionRepository,
+    PostgresReportRepository, PostgresStreamRepository, PostgresSymbolChangeRepository,
+    PostgresSymbolRepository, PostgresTickerRepository, PostgresViewRepository,
 };
 use crate::services::{
-    BalanceService, BotService, CurrencyService, ErrorService, EventService, MsgEventService,
-    MsgSendService, OrderService, PgService, PositionService, StaticService, SymbolService,
-    TickerService,
+    BalanceService, BotService, CurrencyService, ErrorService, EventService, ExchangeService,
+    FeeService, JournalService, LoanService, MarginService, MsgEventService, MsgSendService,
+    OrderService, OrderValidationService, PgService, PositionService, ReportService, StaticService,
+    StreamService, SymbolChangeService, SymbolService, TickerService, ViewService,
 };
 use std::sync::Arc;
 
@@ -15,53 +23,154 @@ use std::sync::Arc;
 pub struct AppState {
     pub balance_service: Arc<BalanceService<PostgresBalanceRepository>>,
     pub bot_service: Arc<BotService<PostgresBotRepository>>,
-    pub currency_service: Arc<CurrencyService<PostgresCurrencyRepository>>,
+    pub currency_service:
+        Arc<CurrencyService<CachedCurrencyRepository<PostgresCurrencyRepository>>>,
     pub error_service: Arc<ErrorService<PostgresErrorRepository>>,
     pub event_service: Arc<EventService<PostgresEventRepository>>,
+    pub exchange_service: Arc<ExchangeService<PostgresExchangeRepository>>,
+    pub fee_service: Arc<FeeService<PostgresFeeRepository>>,
+    pub journal_service: Arc<JournalService<PostgresJournalRepository>>,
+    pub loan_service: Arc<LoanService<PostgresLoanRepository>>,
+    pub margin_service: Arc<MarginService<PostgresMarginRepository>>,
     pub msgevent_service: Arc<MsgEventService<PostgresMsgEventRepository>>,
     pub msgsend_service: Arc<MsgSendService<PostgresMsgSendRepository>>,
     pub order_service: Arc<OrderService<PostgresEventOrderRepository>>,
+    pub order_validation_service: Arc<
+        OrderValidationService<
+            CachedSymbolRepository<PostgresSymbolRepository>,
+            PostgresMsgSendRepository,
+        >,
+    >,
     pub pg_service: Arc<PgService<PostgresPgRepository>>,
     pub position_service: Arc<PositionService<PostgresPositionRepository>>,
-    pub symbol_service: Arc<SymbolService<PostgresSymbolRepository>>,
-    pub ticker_service: Arc<TickerService<PostgresTickerRepository>>,
+    pub report_service:
+        Arc<ReportService<PostgresReportRepository, PostgresBotRepository, PostgresPgRepository>>,
+    pub symbol_service: Arc<SymbolService<CachedSymbolRepository<PostgresSymbolRepository>>>,
+    pub symbol_change_service: Arc<SymbolChangeService<PostgresSymbolChangeRepository>>,
+    pub ticker_service: Arc<TickerService<CachedTickerRepository<PostgresTickerRepository>>>,
+    pub view_service: Arc<ViewService<PostgresViewRepository>>,
     pub static_service: Arc<StaticService>,
+    pub stream_service: Arc<StreamService<PostgresStreamRepository, PostgresBotRepository>>,
+    pub cache_registry: Arc<CacheRegistry>,
 }
 
 impl AppState {
-    pub fn new(pool: sqlx::PgPool) -> Self {
+    pub fn new(pools: &DbPools, config: &AppConfig) -> Self {
+        let cache = &config.cache;
+        let pool = |repository: &str, default: PoolName| pools.for_repository(repository, default);
+        let replica = pools.has_replica().then(|| pools.replica.clone());
+
+        let all_symbols = Arc::new(Cache::new("symbols", "symbol", cache.symbols_ttl));
+        let tickers = Arc::new(Cache::new("tickers", "ticker", cache.tickers_ttl));
+        let currencies = Arc::new(Cache::new("currencies", "currency", cache.currencies_ttl));
+
+        let mut cache_registry = CacheRegistry::default();
+        cache_registry.register(all_symbols.clone());
+        cache_registry.register(tickers.clone());
+        cache_registry.register(currencies.clone());
+
+        let bot_service = Arc::new(BotService::new(
+            PostgresBotRepository::new(pool("bots", PoolName::Replica)),
+            config.bots.stuck_after,
+        ));
+        let pg_service = Arc::new(PgService::new(PostgresPgRepository::new(
+            pool("pg", PoolName::Analytics),
+            pools.primary.clone(),
+            replica,
+        )));
+
         Self {
-            balance_service: Arc::new(BalanceService::new(PostgresBalanceRepository::new(
-                pool.clone(),
-            ))),
-            bot_service: Arc::new(BotService::new(PostgresBotRepository::new(pool.clone()))),
-            currency_service: Arc::new(CurrencyService::new(PostgresCurrencyRepository::new(
-                pool.clone(),
-            ))),
-            error_service: Arc::new(ErrorService::new(PostgresErrorRepository::new(
-                pool.clone(),
-            ))),
-            event_service: Arc::new(EventService::new(PostgresEventRepository::new(
-                pool.clone(),
+            balance_service: Arc::new(BalanceService::new(PostgresBalanceRepository::new(pool(
+                "balance",
+                PoolName::Replica,
+            )))),
+            bot_service: bot_service.clone(),
+            currency_service: Arc::new(CurrencyService::new(CachedCurrencyRepository::new(
+                PostgresCurrencyRepository::new(pool("currency", PoolName::Replica)),
+                currencies,
             ))),
+            error_service: Arc::new(ErrorService::new(PostgresErrorRepository::new(pool(
+                "errors",
+                PoolName::Replica,
+            )))),
+            event_service: Arc::new(EventService::new(PostgresEventRepository::new(pool(
+                "events",
+                PoolName::Replica,
+            )))),
+            exchange_service: Arc::new(ExchangeService::new(
+                PostgresExchangeRepository::new(pool("exchanges", PoolName::Replica)),
+                config.exchanges.summary_window,
+                config.exchanges.stale_after,
+            )),
+            fee_service: Arc::new(FeeService::new(PostgresFeeRepository::new(pool(
+                "fees",
+                PoolName::Replica,
+            )))),
+            journal_service: Arc::new(JournalService::new(PostgresJournalRepository::new(pool(
+                "journal",
+                PoolName::Primary,
+            )))),
+            loan_service: Arc::new(LoanService::new(
+                PostgresLoanRepository::new(pool("loans", PoolName::Replica)),
+                config.loans.clone(),
+            )),
+            margin_service: Arc::new(MarginService::new(
+                PostgresMarginRepository::new(pool("margin", PoolName::Replica)),
+                config.margin.clone(),
+            )),
             msgevent_service: Arc::new(MsgEventService::new(PostgresMsgEventRepository::new(
-                pool.clone(),
-            ))),
-            msgsend_service: Arc::new(MsgSendService::new(PostgresMsgSendRepository::new(
-                pool.clone(),
-            ))),
-            order_service: Arc::new(OrderService::new(PostgresEventOrderRepository::new(
-                pool.clone(),
+                pool("msgevent", PoolName::Replica),
             ))),
-            pg_service: Arc::new(PgService::new(PostgresPgRepository::new(pool.clone()))),
+            msgsend_service: Arc::new(MsgSendService::new(PostgresMsgSendRepository::new(pool(
+                "msgsend",
+                PoolName::Replica,
+            )))),
+            order_service: Arc::new(OrderService::new(PostgresEventOrderRepository::new(pool(
+                "orders",
+                PoolName::Replica,
+            )))),
+            order_validation_service: Arc::new(OrderValidationService::new(
+                CachedSymbolRepository::new(
+                    PostgresSymbolRepository::new(pool("symbol", PoolName::Replica)),
+                    all_symbols.clone(),
+                ),
+                PostgresMsgSendRepository::new(pool("msgsend", PoolName::Replica)),
+            )),
+            pg_service: pg_service.clone(),
             position_service: Arc::new(PositionService::new(PostgresPositionRepository::new(
-                pool.clone(),
+                pool("position", PoolName::Replica),
             ))),
-            symbol_service: Arc::new(SymbolService::new(PostgresSymbolRepository::new(
-                pool.clone(),
+            report_service: Arc::new(ReportService::new(
+                PostgresReportRepository::new(pool("reports", PoolName::Primary)),
+                bot_service.clone(),
+                pg_service,
+                config.reports.webhook_url.clone(),
+            )),
+            symbol_service: Arc::new(SymbolService::new(
+                CachedSymbolRepository::new(
+                    PostgresSymbolRepository::new(pool("symbol", PoolName::Replica)),
+                    all_symbols,
+                ),
+                config.universes.clone(),
+            )),
+            symbol_change_service: Arc::new(SymbolChangeService::new(
+                PostgresSymbolChangeRepository::new(pool("symbol_changes", PoolName::Primary)),
+            )),
+            ticker_service: Arc::new(TickerService::new(CachedTickerRepository::new(
+                PostgresTickerRepository::new(pool("ticker", PoolName::Replica)),
+                tickers,
             ))),
-            ticker_service: Arc::new(TickerService::new(PostgresTickerRepository::new(pool))),
+            view_service: Arc::new(ViewService::new(PostgresViewRepository::new(pool(
+                "views",
+                PoolName::Primary,
+            )))),
             static_service: Arc::new(StaticService::new()),
+            stream_service: Arc::new(StreamService::new(
+                PostgresStreamRepository::new(pool("stream", PoolName::Replica)),
+                bot_service,
+                config.stream.heartbeat,
+            )),
+            cache_registry: Arc::new(cache_registry),
         }
     }
 }
@@ -0,0 +1,251 @@
+use crate::core::error::AppResult;
+use serde::Serialize;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+/// Channel the listener subscribes to; the payload is the changed table name.
+pub const CACHE_CHANNEL: &str = "webaggregator_cache";
+
+/// Opt-in (`CACHE_INSTALL_TRIGGERS`) statement-level triggers on the cached
+/// tables so writes by the trading bot invalidate the cache immediately
+/// instead of waiting for the TTL.
+const NOTIFY_TRIGGERS_SQL: &str = r#"
+    CREATE OR REPLACE FUNCTION webaggregator_notify_cache() RETURNS trigger AS $$
+    BEGIN
+        PERFORM pg_notify('webaggregator_cache', TG_TABLE_NAME);
+        RETURN NULL;
+    END;
+    $$ LANGUAGE plpgsql;
+
+    DROP TRIGGER IF EXISTS webaggregator_cache_notify ON symbol;
+    CREATE TRIGGER webaggregator_cache_notify
+        AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON symbol
+        FOR EACH STATEMENT EXECUTE FUNCTION webaggregator_notify_cache();
+
+    DROP TRIGGER IF EXISTS webaggregator_cache_notify ON ticker;
+    CREATE TRIGGER webaggregator_cache_notify
+        AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON ticker
+        FOR EACH STATEMENT EXECUTE FUNCTION webaggregator_notify_cache();
+
+    DROP TRIGGER IF EXISTS webaggregator_cache_notify ON currency;
+    CREATE TRIGGER webaggregator_cache_notify
+        AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON currency
+        FOR EACH STATEMENT EXECUTE FUNCTION webaggregator_notify_cache();
+"#;
+
+const LISTENER_RETRY: Duration = Duration::from_secs(5);
+
+struct Entry<T> {
+    value: T,
+    loaded_at: Instant,
+}
+
+/// A single cached dataset. Concurrent misses are coalesced: only the first
+/// caller loads, the rest wait on `load_lock` and then read its result.
+pub struct Cache<T> {
+    name: &'static str,
+    table: &'static str,
+    /// `None` disables caching and every call goes to the loader.
+    ttl: Option<Duration>,
+    entry: RwLock<Option<Entry<T>>>,
+    load_lock: Mutex<()>,
+    /// Bumped on invalidation so a load racing with a NOTIFY is not stored.
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl<T: Clone> Cache<T> {
+    pub fn new(name: &'static str, table: &'static str, ttl: Option<Duration>) -> Self {
+        Self {
+            name,
+            table,
+            ttl,
+            entry: RwLock::new(None),
+            load_lock: Mutex::new(()),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn get_or_load<F, Fut, E>(&self, load: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let Some(ttl) = self.ttl else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return load().await;
+        };
+
+        if let Some(value) = self.fresh(ttl) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        let _guard = self.load_lock.lock().await;
+        if let Some(value) = self.fresh(ttl) {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.generation.load(Ordering::Acquire);
+        let value = load().await?;
+
+        if self.generation.load(Ordering::Acquire) == generation {
+            *self.entry.write().unwrap_or_else(|e| e.into_inner()) = Some(Entry {
+                value: value.clone(),
+                loaded_at: Instant::now(),
+            });
+        }
+
+        Ok(value)
+    }
+
+    fn fresh(&self, ttl: Duration) -> Option<T> {
+        self.entry
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|entry| entry.loaded_at.elapsed() < ttl)
+            .map(|entry| entry.value.clone())
+    }
+}
+
+pub trait CacheHandle: Send + Sync {
+    fn table(&self) -> &'static str;
+    fn invalidate(&self);
+    fn stats(&self) -> CacheStats;
+}
+
+impl<T: Clone + Send + Sync> CacheHandle for Cache<T> {
+    fn table(&self) -> &'static str {
+        self.table
+    }
+
+    fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *self.entry.write().unwrap_or_else(|e| e.into_inner()) = None;
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> CacheStats {
+        let age_secs = self
+            .entry
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|entry| entry.loaded_at.elapsed().as_secs());
+
+        CacheStats {
+            name: self.name,
+            table: self.table,
+            ttl_secs: self.ttl.map(|ttl| ttl.as_secs()),
+            age_secs,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub table: &'static str,
+    pub ttl_secs: Option<u64>,
+    pub age_secs: Option<u64>,
+    pub hits: u64,
+    pub misses: u64,
+    /// Misses served by another caller's in-flight load.
+    pub coalesced: u64,
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.coalesced + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.coalesced) as f64 / total as f64 * 100.0
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CacheRegistry {
+    caches: Vec<Arc<dyn CacheHandle>>,
+}
+
+impl CacheRegistry {
+    pub fn register<T: Clone + Send + Sync + 'static>(&mut self, cache: Arc<Cache<T>>) {
+        self.caches.push(cache);
+    }
+
+    pub fn stats(&self) -> Vec<CacheStats> {
+        self.caches.iter().map(|cache| cache.stats()).collect()
+    }
+
+    fn invalidate_table(&self, table: &str) {
+        for cache in self.caches.iter().filter(|cache| cache.table() == table) {
+            cache.invalidate();
+        }
+    }
+
+    fn invalidate_all(&self) {
+        for cache in &self.caches {
+            cache.invalidate();
+        }
+    }
+
+    /// Listens on [`CACHE_CHANNEL`] and drops caches of the notified table.
+    /// Everything is invalidated whenever the connection drops, since
+    /// notifications sent meanwhile are lost. Never returns.
+    pub async fn run_listener(&self, pool: PgPool) {
+        loop {
+            if let Err(e) = self.listen(&pool).await {
+                error!("Cache listener failed: {}", e);
+            }
+            self.invalidate_all();
+            tokio::time::sleep(LISTENER_RETRY).await;
+        }
+    }
+
+    async fn listen(&self, pool: &PgPool) -> AppResult<()> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(CACHE_CHANNEL).await?;
+        info!("Listening for cache invalidations on {}", CACHE_CHANNEL);
+
+        loop {
+            match listener.try_recv().await? {
+                Some(notification) => {
+                    debug!("Cache invalidation for {}", notification.payload());
+                    self.invalidate_table(notification.payload());
+                }
+                None => {
+                    warn!("Cache listener reconnecting, invalidating all caches");
+                    self.invalidate_all();
+                }
+            }
+        }
+    }
+}
+
+pub async fn install_notify_triggers(pool: &PgPool) -> AppResult<()> {
+    sqlx::raw_sql(NOTIFY_TRIGGERS_SQL).execute(pool).await?;
+    Ok(())
+}
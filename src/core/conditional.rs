@@ -0,0 +1,82 @@
+use crate::core::display::{self, DisplaySettings};
+use crate::core::exchange::{EXCHANGE_COOKIE, read_cookie};
+use actix_web::http::header::{
+    self, CacheControl, CacheDirective, ETag, EntityTag, Header, HttpDate, IfNoneMatch,
+    LastModified,
+};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use chrono::{DateTime, SubsecRound, Utc};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::SystemTime;
+
+/// Response validators for a dataset, derived from `max(updated_at)` and the
+/// row count so that deleted rows also change the ETag. The tag is weak since
+/// the rendered page includes its own render time.
+///
+/// Pages show each row's age next to its `updated_at`, in the timezone and
+/// locale of the display cookies, under the exchange selector; all of these
+/// are hashed into the tag too, so a copy showing outdated ages or other
+/// settings is not revalidated.
+pub struct Validators {
+    etag: EntityTag,
+    last_modified: Option<HttpDate>,
+}
+
+impl Validators {
+    pub fn new(
+        req: &HttpRequest,
+        dataset: &str,
+        updated_at: impl IntoIterator<Item = DateTime<Utc>>,
+    ) -> Self {
+        let settings = DisplaySettings::read(req);
+        let mut hasher = DefaultHasher::new();
+        settings.timezone.name().hash(&mut hasher);
+        settings.locale.code().hash(&mut hasher);
+        read_cookie(req, EXCHANGE_COOKIE).hash(&mut hasher);
+
+        let now = Utc::now();
+        let (count, max) = updated_at
+            .into_iter()
+            .fold((0usize, None), |(count, max), ts| {
+                display::relative(&ts, now).hash(&mut hasher);
+                (count + 1, max.max(Some(ts)))
+            });
+
+        let tag = format!(
+            "{}-{}-{}-{:x}",
+            dataset,
+            count,
+            max.map_or(0, |ts| ts.timestamp_micros()),
+            hasher.finish()
+        );
+
+        Self {
+            etag: EntityTag::new_weak(tag),
+            last_modified: max.map(|ts| HttpDate::from(SystemTime::from(ts.trunc_subsecs(0)))),
+        }
+    }
+
+    /// `304 Not Modified` when the client's copy is current. Only
+    /// `If-None-Match` is honored: `If-Modified-Since` cannot tell apart
+    /// copies rendered with other settings or ages.
+    pub fn not_modified(&self, req: &HttpRequest) -> Option<HttpResponse> {
+        let fresh = match IfNoneMatch::parse(req) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&self.etag)),
+            Err(_) => false,
+        };
+
+        fresh.then(|| self.apply(&mut HttpResponse::NotModified()).finish())
+    }
+
+    pub fn apply<'a>(&self, builder: &'a mut HttpResponseBuilder) -> &'a mut HttpResponseBuilder {
+        builder
+            .insert_header(ETag(self.etag.clone()))
+            .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+            .insert_header((header::VARY, "Cookie"));
+        if let Some(last_modified) = self.last_modified {
+            builder.insert_header(LastModified(last_modified));
+        }
+        builder
+    }
+}
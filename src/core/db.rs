@@ -0,0 +1,199 @@
+use crate::config::{DatabaseConfig, PoolConfig, PoolName};
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// A named connection pool handed to repositories. Non-primary pools carry
+/// a fallback pool on the primary, with their own statement timeout, and
+/// serve from it while their own server fails health checks.
+#[derive(Clone)]
+pub struct DbPool {
+    name: PoolName,
+    pool: PgPool,
+    fallback: Option<PgPool>,
+    healthy: Arc<AtomicBool>,
+}
+
+impl DbPool {
+    pub fn get(&self) -> &PgPool {
+        match &self.fallback {
+            Some(fallback) if !self.healthy.load(Ordering::Relaxed) => fallback,
+            _ => &self.pool,
+        }
+    }
+
+    /// The pool's own server, ignoring the fallback.
+    pub fn direct(&self) -> &PgPool {
+        &self.pool
+    }
+
+    async fn check_health(&self) {
+        if self.fallback.is_none() {
+            return;
+        }
+
+        let ok = sqlx::query("SELECT 1").execute(&self.pool).await.is_ok();
+        let was_ok = self.healthy.swap(ok, Ordering::Relaxed);
+        match (was_ok, ok) {
+            (true, false) => warn!("{} pool unavailable, falling back to primary", self.name),
+            (false, true) => info!("{} pool recovered", self.name),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DbPools {
+    pub primary: DbPool,
+    pub replica: DbPool,
+    pub analytics: DbPool,
+    config: DatabaseConfig,
+}
+
+impl DbPools {
+    /// Connects the primary eagerly and fails if it is unreachable. The
+    /// replica and analytics pools connect lazily so a missing server only
+    /// degrades to the primary.
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        let primary_config = config.primary();
+        let primary = pool_options(config, &primary_config)
+            .connect_with(connect_options(&primary_config)?)
+            .await
+            .context("Failed to connect to PostgreSQL")?;
+        let primary = DbPool {
+            name: PoolName::Primary,
+            pool: primary,
+            fallback: None,
+            healthy: Arc::new(AtomicBool::new(true)),
+        };
+
+        let replica = match &config.replica {
+            Some(replica) => {
+                secondary(
+                    config,
+                    PoolName::Replica,
+                    replica,
+                    &primary_config,
+                    &primary.pool,
+                )
+                .await?
+            }
+            None => DbPool {
+                name: PoolName::Replica,
+                ..primary.clone()
+            },
+        };
+        let analytics = secondary(
+            config,
+            PoolName::Analytics,
+            &config.analytics,
+            &primary_config,
+            &primary.pool,
+        )
+        .await?;
+
+        Ok(Self {
+            primary,
+            replica,
+            analytics,
+            config: config.clone(),
+        })
+    }
+
+    pub fn get(&self, name: PoolName) -> DbPool {
+        match name {
+            PoolName::Primary => self.primary.clone(),
+            PoolName::Replica => self.replica.clone(),
+            PoolName::Analytics => self.analytics.clone(),
+        }
+    }
+
+    pub fn has_replica(&self) -> bool {
+        self.config.replica.is_some()
+    }
+
+    /// Pool assigned to `repository` via `DB_POOL_ASSIGNMENTS`, or `default`.
+    pub fn for_repository(&self, repository: &str, default: PoolName) -> DbPool {
+        self.get(
+            self.config
+                .assignments
+                .get(repository)
+                .copied()
+                .unwrap_or(default),
+        )
+    }
+
+    /// Pings the non-primary pools and flips them to the primary while they
+    /// are down. Never returns.
+    pub async fn run_health_checks(&self) {
+        let mut ticker = tokio::time::interval(self.config.health_check_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            tokio::join!(self.replica.check_health(), self.analytics.check_health());
+        }
+    }
+}
+
+async fn secondary(
+    config: &DatabaseConfig,
+    name: PoolName,
+    pool_config: &PoolConfig,
+    primary_config: &PoolConfig,
+    primary: &PgPool,
+) -> Result<DbPool> {
+    let pool = pool_options(config, pool_config)
+        .min_connections(0)
+        .connect_lazy_with(connect_options(pool_config)?);
+
+    let fallback = if pool_config.statement_timeout == primary_config.statement_timeout {
+        primary.clone()
+    } else {
+        let fallback_config = PoolConfig {
+            statement_timeout: pool_config.statement_timeout,
+            ..primary_config.clone()
+        };
+        pool_options(config, pool_config)
+            .min_connections(0)
+            .connect_lazy_with(connect_options(&fallback_config)?)
+    };
+
+    let pool = DbPool {
+        name,
+        pool,
+        fallback: Some(fallback),
+        healthy: Arc::new(AtomicBool::new(true)),
+    };
+    pool.check_health().await;
+
+    Ok(pool)
+}
+
+fn pool_options(config: &DatabaseConfig, pool_config: &PoolConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(pool_config.max_connections)
+        .min_connections(config.min_connections.min(pool_config.max_connections))
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(config.idle_timeout)
+        .max_lifetime(config.max_lifetime)
+}
+
+fn connect_options(pool_config: &PoolConfig) -> Result<PgConnectOptions> {
+    let options = PgConnectOptions::from_str(&pool_config.url).context("Invalid database URL")?;
+
+    Ok(match pool_config.statement_timeout {
+        Some(timeout) => options.options([("statement_timeout", timeout_ms(timeout))]),
+        None => options,
+    })
+}
+
+fn timeout_ms(timeout: Duration) -> String {
+    format!("{}ms", timeout.as_millis())
+}
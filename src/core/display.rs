@@ -0,0 +1,184 @@
+use crate::core::exchange::read_cookie;
+use actix_web::HttpRequest;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// Cookies holding the timezone and locale picked on the display page.
+pub const TIMEZONE_COOKIE: &str = "tz";
+pub const LOCALE_COOKIE: &str = "locale";
+
+/// Number formatting conventions offered in the display settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+    Fr,
+    Ch,
+}
+
+impl Locale {
+    pub const ALL: &[Locale] = &[Locale::En, Locale::De, Locale::Fr, Locale::Ch];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+            Locale::Ch => "de-CH",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|l| l.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// A sample number, for the settings page.
+    pub fn example(&self) -> String {
+        format_number("1234567.89", *self)
+    }
+
+    /// Digit group and decimal separators.
+    fn separators(&self) -> (&'static str, &'static str) {
+        match self {
+            Locale::En => (",", "."),
+            Locale::De => (".", ","),
+            Locale::Fr => ("\u{202f}", ","),
+            Locale::Ch => ("'", "."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisplaySettings {
+    pub timezone: Tz,
+    pub locale: Locale,
+}
+
+impl DisplaySettings {
+    /// The request's cookies, else the configured defaults.
+    pub fn read(req: &HttpRequest) -> Self {
+        let default = default();
+        Self {
+            timezone: read_cookie(req, TIMEZONE_COOKIE)
+                .and_then(|tz| tz.parse().ok())
+                .unwrap_or(default.timezone),
+            locale: read_cookie(req, LOCALE_COOKIE)
+                .and_then(|code| Locale::parse(&code))
+                .unwrap_or(default.locale),
+        }
+    }
+}
+
+/// Configured defaults, set once at startup.
+static DEFAULT: OnceLock<DisplaySettings> = OnceLock::new();
+
+tokio::task_local! {
+    /// Settings of the request being served.
+    static CURRENT: DisplaySettings;
+}
+
+pub fn init(settings: DisplaySettings) {
+    let _ = DEFAULT.set(settings);
+}
+
+pub fn default() -> DisplaySettings {
+    DEFAULT.get().copied().unwrap_or(DisplaySettings {
+        timezone: Tz::UTC,
+        locale: Locale::En,
+    })
+}
+
+pub fn current() -> DisplaySettings {
+    CURRENT.try_with(|s| *s).unwrap_or_else(|_| default())
+}
+
+/// Runs `fut`, a request, with `settings` as the current settings.
+pub async fn scope<F: Future>(settings: DisplaySettings, fut: F) -> F::Output {
+    CURRENT.scope(settings, fut).await
+}
+
+/// `at` in the current timezone with its age, e.g.
+/// `2026-10-19 10:00:00 CEST, 3 min ago`.
+pub fn timestamp(at: &DateTime<Utc>) -> String {
+    format!(
+        "{}, {}",
+        at.with_timezone(&current().timezone)
+            .format("%Y-%m-%d %H:%M:%S %Z"),
+        relative(at, Utc::now())
+    )
+}
+
+/// Age of `at` at `now` in its largest whole unit.
+pub fn relative(at: &DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - *at).num_seconds();
+    let (amount, unit) = match secs.unsigned_abs() {
+        s if s < 60 => (s, "s"),
+        s if s < 3_600 => (s / 60, "min"),
+        s if s < 86_400 => (s / 3_600, "h"),
+        s => (s / 86_400, "d"),
+    };
+    match secs {
+        0 => "now".to_string(),
+        s if s > 0 => format!("{} {} ago", amount, unit),
+        _ => format!("in {} {}", amount, unit),
+    }
+}
+
+/// An epoch time in seconds, milliseconds, microseconds or nanoseconds, told
+/// apart by magnitude; the bot's tables use all four.
+pub fn from_epoch(value: f64) -> Option<DateTime<Utc>> {
+    let nanos = match value.abs() {
+        v if v < 1e11 => value * 1e9,
+        v if v < 1e14 => value * 1e6,
+        v if v < 1e17 => value * 1e3,
+        _ => value,
+    };
+    nanos
+        .is_finite()
+        .then(|| DateTime::from_timestamp_nanos(nanos as i64))
+}
+
+/// `value` with the current locale's separators when it is a plain decimal
+/// number, else unchanged. Digits are kept as given, never rounded.
+pub fn number(value: &str) -> String {
+    format_number(value, current().locale)
+}
+
+/// `value` rounded to `decimals` places, with the current locale's
+/// separators.
+pub fn fixed(value: &f64, decimals: usize) -> String {
+    number(&format!("{:.*}", decimals, value))
+}
+
+fn format_number(value: &str, locale: Locale) -> String {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value),
+    };
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (unsigned, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(int) || frac.is_some_and(|f| !is_digits(f)) {
+        return value.to_string();
+    }
+
+    let (group, decimal) = locale.separators();
+    let mut out = String::from(sign);
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            out.push_str(group);
+        }
+        out.push(digit);
+    }
+    if let Some(frac) = frac {
+        out.push_str(decimal);
+        out.push_str(frac);
+    }
+    out
+}
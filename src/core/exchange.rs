@@ -0,0 +1,98 @@
+use crate::config::ExchangeInfo;
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{FromRequest, HttpRequest};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::future::{Ready, ready};
+use std::sync::OnceLock;
+use tracing::warn;
+use utoipa::IntoParams;
+
+/// Cookie holding the exchange picked in the base template's selector.
+pub const EXCHANGE_COOKIE: &str = "exchange";
+
+/// Configured exchanges, set once at startup so that the base template can
+/// list them without every page carrying them.
+static EXCHANGES: OnceLock<Vec<ExchangeInfo>> = OnceLock::new();
+
+pub fn init(exchanges: Vec<ExchangeInfo>) {
+    if EXCHANGES.set(exchanges).is_err() {
+        warn!("Exchange metadata already initialized");
+    }
+}
+
+pub fn all() -> &'static [ExchangeInfo] {
+    EXCHANGES.get().map_or(&[], Vec::as_slice)
+}
+
+pub fn get(name: &str) -> Option<&'static ExchangeInfo> {
+    all().iter().find(|e| e.name.eq_ignore_ascii_case(name))
+}
+
+/// Configured display name, else the raw `exchange` value.
+pub fn display_name(name: &str) -> &str {
+    get(name).map_or(name, |e| e.display_name.as_str())
+}
+
+pub fn order_link(exchange: &str, symbol: &str, order_id: &str) -> Option<String> {
+    get(exchange).and_then(|e| e.order_link(symbol, order_id))
+}
+
+pub(crate) fn read_cookie(req: &HttpRequest, cookie: &str) -> Option<String> {
+    req.headers()
+        .get_all(header::COOKIE)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == cookie)
+        .map(|(_, value)| value.to_string())
+}
+
+/// The `exchange` filter of pages and API routes taking a
+/// [`SelectedExchange`].
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExchangeQuery {
+    /// Exchange name; overrides the selector cookie.
+    pub exchange: Option<String>,
+}
+
+/// The exchange a page is filtered to: the `exchange` query parameter, else
+/// the selector cookie. `None` means all exchanges.
+#[derive(Debug, Clone, Default)]
+pub struct SelectedExchange(pub Option<String>);
+
+impl SelectedExchange {
+    fn read(req: &HttpRequest) -> Self {
+        let from_query = actix_web::web::Query::<ExchangeQuery>::from_query(req.query_string())
+            .ok()
+            .and_then(|q| q.into_inner().exchange);
+        Self(
+            from_query
+                .or_else(|| read_cookie(req, EXCHANGE_COOKIE))
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty()),
+        )
+    }
+
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+
+    /// Whether rows of `exchange` are shown, matched like the SQL filters.
+    pub fn includes(&self, exchange: &str) -> bool {
+        self.0
+            .as_deref()
+            .is_none_or(|e| e.eq_ignore_ascii_case(exchange))
+    }
+}
+
+impl FromRequest for SelectedExchange {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Infallible>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(SelectedExchange::read(req)))
+    }
+}
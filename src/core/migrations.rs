@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use tracing::info;
+
+/// Arbitrary key for the advisory lock that serialises migrations when
+/// several aggregator instances start against the same database.
+const MIGRATION_LOCK_KEY: i64 = 0x7765_6261_6767;
+
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// Schema owned by the aggregator itself. The trading bot's tables are never
+/// touched here; applied versions are tracked in `webaggregator_migrations`
+/// so we do not collide with the bot's own migration bookkeeping.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "pg_stat_snapshots",
+        sql: include_str!("../../migrations/0001_pg_stat_snapshots.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "pg_stat_snapshot_wal",
+        sql: include_str!("../../migrations/0002_pg_stat_snapshot_wal.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "symbol_catalog",
+        sql: include_str!("../../migrations/0003_symbol_catalog.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "journal",
+        sql: include_str!("../../migrations/0004_journal.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "table_views",
+        sql: include_str!("../../migrations/0005_table_views.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "reports",
+        sql: include_str!("../../migrations/0006_reports.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "report_delivery_attempts",
+        sql: include_str!("../../migrations/0007_report_delivery_attempts.sql"),
+    },
+];
+
+pub async fn run(pool: &PgPool) -> Result<()> {
+    sqlx::raw_sql(
+        r#"
+        CREATE TABLE IF NOT EXISTS webaggregator_migrations (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        );
+        "#,
+    )
+    .execute(pool)
+    .await
+    .context("Failed to create migrations table")?;
+
+    for migration in MIGRATIONS {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let applied: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM webaggregator_migrations WHERE version = $1)",
+        )
+        .bind(migration.version)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if applied {
+            tx.commit().await?;
+            continue;
+        }
+
+        sqlx::raw_sql(migration.sql)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to apply migration {}", migration.name))?;
+
+        sqlx::query("INSERT INTO webaggregator_migrations (version, name) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        info!(
+            "Applied migration {} ({})",
+            migration.version, migration.name
+        );
+    }
+
+    Ok(())
+}
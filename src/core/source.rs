@@ -0,0 +1,68 @@
+use crate::core::app_state::AppState;
+use crate::services::exchange_service::ExchangeOverview;
+use serde::Serialize;
+use std::sync::OnceLock;
+use utoipa::ToSchema;
+
+/// Names of the configured data sources, default first, for the source
+/// switcher in the base template.
+static SOURCES: OnceLock<Vec<String>> = OnceLock::new();
+
+tokio::task_local! {
+    /// Source of the request being served under a `/s/{source}` prefix.
+    static CURRENT: String;
+}
+
+pub fn init(names: Vec<String>) {
+    let _ = SOURCES.set(names);
+}
+
+pub fn all() -> &'static [String] {
+    SOURCES.get().map_or(&[], Vec::as_slice)
+}
+
+pub fn is_multi() -> bool {
+    all().len() > 1
+}
+
+/// The source the current request is served from.
+pub fn current() -> String {
+    CURRENT
+        .try_with(String::clone)
+        .unwrap_or_else(|_| all().first().cloned().unwrap_or_default())
+}
+
+/// Route prefix of the current request, empty for unprefixed routes.
+pub fn prefix() -> String {
+    CURRENT
+        .try_with(|name| source_prefix(name))
+        .unwrap_or_default()
+}
+
+pub fn source_prefix(name: &str) -> String {
+    format!("/s/{}", name)
+}
+
+/// `path` under the current request's prefix, for links and redirects.
+pub fn url(path: &str) -> String {
+    format!("{}{}", prefix(), path)
+}
+
+/// Runs `fut`, a request served under `name`'s prefix, with `name` as the
+/// current source.
+pub async fn scope<F: Future>(name: String, fut: F) -> F::Output {
+    CURRENT.scope(name, fut).await
+}
+
+/// Every source's service set, for the combined overview.
+pub struct DataSources {
+    pub sources: Vec<(String, AppState)>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SourceOverview {
+    pub name: String,
+    pub prefix: String,
+    /// `None` when the source's database could not be queried.
+    pub overview: Option<ExchangeOverview>,
+}
@@ -1,33 +1,35 @@
-use crate::api::templates::BalanceTemplate;
+use crate::api::table::TableQuery;
 use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{BALANCE, resolve_view, table_filter, table_page};
 use actix_web::{HttpResponse, Result as ActixResult, web};
-use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn balances(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn balances(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
+    let view = resolve_view(&state, BALANCE, query.into_inner()).await?;
+    let filter = table_filter(BALANCE, &view)?;
     let balances = state
         .balance_service
-        .get_balances(1000)
+        .get_balances(exchange.as_deref(), &filter)
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
             actix_web::error::ErrorInternalServerError("Service error")
         })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            BalanceTemplate {
-                balances,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(
+        HttpResponse::Ok(),
+        BALANCE,
+        &balances,
+        view,
+        exchange,
+        start,
+    )
 }
@@ -1,14 +1,40 @@
-use crate::api::templates::BotsTemplate;
+use crate::api::templates::{BotPerformanceTemplate, BotsTemplate};
 use crate::core::app_state::AppState;
+use crate::core::exchange::{ExchangeQuery, SelectedExchange};
+use crate::repositories::JournalKeys;
+use crate::services::bot_service::{PerformanceGroup, PerformanceReport};
 use actix_web::{HttpResponse, Result as ActixResult, web};
 use askama::Template;
+use serde::Deserialize;
 use std::time::Instant;
 use tracing::error;
+use utoipa::IntoParams;
 
-pub async fn bots(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn bots(
+    state: web::Data<AppState>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let stats = state.bot_service.get_bots_with_stats().await.map_err(|e| {
+    let stats = state
+        .bot_service
+        .get_bots_with_stats(exchange.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    let keys = JournalKeys {
+        symbols: stats
+            .bots
+            .iter()
+            .filter_map(|(_, bot, _)| bot.symbol.as_deref().map(str::to_uppercase))
+            .collect(),
+        order_ids: Vec::new(),
+        span: JournalKeys::span(stats.bots.iter().map(|(_, bot, _)| bot.updated_at)),
+    };
+    let journal = state.journal_service.get_index(&keys).await.map_err(|e| {
         error!("Service error: {}", e);
         actix_web::error::ErrorInternalServerError("Service error")
     })?;
@@ -17,9 +43,9 @@ pub async fn bots(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
         .content_type("text/html; charset=utf-8")
         .body(
             BotsTemplate {
-                bots: stats.bots,
-                init_balance: stats.init_balance,
-                final_balance: stats.final_balance,
+                stats,
+                journal,
+                exchange,
                 elapsed_ms: start.elapsed().as_millis(),
             }
             .render()
@@ -29,3 +55,103 @@ pub async fn bots(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
             })?,
         ))
 }
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PerformanceQuery {
+    #[serde(default)]
+    pub group: PerformanceGroup,
+}
+
+async fn load_performance(
+    state: &AppState,
+    query: &PerformanceQuery,
+    exchange: &SelectedExchange,
+) -> ActixResult<PerformanceReport> {
+    state
+        .bot_service
+        .get_performance(query.group, exchange.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })
+}
+
+pub async fn bot_performance(
+    state: web::Data<AppState>,
+    query: web::Query<PerformanceQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let report = load_performance(&state, &query, &exchange).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            BotPerformanceTemplate {
+                report,
+                exchange,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/bots/performance",
+    tag = "bots",
+    params(PerformanceQuery, ExchangeQuery),
+    responses(
+        (status = 200, body = PerformanceReport),
+        (status = 400, description = "Invalid query"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn bot_performance_json(
+    state: web::Data<AppState>,
+    query: web::Query<PerformanceQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
+    let report = load_performance(&state, &query, &exchange).await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// The grouped rows only, one per line.
+pub async fn bot_performance_csv(
+    state: web::Data<AppState>,
+    query: web::Query<PerformanceQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
+    let report = load_performance(&state, &query, &exchange).await?;
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in &report.rows {
+        writer.serialize(row).map_err(|e| {
+            error!("CSV error: {}", e);
+            actix_web::error::ErrorInternalServerError("CSV error")
+        })?;
+    }
+    let body = writer.into_inner().map_err(|e| {
+        error!("CSV error: {}", e);
+        actix_web::error::ErrorInternalServerError("CSV error")
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"bot-performance-{}.csv\"",
+                report.group.as_str()
+            ),
+        ))
+        .body(body))
+}
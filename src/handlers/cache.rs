@@ -0,0 +1,26 @@
+use crate::api::templates::CacheTemplate;
+use crate::core::app_state::AppState;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+pub async fn cache(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let caches = state.cache_registry.stats();
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            CacheTemplate {
+                caches,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
@@ -1,12 +1,13 @@
 use crate::api::models::Currency;
 use crate::api::templates::CurrenciesTemplate;
 use crate::core::app_state::AppState;
-use actix_web::{HttpResponse, Result as ActixResult, web};
+use crate::core::conditional::Validators;
+use actix_web::{HttpRequest, HttpResponse, Result as ActixResult, web};
 use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn currencies(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn currencies(req: HttpRequest, state: web::Data<AppState>) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
     let currencies = state.currency_service.get_currencies().await.map_err(|e| {
@@ -20,7 +21,17 @@ pub async fn currencies(state: web::Data<AppState>) -> ActixResult<HttpResponse>
         .map(|(i, v)| (i + 1, v))
         .collect();
 
-    Ok(HttpResponse::Ok()
+    let validators = Validators::new(
+        &req,
+        "currencies",
+        currencies.iter().map(|(_, c)| c.updated_at),
+    );
+    if let Some(not_modified) = validators.not_modified(&req) {
+        return Ok(not_modified);
+    }
+
+    Ok(validators
+        .apply(&mut HttpResponse::Ok())
         .content_type("text/html; charset=utf-8")
         .body(
             CurrenciesTemplate {
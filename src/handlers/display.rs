@@ -0,0 +1,79 @@
+use crate::api::templates::DisplayTemplate;
+use crate::core::display::{self, LOCALE_COOKIE, Locale, TIMEZONE_COOKIE};
+use crate::core::source;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use chrono_tz::{TZ_VARIANTS, Tz};
+use serde::Deserialize;
+use std::time::Instant;
+use tracing::error;
+
+pub async fn display_settings(req: HttpRequest) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let back = req
+        .headers()
+        .get(header::REFERER)
+        .and_then(|r| r.to_str().ok())
+        .and_then(local_path)
+        .unwrap_or_else(|| source::url("/"));
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            DisplayTemplate {
+                settings: display::current(),
+                default: display::default(),
+                timezones: &TZ_VARIANTS,
+                locales: Locale::ALL,
+                back,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[derive(Deserialize)]
+pub struct DisplayForm {
+    pub timezone: String,
+    pub locale: String,
+    #[serde(default)]
+    pub back: String,
+}
+
+pub async fn save_display_settings(form: web::Form<DisplayForm>) -> ActixResult<HttpResponse> {
+    let timezone: Tz = form
+        .timezone
+        .parse()
+        .map_err(|_| actix_web::error::ErrorBadRequest("Unknown timezone"))?;
+    let locale = Locale::parse(&form.locale)
+        .ok_or_else(|| actix_web::error::ErrorBadRequest("Unknown locale"))?;
+    let back = local_path(&form.back).unwrap_or_else(|| source::url("/"));
+
+    // A year, so the choice survives browser restarts.
+    let cookie = |name: &str, value: &str| {
+        format!("{}={}; Path=/; Max-Age=31536000; SameSite=Lax", name, value)
+    };
+
+    Ok(HttpResponse::SeeOther()
+        .append_header((header::SET_COOKIE, cookie(TIMEZONE_COOKIE, timezone.name())))
+        .append_header((header::SET_COOKIE, cookie(LOCALE_COOKIE, locale.code())))
+        .insert_header((header::LOCATION, back))
+        .finish())
+}
+
+/// Path and query of `url` when it is on this server, so that the redirect
+/// back cannot leave the site. Browsers read a leading `/\` like `//`.
+pub(crate) fn local_path(url: &str) -> Option<String> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => &rest[rest.find('/')?..],
+        None => url,
+    };
+    (path.starts_with('/') && !path.starts_with("//") && !path.starts_with("/\\"))
+        .then(|| path.to_string())
+}
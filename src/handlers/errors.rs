@@ -1,29 +1,28 @@
-use crate::api::templates::ErrorsTemplate;
+use crate::api::table::TableQuery;
 use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{ERRORS, resolve_view, table_filter, table_page};
 use actix_web::{HttpResponse, Result as ActixResult, web};
-use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn errors(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn errors(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let errors = state.error_service.get_errors().await.map_err(|e| {
-        error!("Service error: {}", e);
-        actix_web::error::ErrorInternalServerError("Service error")
-    })?;
+    let view = resolve_view(&state, ERRORS, query.into_inner()).await?;
+    let filter = table_filter(ERRORS, &view)?;
+    let errors = state
+        .error_service
+        .get_errors(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            ErrorsTemplate {
-                errors,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(HttpResponse::Ok(), ERRORS, &errors, view, exchange, start)
 }
@@ -1,75 +1,84 @@
-use crate::api::templates::{EventsTemplate, MsgEventTemplate, MsgSendTemplate};
+use crate::api::table::TableQuery;
 use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{EVENTS, MSGEVENT, MSGSEND, resolve_view, table_filter, table_page};
 use actix_web::{HttpResponse, Result as ActixResult, web};
-use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn events(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn events(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let events = state.event_service.get_events().await.map_err(|e| {
-        error!("Service error: {}", e);
-        actix_web::error::ErrorInternalServerError("Service error")
-    })?;
+    let view = resolve_view(&state, EVENTS, query.into_inner()).await?;
+    let filter = table_filter(EVENTS, &view)?;
+    let events = state
+        .event_service
+        .get_events(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            EventsTemplate {
-                events,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(HttpResponse::Ok(), EVENTS, &events, view, exchange, start)
 }
 
-pub async fn msgevent(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn msgevent(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let msgevents = state.msgevent_service.get_msgevents().await.map_err(|e| {
-        error!("Service error: {}", e);
-        actix_web::error::ErrorInternalServerError("Service error")
-    })?;
+    let view = resolve_view(&state, MSGEVENT, query.into_inner()).await?;
+    let filter = table_filter(MSGEVENT, &view)?;
+    let msgevents = state
+        .msgevent_service
+        .get_msgevents(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            MsgEventTemplate {
-                msgevents,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(
+        HttpResponse::Ok(),
+        MSGEVENT,
+        &msgevents,
+        view,
+        exchange,
+        start,
+    )
 }
 
-pub async fn msgsend(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn msgsend(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let msgsends = state.msgsend_service.get_msgsends().await.map_err(|e| {
-        error!("Service error: {}", e);
-        actix_web::error::ErrorInternalServerError("Service error")
-    })?;
+    let view = resolve_view(&state, MSGSEND, query.into_inner()).await?;
+    let filter = table_filter(MSGSEND, &view)?;
+    let msgsends = state
+        .msgsend_service
+        .get_msgsends(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            MsgSendTemplate {
-                msgsend: msgsends,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(
+        HttpResponse::Ok(),
+        MSGSEND,
+        &msgsends,
+        view,
+        exchange,
+        start,
+    )
 }
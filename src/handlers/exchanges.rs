@@ -0,0 +1,92 @@
+use crate::api::templates::ExchangesTemplate;
+use crate::core::app_state::AppState;
+use crate::core::exchange::{EXCHANGE_COOKIE, SelectedExchange};
+use crate::core::source;
+use crate::handlers::display::local_path;
+use crate::services::exchange_service::ExchangeOverview;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+/// Stores the selector's choice in a cookie and goes back to the page it was
+/// made on. An empty exchange clears the filter.
+pub async fn select_exchange(req: HttpRequest, exchange: SelectedExchange) -> HttpResponse {
+    let cookie = match exchange.as_deref() {
+        Some(name)
+            if name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') =>
+        {
+            format!("{}={}; Path=/; SameSite=Lax", EXCHANGE_COOKIE, name)
+        }
+        _ => format!("{}=; Path=/; Max-Age=0; SameSite=Lax", EXCHANGE_COOKIE),
+    };
+    let back = req
+        .headers()
+        .get(header::REFERER)
+        .and_then(|r| r.to_str().ok())
+        .and_then(local_path)
+        .unwrap_or_else(|| source::url("/"));
+
+    HttpResponse::SeeOther()
+        .insert_header((header::SET_COOKIE, cookie))
+        .insert_header((header::LOCATION, back))
+        .finish()
+}
+
+pub(crate) async fn load_overview(state: &AppState) -> ActixResult<ExchangeOverview> {
+    let bots = state
+        .bot_service
+        .get_bots_with_stats(None)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    state
+        .exchange_service
+        .get_overview(&bots.open_by_exchange())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })
+}
+
+pub async fn exchanges(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let overview = load_overview(&state).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            ExchangesTemplate {
+                overview,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/exchanges",
+    tag = "exchanges",
+    responses(
+        (status = 200, body = ExchangeOverview),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn exchanges_json(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let overview = load_overview(&state).await?;
+
+    Ok(HttpResponse::Ok().json(overview))
+}
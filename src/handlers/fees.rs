@@ -0,0 +1,75 @@
+use crate::api::templates::FeesTemplate;
+use crate::core::app_state::AppState;
+use crate::services::fee_service::{FeeQuote, FeeRequest};
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+pub async fn fees(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let fees = state.fee_service.get_effective_fees().await.map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+
+    let comparisons = state
+        .fee_service
+        .get_fee_comparisons(&fees)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            FeesTemplate {
+                fees,
+                comparisons,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/fees/calculate",
+    tag = "fees",
+    params(FeeRequest),
+    responses(
+        (status = 200, body = FeeQuote),
+        (status = 400, description = "Invalid query"),
+        (status = 404, description = "Unknown symbol"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn fee_calculator(
+    state: web::Data<AppState>,
+    query: web::Query<FeeRequest>,
+) -> ActixResult<HttpResponse> {
+    if !(query.size > 0.0 && query.price > 0.0) {
+        return Err(actix_web::error::ErrorBadRequest(
+            "size and price must be positive",
+        ));
+    }
+
+    let quote = state
+        .fee_service
+        .calculate(&query)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown symbol"))?;
+
+    Ok(HttpResponse::Ok().json(quote))
+}
@@ -0,0 +1,25 @@
+use crate::api::graphql::TradingSchema;
+use crate::core::app_state::AppState;
+use crate::core::source;
+use actix_web::{HttpResponse, web};
+use async_graphql::http::{GraphQLPlaygroundConfig, playground_source};
+
+/// Runs a query against the current source. Query errors are reported in
+/// the response's `errors`, as GraphQL clients expect, not as HTTP errors.
+pub async fn graphql(
+    state: web::Data<AppState>,
+    schema: web::Data<TradingSchema>,
+    request: web::Json<async_graphql::Request>,
+) -> HttpResponse {
+    let request = request.into_inner().data(state.get_ref().clone());
+
+    HttpResponse::Ok().json(schema.execute(request).await)
+}
+
+pub async fn graphql_playground() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(playground_source(GraphQLPlaygroundConfig::new(
+            &source::url("/graphql"),
+        )))
+}
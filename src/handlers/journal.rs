@@ -0,0 +1,85 @@
+use crate::api::templates::JournalTemplate;
+use crate::core::app_state::AppState;
+use crate::core::source;
+use crate::services::journal_service::{JournalForm, JournalQuery};
+use actix_web::http::header;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+pub async fn journal(
+    state: web::Data<AppState>,
+    query: web::Query<JournalQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let entries = state
+        .journal_service
+        .get_entries(&query)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            JournalTemplate {
+                entries,
+                query: query.into_inner(),
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+pub async fn create_journal_entry(
+    state: web::Data<AppState>,
+    form: web::Form<JournalForm>,
+) -> ActixResult<HttpResponse> {
+    let entry = form
+        .into_inner()
+        .into_entry()
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    state
+        .journal_service
+        .create_entry(&entry)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header((header::LOCATION, source::url("/journal")))
+        .finish())
+}
+
+pub async fn delete_journal_entry(
+    state: web::Data<AppState>,
+    path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let deleted = state
+        .journal_service
+        .delete_entry(path.into_inner())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    if !deleted {
+        return Err(actix_web::error::ErrorNotFound("Unknown journal entry"));
+    }
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header((header::LOCATION, source::url("/journal")))
+        .finish())
+}
@@ -0,0 +1,29 @@
+use crate::api::templates::LoansTemplate;
+use crate::core::app_state::AppState;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+pub async fn loans(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let ledger = state.loan_service.get_ledger().await.map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            LoansTemplate {
+                ledger,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
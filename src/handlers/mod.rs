@@ -1,12 +1,25 @@
 pub mod balance;
 pub mod bots;
+pub mod cache;
 pub mod currency;
+pub mod display;
 pub mod errors;
 pub mod events;
+pub mod exchanges;
+pub mod fees;
+pub mod graphql;
 pub mod index;
+pub mod journal;
+pub mod loans;
 pub mod orders;
 pub mod pg;
 pub mod position;
+pub mod reports;
+pub mod search;
+pub mod sources;
+pub mod stream;
 pub mod symbol;
 pub mod system;
+pub mod table;
 pub mod ticker;
+pub mod views;
@@ -1,23 +1,71 @@
-use crate::api::templates::EventOrderTemplate;
+use crate::api::table::TableQuery;
+use crate::api::templates::OrderValidationTemplate;
 use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{EVENT_ORDERS, resolve_view, table_filter, table_page};
+use crate::repositories::JournalKeys;
+use crate::services::order_validation_service::{OrderRequest, OrderValidation};
 use actix_web::{HttpResponse, Result as ActixResult, web};
 use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn eventorders(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn eventorders(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let event_orders = state.order_service.get_event_orders().await.map_err(|e| {
+    let view = resolve_view(&state, EVENT_ORDERS, query.into_inner()).await?;
+    let filter = table_filter(EVENT_ORDERS, &view)?;
+    let event_orders = state
+        .order_service
+        .get_event_orders(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    let keys = JournalKeys {
+        symbols: Vec::new(),
+        order_ids: event_orders.iter().map(|o| o.order_id.clone()).collect(),
+        span: JournalKeys::span(event_orders.iter().map(|o| o.updated_at)),
+    };
+    let journal = state.journal_service.get_index(&keys).await.map_err(|e| {
         error!("Service error: {}", e);
         actix_web::error::ErrorInternalServerError("Service error")
     })?;
+    let orders = journal.annotate_orders(event_orders);
+
+    table_page(
+        HttpResponse::Ok(),
+        EVENT_ORDERS,
+        &orders,
+        view,
+        exchange,
+        start,
+    )
+}
+
+pub async fn order_validation(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let retrospective = state
+        .order_validation_service
+        .get_retrospective()
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
 
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(
-            EventOrderTemplate {
-                event_orders,
+            OrderValidationTemplate {
+                retrospective,
                 elapsed_ms: start.elapsed().as_millis(),
             }
             .render()
@@ -27,3 +75,32 @@ pub async fn eventorders(state: web::Data<AppState>) -> ActixResult<HttpResponse
             })?,
         ))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/orders/validate",
+    tag = "orders",
+    params(OrderRequest),
+    responses(
+        (status = 200, body = OrderValidation),
+        (status = 400, description = "Invalid query"),
+        (status = 404, description = "Unknown symbol"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn validate_order(
+    state: web::Data<AppState>,
+    query: web::Query<OrderRequest>,
+) -> ActixResult<HttpResponse> {
+    let validation = state
+        .order_validation_service
+        .validate(&query)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown symbol"))?;
+
+    Ok(HttpResponse::Ok().json(validation))
+}
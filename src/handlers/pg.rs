@@ -1,7 +1,10 @@
-use crate::api::templates::PgTemplate;
+use crate::api::templates::{
+    PgExplainTemplate, PgReplicationTemplate, PgSnapshotsTemplate, PgTemplate,
+};
 use crate::core::app_state::AppState;
 use actix_web::{HttpResponse, Result as ActixResult, web};
 use askama::Template;
+use serde::Deserialize;
 use std::time::Instant;
 use tracing::error;
 
@@ -31,3 +34,106 @@ pub async fn pg(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
             })?,
         ))
 }
+
+#[derive(Deserialize)]
+pub struct SnapshotQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+pub async fn pg_snapshots(
+    state: web::Data<AppState>,
+    query: web::Query<SnapshotQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let history = state
+        .pg_service
+        .get_snapshot_history(query.from, query.to)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown snapshot"))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            PgSnapshotsTemplate {
+                snapshots: history.snapshots,
+                diff: history.diff,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[derive(Deserialize)]
+pub struct ExplainQuery {
+    pub name: Option<String>,
+    /// Runs `EXPLAIN ANALYZE` on every query instead of only listing them.
+    #[serde(default)]
+    pub all: bool,
+}
+
+pub async fn pg_explain(
+    state: web::Data<AppState>,
+    query: web::Query<ExplainQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let results = match &query.name {
+        Some(name) => vec![state.pg_service.explain_query(name).await.map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorNotFound("Unknown query")
+        })?],
+        None if query.all => state.pg_service.explain_all().await,
+        None => state.pg_service.registered_queries(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            PgExplainTemplate {
+                detailed: query.name.is_some(),
+                analyzed: query.name.is_some() || query.all,
+                results,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+pub async fn pg_replication(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let status = state.pg_service.get_replication_status().await;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            PgReplicationTemplate {
+                servers: status.servers,
+                replicas: status.replicas,
+                slots: status.slots,
+                archiver: status.archiver,
+                wal_rate: status.wal_rate,
+                errors: status.errors,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
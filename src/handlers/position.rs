@@ -1,70 +1,127 @@
-use crate::api::templates::{PositinRatioTemplate, PositionAssetTemplate, PositionDebtTemplate};
+use crate::api::table::TableQuery;
+use crate::api::templates::MarginTemplate;
 use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{
+    POSITION_ASSET, POSITION_DEBT, POSITION_RATIO, resolve_view, table_filter, table_page,
+};
+use crate::repositories::PositionSeries;
+use crate::services::position_service::{Bucket, HistoryRange, PositionHistory};
 use actix_web::{HttpResponse, Result as ActixResult, web};
 use askama::Template;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use std::time::Instant;
 use tracing::error;
+use utoipa::IntoParams;
 
-pub async fn positionasset(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn positionasset(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
+    let view = resolve_view(&state, POSITION_ASSET, query.into_inner()).await?;
+    let filter = table_filter(POSITION_ASSET, &view)?;
     let position_asset = state
         .position_service
-        .get_position_assets()
+        .get_position_assets(exchange.as_deref(), &filter)
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
             actix_web::error::ErrorInternalServerError("Service error")
         })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            PositionAssetTemplate {
-                position_asset,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(
+        HttpResponse::Ok(),
+        POSITION_ASSET,
+        &position_asset,
+        view,
+        exchange,
+        start,
+    )
 }
 
-pub async fn positiondebt(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn positiondebt(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
+    let view = resolve_view(&state, POSITION_DEBT, query.into_inner()).await?;
+    let filter = table_filter(POSITION_DEBT, &view)?;
     let position_debt = state
         .position_service
-        .get_position_debts()
+        .get_position_debts(exchange.as_deref(), &filter)
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
             actix_web::error::ErrorInternalServerError("Service error")
         })?;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .body(
-            PositionDebtTemplate {
-                position_debt,
-                elapsed_ms: start.elapsed().as_millis(),
-            }
-            .render()
-            .map_err(|e| {
-                error!("Template render error: {}", e);
-                actix_web::error::ErrorInternalServerError("Template render error")
-            })?,
-        ))
+    table_page(
+        HttpResponse::Ok(),
+        POSITION_DEBT,
+        &position_debt,
+        view,
+        exchange,
+        start,
+    )
 }
 
-pub async fn positionratio(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn positionratio(
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
+    let view = resolve_view(&state, POSITION_RATIO, query.into_inner()).await?;
+    let filter = table_filter(POSITION_RATIO, &view)?;
     let position_ratio = state
         .position_service
-        .get_position_ratios()
+        .get_position_ratios(exchange.as_deref(), &filter)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    table_page(
+        HttpResponse::Ok(),
+        POSITION_RATIO,
+        &position_ratio,
+        view,
+        exchange,
+        start,
+    )
+}
+
+#[derive(Deserialize)]
+pub struct MarginQuery {
+    /// Stress test price shock in percent, applied both ways.
+    pub shock: Option<f64>,
+}
+
+pub async fn margin(
+    state: web::Data<AppState>,
+    query: web::Query<MarginQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let config = state.margin_service.config().clone();
+    let shock_pct = query.shock.unwrap_or(config.stress_shock_pct);
+    if !(shock_pct > 0.0 && shock_pct < 100.0) {
+        return Err(actix_web::error::ErrorBadRequest(
+            "shock must be between 0 and 100",
+        ));
+    }
+
+    let risks = state
+        .margin_service
+        .get_margin_risk(shock_pct)
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
@@ -74,8 +131,10 @@ pub async fn positionratio(state: web::Data<AppState>) -> ActixResult<HttpRespon
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(
-            PositinRatioTemplate {
-                position_ratio,
+            MarginTemplate {
+                risks,
+                config,
+                shock_pct,
                 elapsed_ms: start.elapsed().as_millis(),
             }
             .render()
@@ -85,3 +144,51 @@ pub async fn positionratio(state: web::Data<AppState>) -> ActixResult<HttpRespon
             })?,
         ))
 }
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PositionHistoryQuery {
+    pub series: PositionSeries,
+    /// RFC 3339 timestamps; the window defaults to the last 24 hours.
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub bucket: Option<Bucket>,
+    pub exchange: Option<String>,
+    /// Metric for `ratio`, currency for `debt` and `asset`.
+    pub key: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/positions/history",
+    tag = "positions",
+    params(PositionHistoryQuery),
+    responses(
+        (status = 200, body = PositionHistory),
+        (status = 400, description = "Invalid query"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn position_history(
+    state: web::Data<AppState>,
+    query: web::Query<PositionHistoryQuery>,
+) -> ActixResult<HttpResponse> {
+    let range = HistoryRange::new(query.from, query.to, query.bucket, Utc::now())
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let history = state
+        .position_service
+        .get_position_history(
+            query.series,
+            range,
+            query.exchange.as_deref(),
+            query.key.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(history))
+}
@@ -0,0 +1,119 @@
+use crate::api::models::Report;
+use crate::api::templates::{ReportTemplate, ReportsTemplate};
+use crate::core::app_state::AppState;
+use crate::core::source;
+use crate::services::report_service::ReportPeriod;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use serde::Deserialize;
+use std::time::Instant;
+use tracing::error;
+
+#[derive(Deserialize)]
+pub struct ReportsQuery {
+    pub period: Option<ReportPeriod>,
+}
+
+async fn load_report(state: &AppState, id: i64) -> ActixResult<Report> {
+    state
+        .report_service
+        .get_report(id)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown report"))
+}
+
+pub async fn reports(
+    state: web::Data<AppState>,
+    query: web::Query<ReportsQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let reports = state
+        .report_service
+        .get_reports(query.period)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            ReportsTemplate {
+                reports,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+pub async fn report(state: web::Data<AppState>, path: web::Path<i64>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let report = load_report(&state, path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            ReportTemplate {
+                report,
+                has_webhook: state.report_service.has_webhook(),
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+pub async fn report_markdown(
+    state: web::Data<AppState>,
+    path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let report = load_report(&state, path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/markdown; charset=utf-8")
+        .body(report.markdown))
+}
+
+/// Posts a report to the webhook again, e.g. after a failed delivery.
+pub async fn deliver_report(
+    state: web::Data<AppState>,
+    path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    if !state.report_service.has_webhook() {
+        return Err(actix_web::error::ErrorBadRequest(
+            "No report webhook configured",
+        ));
+    }
+    let report = load_report(&state, path.into_inner()).await?;
+
+    state
+        .report_service
+        .deliver(&report, &source::current())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header((
+            header::LOCATION,
+            source::url(&format!("/reports/{}", report.id)),
+        ))
+        .finish())
+}
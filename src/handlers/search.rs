@@ -0,0 +1,108 @@
+use crate::api::models::SearchResults;
+use crate::api::templates::SearchTemplate;
+use crate::core::app_state::AppState;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use serde::Deserialize;
+use std::time::Instant;
+use tracing::error;
+use utoipa::IntoParams;
+
+/// Hits returned per table.
+const SEARCH_LIMIT: i64 = 100;
+const MAX_TERM_LEN: usize = 200;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Looks `q` up in every table at once. An empty term matches nothing.
+async fn run_search(state: &AppState, query: &SearchQuery) -> ActixResult<SearchResults> {
+    let term = query.q.trim();
+    if term.len() > MAX_TERM_LEN {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "q is longer than {} bytes",
+            MAX_TERM_LEN
+        )));
+    }
+    if term.is_empty() {
+        return Ok(SearchResults {
+            term: String::new(),
+            orders: Vec::new(),
+            msgsends: Vec::new(),
+            msgevents: Vec::new(),
+            balances: Vec::new(),
+            bots: Vec::new(),
+            symbols: Vec::new(),
+        });
+    }
+
+    let (orders, msgsends, msgevents, balances, bots, symbols) = tokio::try_join!(
+        state.order_service.search_event_orders(term, SEARCH_LIMIT),
+        state.msgsend_service.search_msgsends(term, SEARCH_LIMIT),
+        state.msgevent_service.search_msgevents(term, SEARCH_LIMIT),
+        state.balance_service.search_balances(term, SEARCH_LIMIT),
+        state.bot_service.search_bots(term, SEARCH_LIMIT),
+        state
+            .symbol_service
+            .search_symbols(term, SEARCH_LIMIT as usize),
+    )
+    .map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+
+    Ok(SearchResults {
+        term: term.to_string(),
+        orders,
+        msgsends,
+        msgevents,
+        balances,
+        bots,
+        symbols,
+    })
+}
+
+pub async fn search(
+    state: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let results = run_search(&state, &query).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            SearchTemplate {
+                results,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, body = SearchResults),
+        (status = 400, description = "Invalid query"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn search_json(
+    state: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(run_search(&state, &query).await?))
+}
@@ -0,0 +1,56 @@
+use crate::api::templates::SourcesTemplate;
+use crate::core::source::{self, DataSources, SourceOverview};
+use crate::handlers::exchanges::load_overview;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use askama::Template;
+use std::time::Instant;
+use tracing::{error, warn};
+
+/// One source failing does not hide the others.
+async fn load_sources(sources: &DataSources) -> Vec<SourceOverview> {
+    let loads = sources.sources.iter().map(|(name, state)| async move {
+        let overview = load_overview(state).await;
+        if let Err(e) = &overview {
+            warn!("Overview of source {} failed: {}", name, e);
+        }
+        SourceOverview {
+            name: name.clone(),
+            prefix: source::source_prefix(name),
+            overview: overview.ok(),
+        }
+    });
+
+    futures::future::join_all(loads).await
+}
+
+pub async fn sources(sources: web::Data<DataSources>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let sources = load_sources(&sources).await;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            SourcesTemplate {
+                sources,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sources",
+    tag = "sources",
+    responses(
+        (status = 200, body = [SourceOverview]),
+    )
+)]
+pub async fn sources_json(sources: web::Data<DataSources>) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(load_sources(&sources).await))
+}
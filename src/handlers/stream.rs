@@ -0,0 +1,249 @@
+use crate::core::app_state::AppState;
+use crate::repositories::stream_repository::RowKey;
+use crate::services::stream_service::{ReplayError, StreamEvent, Topic, TopicFilter};
+use actix_web::{HttpRequest, HttpResponse, web};
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream, Session};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error};
+
+/// Largest frame a client may send; its messages are small commands.
+const MAX_FRAME: usize = 16 * 1024;
+/// Heartbeats a client may miss before it is disconnected.
+const MISSED_HEARTBEATS: u32 = 3;
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        topic: Topic,
+        #[serde(default)]
+        filter: TopicFilter,
+        /// `at` of the last event received, to replay what was missed.
+        /// Events at `since` itself are sent again; their `id`s tell which
+        /// were already received.
+        since: Option<DateTime<Utc>>,
+    },
+    Unsubscribe {
+        topic: Topic,
+    },
+    Ping,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    Subscribed {
+        topic: Topic,
+        replayed: usize,
+    },
+    Unsubscribed {
+        topic: Topic,
+    },
+    Event(&'a StreamEvent),
+    Heartbeat {
+        at: DateTime<Utc>,
+    },
+    Pong,
+    /// Events were dropped because the client read too slowly; resubscribe
+    /// with `since` to recover them.
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+struct Subscription {
+    filter: TopicFilter,
+    /// Events up to this `seq` were broadcast before the subscription and
+    /// are either replayed or not wanted.
+    after_seq: u64,
+    /// Replayed events, which a poll may still broadcast when it read them
+    /// after the replay did.
+    replayed: HashSet<RowKey>,
+}
+
+impl Subscription {
+    fn wants(&mut self, event: &StreamEvent) -> bool {
+        event.seq > self.after_seq
+            && self.filter.matches(event)
+            && (self.replayed.is_empty() || !self.replayed.remove(&event.key()))
+    }
+}
+
+/// Upgrades to a WebSocket pushing the current source's new rows to the
+/// topics the client subscribes to.
+pub async fn stream(
+    req: HttpRequest,
+    body: web::Payload,
+    state: web::Data<AppState>,
+) -> actix_web::Result<HttpResponse> {
+    let (response, session, messages) = actix_ws::handle(&req, body)?;
+
+    actix_web::rt::spawn(run_session(
+        state.into_inner(),
+        session,
+        messages.max_frame_size(MAX_FRAME),
+    ));
+
+    Ok(response)
+}
+
+async fn run_session(
+    state: std::sync::Arc<AppState>,
+    mut session: Session,
+    mut messages: MessageStream,
+) {
+    let service = &state.stream_service;
+    let mut events = service.subscribe();
+    let mut subscriptions: HashMap<Topic, Subscription> = HashMap::new();
+
+    let period = service.heartbeat();
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    let mut last_seen = Instant::now();
+
+    let reason = loop {
+        let sent = tokio::select! {
+            message = messages.recv() => {
+                last_seen = Instant::now();
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_message(&state, &mut session, &mut subscriptions, &text).await
+                    }
+                    Some(Ok(Message::Binary(_))) => {
+                        send(&mut session, &ServerMessage::Error {
+                            message: "Messages must be JSON text".to_string(),
+                        })
+                        .await
+                    }
+                    Some(Ok(Message::Ping(bytes))) => session.pong(&bytes).await.is_ok(),
+                    Some(Ok(Message::Close(reason))) => break reason,
+                    Some(Ok(_)) => true,
+                    Some(Err(e)) => {
+                        debug!("WebSocket protocol error: {}", e);
+                        break Some(CloseCode::Protocol.into());
+                    }
+                    None => break None,
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let wanted = subscriptions
+                        .get_mut(&event.topic)
+                        .is_some_and(|subscription| subscription.wants(&event));
+                    !wanted || send(&mut session, &ServerMessage::Event(&event)).await
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    send(&mut session, &ServerMessage::Lagged { missed }).await
+                }
+                Err(RecvError::Closed) => break Some(CloseCode::Restart.into()),
+            },
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > period * MISSED_HEARTBEATS {
+                    break Some(CloseReason {
+                        code: CloseCode::Away,
+                        description: Some("Heartbeat timeout".to_string()),
+                    });
+                }
+                send(&mut session, &ServerMessage::Heartbeat { at: Utc::now() }).await
+                    && session.ping(b"").await.is_ok()
+            }
+        };
+        if !sent {
+            // The client is gone.
+            return;
+        }
+    };
+
+    let _ = session.close(reason).await;
+}
+
+/// Whether the reply could be sent.
+async fn handle_message(
+    state: &AppState,
+    session: &mut Session,
+    subscriptions: &mut HashMap<Topic, Subscription>,
+    text: &str,
+) -> bool {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            let message = format!("Invalid message: {}", e);
+            return send(session, &ServerMessage::Error { message }).await;
+        }
+    };
+
+    match message {
+        ClientMessage::Subscribe {
+            topic,
+            filter,
+            since,
+        } => {
+            if let Err(message) = filter.validate(topic) {
+                return send(session, &ServerMessage::Error { message }).await;
+            }
+            let service = &state.stream_service;
+            let position = service.position(topic);
+
+            let mut replayed = Vec::new();
+            if let Some(since) = since {
+                match service
+                    .replay(topic, since, position.polled_to, &filter)
+                    .await
+                {
+                    Ok(events) => replayed = events,
+                    Err(e) => {
+                        let message = match e {
+                            ReplayError::Rejected(message) => message,
+                            ReplayError::Failed(e) => {
+                                error!("Service error: {}", e);
+                                "Replay failed".to_string()
+                            }
+                        };
+                        return send(session, &ServerMessage::Error { message }).await;
+                    }
+                }
+            }
+
+            let subscription = Subscription {
+                filter,
+                after_seq: position.seq,
+                replayed: replayed.iter().map(StreamEvent::key).collect(),
+            };
+            subscriptions.insert(topic, subscription);
+            let subscribed = ServerMessage::Subscribed {
+                topic,
+                replayed: replayed.len(),
+            };
+            if !send(session, &subscribed).await {
+                return false;
+            }
+            for event in &replayed {
+                if !send(session, &ServerMessage::Event(event)).await {
+                    return false;
+                }
+            }
+            true
+        }
+        ClientMessage::Unsubscribe { topic } => {
+            subscriptions.remove(&topic);
+            send(session, &ServerMessage::Unsubscribed { topic }).await
+        }
+        ClientMessage::Ping => send(session, &ServerMessage::Pong).await,
+    }
+}
+
+async fn send(session: &mut Session, message: &ServerMessage<'_>) -> bool {
+    match serde_json::to_string(message) {
+        Ok(json) => session.text(json).await.is_ok(),
+        Err(e) => {
+            error!("Message serialization: {}", e);
+            true
+        }
+    }
+}
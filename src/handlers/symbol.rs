@@ -1,16 +1,107 @@
-use crate::api::templates::SymbolsTemplate;
+use crate::api::models::Symbol;
+use crate::api::table::TableQuery;
+use crate::api::templates::{SymbolChangesTemplate, UniverseTemplate, UniversesTemplate};
 use crate::core::app_state::AppState;
-use actix_web::{HttpResponse, Result as ActixResult, web};
+use crate::core::conditional::Validators;
+use crate::core::exchange::SelectedExchange;
+use crate::handlers::table::{SYMBOLS, TRADEABLE, resolve_view, table_page};
+use crate::services::symbol_change_service::{CHANGE_KINDS, SymbolChangelog};
+use actix_web::{HttpRequest, HttpResponse, Result as ActixResult, web};
 use askama::Template;
+use serde::Deserialize;
 use std::time::Instant;
 use tracing::error;
+use utoipa::IntoParams;
 
-pub async fn symbols(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn symbols(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let symbols = state
+    let mut symbols = state.symbol_service.get_all_symbols().await.map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+    symbols.retain(|s| exchange.includes(&s.exchange));
+
+    let view = resolve_view(&state, SYMBOLS, query.into_inner()).await?;
+    let validators = Validators::new(
+        &req,
+        &format!(
+            "symbols-{}-{}",
+            exchange.as_deref().unwrap_or_default(),
+            view.fingerprint()
+        ),
+        symbols.iter().map(|s| s.updated_at),
+    );
+    if let Some(not_modified) = validators.not_modified(&req) {
+        return Ok(not_modified);
+    }
+
+    let mut response = HttpResponse::Ok();
+    validators.apply(&mut response);
+    table_page(response, SYMBOLS, &symbols, view, exchange, start)
+}
+
+/// Members of the `tradeable` universe.
+pub async fn tradeable(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<TableQuery>,
+    exchange: SelectedExchange,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let universe = state
+        .symbol_service
+        .universe("tradeable")
+        .ok_or_else(|| actix_web::error::ErrorNotFound("No tradeable universe configured"))?;
+
+    let evaluation = state
+        .symbol_service
+        .evaluate_universe(universe)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?;
+    let members = evaluation.fingerprint();
+    let symbols: Vec<Symbol> = evaluation
+        .members
+        .into_iter()
+        .map(|(_, symbol)| symbol)
+        .filter(|s| exchange.includes(&s.exchange))
+        .collect();
+
+    let view = resolve_view(&state, TRADEABLE, query.into_inner()).await?;
+    let validators = Validators::new(
+        &req,
+        &format!(
+            "tradeable-{}-{}-{}",
+            members,
+            exchange.as_deref().unwrap_or_default(),
+            view.fingerprint()
+        ),
+        symbols.iter().map(|s| s.updated_at),
+    );
+    if let Some(not_modified) = validators.not_modified(&req) {
+        return Ok(not_modified);
+    }
+
+    let mut response = HttpResponse::Ok();
+    validators.apply(&mut response);
+    table_page(response, TRADEABLE, &symbols, view, exchange, start)
+}
+
+pub async fn universes(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let universes = state
         .symbol_service
-        .get_symbols_with_index(false)
+        .get_universe_summaries()
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
@@ -20,8 +111,8 @@ pub async fn symbols(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(
-            SymbolsTemplate {
-                symbols,
+            UniversesTemplate {
+                universes,
                 elapsed_ms: start.elapsed().as_millis(),
             }
             .render()
@@ -32,23 +123,136 @@ pub async fn symbols(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
         ))
 }
 
-pub async fn tradeable(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+#[derive(Deserialize)]
+pub struct UniverseQuery {
+    /// Symbol to explain.
+    pub symbol: Option<String>,
+}
+
+pub async fn universe(
+    state: web::Data<AppState>,
+    name: web::Path<String>,
+    query: web::Query<UniverseQuery>,
+) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
-    let symbols = state
+    let universe = state
+        .symbol_service
+        .universe(&name)
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown universe"))?;
+
+    let evaluation = state
         .symbol_service
-        .get_symbols_with_index(true)
+        .evaluate_universe(universe)
         .await
         .map_err(|e| {
             error!("Service error: {}", e);
             actix_web::error::ErrorInternalServerError("Service error")
         })?;
 
+    let explain = query
+        .symbol
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let explanation = if explain.is_empty() {
+        None
+    } else {
+        state
+            .symbol_service
+            .explain(universe, &explain)
+            .await
+            .map_err(|e| {
+                error!("Service error: {}", e);
+                actix_web::error::ErrorInternalServerError("Service error")
+            })?
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            UniverseTemplate {
+                universe: universe.clone(),
+                members: evaluation.members,
+                excluded: evaluation.excluded,
+                explain,
+                explanation,
+                elapsed_ms: start.elapsed().as_millis(),
+            }
+            .render()
+            .map_err(|e| {
+                error!("Template render error: {}", e);
+                actix_web::error::ErrorInternalServerError("Template render error")
+            })?,
+        ))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SymbolChangeQuery {
+    pub symbol: Option<String>,
+    pub field: Option<String>,
+    pub kind: Option<String>,
+}
+
+impl SymbolChangeQuery {
+    /// Empty form inputs mean "no filter".
+    fn param(value: &Option<String>) -> Option<String> {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(String::from)
+    }
+}
+
+async fn load_changelog(
+    state: &AppState,
+    query: &SymbolChangeQuery,
+) -> ActixResult<SymbolChangelog> {
+    let kind = SymbolChangeQuery::param(&query.kind);
+    if let Some(kind) = &kind
+        && !CHANGE_KINDS.contains(&kind.as_str())
+    {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "Unknown change kind {}",
+            kind
+        )));
+    }
+
+    state
+        .symbol_change_service
+        .get_changelog(
+            SymbolChangeQuery::param(&query.symbol),
+            SymbolChangeQuery::param(&query.field),
+            kind,
+        )
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })
+}
+
+pub async fn symbol_changes(
+    state: web::Data<AppState>,
+    query: web::Query<SymbolChangeQuery>,
+) -> ActixResult<HttpResponse> {
+    let start = Instant::now();
+
+    let changelog = load_changelog(&state, &query).await?;
+
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
         .body(
-            SymbolsTemplate {
-                symbols,
+            SymbolChangesTemplate {
+                latest: changelog.latest,
+                changes: changelog.changes,
+                symbol: SymbolChangeQuery::param(&query.symbol).unwrap_or_default(),
+                field: SymbolChangeQuery::param(&query.field).unwrap_or_default(),
+                kind: SymbolChangeQuery::param(&query.kind).unwrap_or_default(),
+                kinds: CHANGE_KINDS,
                 elapsed_ms: start.elapsed().as_millis(),
             }
             .render()
@@ -58,3 +262,23 @@ pub async fn tradeable(state: web::Data<AppState>) -> ActixResult<HttpResponse>
             })?,
         ))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/symbols/changes",
+    tag = "symbols",
+    params(SymbolChangeQuery),
+    responses(
+        (status = 200, body = SymbolChangelog),
+        (status = 400, description = "Invalid query"),
+        (status = 500, description = "Service error"),
+    )
+)]
+pub async fn symbol_changes_json(
+    state: web::Data<AppState>,
+    query: web::Query<SymbolChangeQuery>,
+) -> ActixResult<HttpResponse> {
+    let changelog = load_changelog(&state, &query).await?;
+
+    Ok(HttpResponse::Ok().json(changelog))
+}
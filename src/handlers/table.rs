@@ -0,0 +1,147 @@
+use crate::api::models::{
+    Balance, Error, Event, MsgEvent, MsgSend, PositionAsset, PositionDebt, PositionRatio, Symbol,
+};
+use crate::api::table::{Table, TablePage, TableQuery, TableRow};
+use crate::api::templates::TableTemplate;
+use crate::core::app_state::AppState;
+use crate::core::exchange::SelectedExchange;
+use crate::repositories::TableFilter;
+use crate::services::journal_service::AnnotatedOrder;
+use crate::services::view_service::ResolvedView;
+use actix_web::{HttpResponse, HttpResponseBuilder, Result as ActixResult};
+use askama::Template;
+use std::time::Instant;
+use tracing::error;
+
+pub(crate) const BALANCE: TablePage = TablePage {
+    path: "/balance",
+    title: "Balance",
+    columns: Balance::COLUMNS,
+    limited: true,
+};
+pub(crate) const ERRORS: TablePage = TablePage {
+    path: "/errors",
+    title: "Errors",
+    columns: Error::COLUMNS,
+    limited: true,
+};
+pub(crate) const EVENTS: TablePage = TablePage {
+    path: "/events",
+    title: "Events",
+    columns: Event::COLUMNS,
+    limited: true,
+};
+pub(crate) const EVENT_ORDERS: TablePage = TablePage {
+    path: "/eventorder",
+    title: "Event Orders",
+    columns: AnnotatedOrder::COLUMNS,
+    limited: true,
+};
+pub(crate) const MSGEVENT: TablePage = TablePage {
+    path: "/msgevent",
+    title: "MsgEvent",
+    columns: MsgEvent::COLUMNS,
+    limited: true,
+};
+pub(crate) const MSGSEND: TablePage = TablePage {
+    path: "/msgsend",
+    title: "MsgSend",
+    columns: MsgSend::COLUMNS,
+    limited: true,
+};
+pub(crate) const POSITION_ASSET: TablePage = TablePage {
+    path: "/positionasset",
+    title: "Position Asset",
+    columns: PositionAsset::COLUMNS,
+    limited: true,
+};
+pub(crate) const POSITION_DEBT: TablePage = TablePage {
+    path: "/positiondebt",
+    title: "Position Debt",
+    columns: PositionDebt::COLUMNS,
+    limited: true,
+};
+pub(crate) const POSITION_RATIO: TablePage = TablePage {
+    path: "/positionratio",
+    title: "Position Ratio",
+    columns: PositionRatio::COLUMNS,
+    limited: true,
+};
+pub(crate) const SYMBOLS: TablePage = TablePage {
+    path: "/symbols",
+    title: "Symbols",
+    columns: Symbol::COLUMNS,
+    limited: false,
+};
+pub(crate) const TRADEABLE: TablePage = TablePage {
+    path: "/tradeable",
+    title: "Tradeable",
+    columns: Symbol::COLUMNS,
+    limited: false,
+};
+
+/// Pages that views can be saved for.
+pub(crate) const TABLE_PAGES: &[TablePage] = &[
+    BALANCE,
+    ERRORS,
+    EVENTS,
+    EVENT_ORDERS,
+    MSGEVENT,
+    MSGSEND,
+    POSITION_ASSET,
+    POSITION_DEBT,
+    POSITION_RATIO,
+    SYMBOLS,
+    TRADEABLE,
+];
+
+/// Loads `page`'s saved views and picks the one `query` asks for.
+pub(crate) async fn resolve_view(
+    state: &AppState,
+    page: TablePage,
+    query: TableQuery,
+) -> ActixResult<ResolvedView> {
+    let views = state.view_service.get_views(page.path).await.map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+
+    ResolvedView::new(query, views).map_err(actix_web::error::ErrorNotFound)
+}
+
+/// The filters and sort of `view` for a limited `page`'s query.
+pub(crate) fn table_filter(page: TablePage, view: &ResolvedView) -> ActixResult<TableFilter> {
+    view.query
+        .table_filter(page.columns)
+        .map_err(actix_web::error::ErrorBadRequest)
+}
+
+/// Renders `rows` as `page` with the columns, sort and filter of `view`.
+/// `start` is when the handler started, for the page's timing.
+pub(crate) fn table_page<T: TableRow>(
+    mut response: HttpResponseBuilder,
+    page: TablePage,
+    rows: &[T],
+    view: ResolvedView,
+    exchange: SelectedExchange,
+    start: Instant,
+) -> ActixResult<HttpResponse> {
+    let table =
+        Table::build(rows, &view.query, page.limited).map_err(actix_web::error::ErrorBadRequest)?;
+
+    Ok(response.content_type("text/html; charset=utf-8").body(
+        TableTemplate {
+            page,
+            table,
+            views: view.views,
+            active: view.active,
+            exchange,
+            elapsed_ms: start.elapsed().as_millis(),
+        }
+        .render()
+        .map_err(|e| {
+            error!("Template render error: {}", e);
+            actix_web::error::ErrorInternalServerError("Template render error")
+        })?,
+    ))
+}
@@ -1,11 +1,12 @@
 use crate::api::templates::TickersTemplate;
 use crate::core::app_state::AppState;
-use actix_web::{HttpResponse, Result as ActixResult, web};
+use crate::core::conditional::Validators;
+use actix_web::{HttpRequest, HttpResponse, Result as ActixResult, web};
 use askama::Template;
 use std::time::Instant;
 use tracing::error;
 
-pub async fn tickers(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
+pub async fn tickers(req: HttpRequest, state: web::Data<AppState>) -> ActixResult<HttpResponse> {
     let start = Instant::now();
 
     let tickers = state
@@ -17,7 +18,13 @@ pub async fn tickers(state: web::Data<AppState>) -> ActixResult<HttpResponse> {
             actix_web::error::ErrorInternalServerError("Service error")
         })?;
 
-    Ok(HttpResponse::Ok()
+    let validators = Validators::new(&req, "tickers", tickers.iter().map(|(_, t)| t.updated_at));
+    if let Some(not_modified) = validators.not_modified(&req) {
+        return Ok(not_modified);
+    }
+
+    Ok(validators
+        .apply(&mut HttpResponse::Ok())
         .content_type("text/html; charset=utf-8")
         .body(
             TickersTemplate {
@@ -0,0 +1,84 @@
+use crate::api::models::TableView;
+use crate::api::table::url_encode;
+use crate::core::app_state::AppState;
+use crate::core::source;
+use crate::handlers::table::TABLE_PAGES;
+use crate::services::view_service::ViewForm;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Result as ActixResult, web};
+use serde::Deserialize;
+use tracing::error;
+
+pub async fn save_view(
+    state: web::Data<AppState>,
+    form: web::Form<ViewForm>,
+) -> ActixResult<HttpResponse> {
+    let form = form.into_inner();
+    let page = TABLE_PAGES
+        .iter()
+        .find(|p| p.path == form.page)
+        .ok_or_else(|| actix_web::error::ErrorBadRequest("Unknown table page"))?;
+    let view = form
+        .into_view(page.columns)
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let view = state.view_service.save_view(&view).await.map_err(|e| {
+        error!("Service error: {}", e);
+        actix_web::error::ErrorInternalServerError("Service error")
+    })?;
+
+    Ok(redirect_to(&view, true))
+}
+
+pub async fn delete_view(
+    state: web::Data<AppState>,
+    path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    let view = state
+        .view_service
+        .delete_view(path.into_inner())
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown view"))?;
+
+    Ok(redirect_to(&view, false))
+}
+
+#[derive(Deserialize)]
+pub struct DefaultViewForm {
+    pub is_default: bool,
+}
+
+pub async fn set_default_view(
+    state: web::Data<AppState>,
+    path: web::Path<i64>,
+    form: web::Form<DefaultViewForm>,
+) -> ActixResult<HttpResponse> {
+    let view = state
+        .view_service
+        .set_default(path.into_inner(), form.is_default)
+        .await
+        .map_err(|e| {
+            error!("Service error: {}", e);
+            actix_web::error::ErrorInternalServerError("Service error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown view"))?;
+
+    Ok(redirect_to(&view, view.is_default))
+}
+
+/// Back to the view's page, showing the view when `show` is set.
+fn redirect_to(view: &TableView, show: bool) -> HttpResponse {
+    let location = if show {
+        source::url(&format!("{}?view={}", view.page, url_encode(&view.name)))
+    } else {
+        source::url(&view.page)
+    };
+
+    HttpResponse::SeeOther()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
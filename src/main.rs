@@ -1,38 +1,64 @@
 mod api {
+    pub mod graphql;
     pub mod models;
+    pub mod openapi;
+    pub mod table;
     pub mod templates;
 }
 mod core {
     pub mod app_state;
+    pub mod cache;
+    pub mod conditional;
+    pub mod db;
+    pub mod display;
     pub mod error;
+    pub mod exchange;
+    pub mod migrations;
+    pub mod source;
 }
 mod config;
 mod handlers;
 mod repositories;
 mod services;
 
-use crate::config::AppConfig;
+use crate::api::openapi::{ApiDoc, api_routes};
+use crate::config::{AppConfig, DatabaseConfig};
 use crate::core::app_state::AppState;
+use crate::core::db::DbPools;
+use crate::core::source::DataSources;
 use crate::handlers::{
     balance::balances,
-    bots::bots,
+    bots::{bot_performance, bot_performance_csv, bots},
+    cache::cache,
     currency::currencies,
+    display::{display_settings, save_display_settings},
     errors::errors,
     events::{events, msgevent, msgsend},
+    exchanges::{exchanges, select_exchange},
+    fees::fees,
+    graphql::{graphql, graphql_playground},
     index::index,
-    orders::eventorders,
-    pg::pg,
-    position::{positionasset, positiondebt, positionratio},
-    symbol::{symbols, tradeable},
+    journal::{create_journal_entry, delete_journal_entry, journal},
+    loans::loans,
+    orders::{eventorders, order_validation},
+    pg::{pg, pg_explain, pg_replication, pg_snapshots},
+    position::{margin, positionasset, positiondebt, positionratio},
+    reports::{deliver_report, report, report_markdown, reports},
+    search::search,
+    sources::sources,
+    stream::stream,
+    symbol::{symbol_changes, symbols, tradeable, universe, universes},
     system::{favicon, serve_css},
     ticker::tickers,
+    views::{delete_view, save_view, set_default_view},
 };
+use actix_web::dev::Service;
 use actix_web::{App, HttpServer, middleware, web};
 use anyhow::{Context, Result};
 use dotenvy::dotenv;
-use sqlx::PgPool;
-use sqlx::postgres::PgPoolOptions;
 use tracing::info;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 fn init_tracing(config: &config::LoggingConfig) {
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -50,38 +76,139 @@ fn init_tracing(config: &config::LoggingConfig) {
     }
 }
 
-async fn create_db_pool(config: &config::DatabaseConfig) -> Result<PgPool> {
-    Ok(PgPoolOptions::new()
-        .max_connections(config.max_connections)
-        .min_connections(config.min_connections)
-        .acquire_timeout(config.acquire_timeout)
-        .idle_timeout(config.idle_timeout)
-        .max_lifetime(config.max_lifetime)
-        .connect(&config.url)
-        .await
-        .context("Failed to connect to PostgreSQL")?)
-}
-
 fn routes(cfg: &mut web::ServiceConfig) {
-    use web::get;
+    use web::{get, post};
     cfg.route("/", get().to(index))
         .route("/pg", get().to(pg))
+        .route("/pg/snapshots", get().to(pg_snapshots))
+        .route("/pg/explain", get().to(pg_explain))
+        .route("/pg/replication", get().to(pg_replication))
         .route("/events", get().to(events))
         .route("/errors", get().to(errors))
+        .route("/exchange", get().to(select_exchange))
+        .route("/exchanges", get().to(exchanges))
+        .route("/search", get().to(search))
+        .route("/sources", get().to(sources))
         .route("/balance", get().to(balances))
         .route("/eventorder", get().to(eventorders))
+        .route("/orders/validation", get().to(order_validation))
         .route("/positiondebt", get().to(positiondebt))
         .route("/msgevent", get().to(msgevent))
         .route("/msgsend", get().to(msgsend))
         .route("/positionasset", get().to(positionasset))
         .route("/positionratio", get().to(positionratio))
+        .route("/margin", get().to(margin))
+        .route("/loans", get().to(loans))
         .route("/tradeable", get().to(tradeable))
+        .route("/universes", get().to(universes))
+        .route("/universes/{name}", get().to(universe))
         .route("/tickers", get().to(tickers))
         .route("/currencies", get().to(currencies))
         .route("/symbols", get().to(symbols))
+        .route("/symbols/changes", get().to(symbol_changes))
         .route("/bots", get().to(bots))
+        .route("/bots/performance", get().to(bot_performance))
+        .route("/bots/performance.csv", get().to(bot_performance_csv))
+        .route("/journal", get().to(journal))
+        .route("/journal", post().to(create_journal_entry))
+        .route("/journal/{id}/delete", post().to(delete_journal_entry))
+        .route("/display", get().to(display_settings))
+        .route("/display", post().to(save_display_settings))
+        .route("/views", post().to(save_view))
+        .route("/views/{id}/delete", post().to(delete_view))
+        .route("/views/{id}/default", post().to(set_default_view))
+        .route("/fees", get().to(fees))
+        .route("/graphql", get().to(graphql_playground))
+        .route("/graphql", post().to(graphql))
+        .route("/ws", get().to(stream))
+        .route("/reports", get().to(reports))
+        .route("/reports/{id}", get().to(report))
+        .route("/reports/{id}/markdown", get().to(report_markdown))
+        .route("/reports/{id}/deliver", post().to(deliver_report))
+        .route("/cache", get().to(cache))
         .route("/static/style.css", get().to(serve_css))
-        .route("/favicon.png", get().to(favicon));
+        .route("/favicon.png", get().to(favicon))
+        .configure(api_routes);
+}
+
+/// Connects a source's database, migrates it and starts its background
+/// tasks.
+async fn start_source(
+    name: &str,
+    database: &DatabaseConfig,
+    config: &AppConfig,
+) -> Result<AppState> {
+    let pools = DbPools::connect(database)
+        .await
+        .with_context(|| format!("Data source {}", name))?;
+    info!("Database connected for source {}", name);
+
+    core::migrations::run(pools.primary.get()).await?;
+
+    if config.cache.install_triggers {
+        core::cache::install_notify_triggers(pools.primary.get()).await?;
+        info!("Cache NOTIFY triggers installed for source {}", name);
+    }
+
+    let app_state = AppState::new(&pools, config);
+
+    let cache_registry = app_state.cache_registry.clone();
+    let listener_pool = pools.primary.direct().clone();
+    tokio::spawn(async move { cache_registry.run_listener(listener_pool).await });
+
+    tokio::spawn(async move { pools.run_health_checks().await });
+
+    if let Some(interval) = config.snapshots.interval {
+        let pg_service = app_state.pg_service.clone();
+        let retention = config.snapshots.retention;
+        tokio::spawn(async move { pg_service.run_sampler(interval, retention).await });
+        info!(
+            "pg_stat snapshot sampler every {}s for source {}",
+            interval.as_secs(),
+            name
+        );
+    }
+
+    if let Some(interval) = config.snapshots.symbol_interval {
+        let symbol_change_service = app_state.symbol_change_service.clone();
+        let retention = config.snapshots.symbol_retention;
+        tokio::spawn(async move { symbol_change_service.run_tracker(interval, retention).await });
+        info!(
+            "Symbol catalog tracker every {}s for source {}",
+            interval.as_secs(),
+            name
+        );
+    }
+
+    if let Some(interval) = config.stream.poll_interval {
+        let stream_service = app_state.stream_service.clone();
+        let bot_interval = config.stream.bot_poll_interval;
+        tokio::spawn(async move { stream_service.run_poller(interval, bot_interval).await });
+        info!(
+            "WebSocket feed polling every {}ms for source {}",
+            interval.as_millis(),
+            name
+        );
+    }
+
+    if !config.reports.periods.is_empty() {
+        let report_service = app_state.report_service.clone();
+        let source = name.to_string();
+        let periods = config.reports.periods.clone();
+        let interval = config.reports.check_interval;
+        tokio::spawn(async move {
+            report_service
+                .run_scheduler(source, periods, interval)
+                .await
+        });
+        info!(
+            "Report scheduler every {}s for source {}",
+            interval.as_secs(),
+            name
+        );
+    }
+
+    Ok(app_state)
 }
 
 #[actix_web::main]
@@ -92,19 +219,59 @@ async fn main() -> Result<()> {
 
     init_tracing(&config.logging);
 
-    let pool = create_db_pool(&config.database).await?;
-    info!("Database connected");
+    core::exchange::init(config.exchanges.exchanges.clone());
+    core::display::init(core::display::DisplaySettings {
+        timezone: config.display.timezone,
+        locale: config.display.locale,
+    });
+
+    let default_name = &config.sources.default_name;
+    let mut sources = vec![(
+        default_name.clone(),
+        start_source(default_name, &config.database, &config).await?,
+    )];
+    for source in &config.sources.extra {
+        let app_state = start_source(&source.name, &source.database, &config).await?;
+        sources.push((source.name.clone(), app_state));
+    }
+    core::source::init(sources.iter().map(|(name, _)| name.clone()).collect());
+
+    // Unprefixed routes serve the default source.
+    let app_state = sources[0].1.clone();
+    let data_sources = web::Data::new(DataSources { sources });
 
-    let app_state = AppState::new(pool);
+    let schema = web::Data::new(api::graphql::schema(&config.graphql));
 
     let server_addr = config.server_addr();
     let workers = config.server.workers;
 
     let server = HttpServer::new(move || {
-        App::new()
+        let mut app = App::new()
             .app_data(web::Data::new(app_state.clone()))
+            .app_data(data_sources.clone())
+            .app_data(schema.clone())
+            .wrap_fn(|req, srv| {
+                let settings = core::display::DisplaySettings::read(req.request());
+                core::display::scope(settings, srv.call(req))
+            })
             .wrap(middleware::Compress::default())
-            .configure(routes)
+            .service(
+                SwaggerUi::new("/api/docs/{_:.*}").url("/api/openapi.json", ApiDoc::openapi()),
+            );
+
+        for (name, state) in &data_sources.sources {
+            let source = name.clone();
+            let prefix = core::source::source_prefix(name);
+            app = app.service(web::redirect(prefix.clone(), format!("{}/", prefix)));
+            app = app.service(
+                web::scope(&prefix)
+                    .app_data(web::Data::new(state.clone()))
+                    .wrap_fn(move |req, srv| core::source::scope(source.clone(), srv.call(req)))
+                    .configure(routes),
+            );
+        }
+
+        app.configure(routes)
     })
     .bind(&server_addr)
     .with_context(|| format!("Failed to bind server to {}", server_addr))?
@@ -1,39 +1,98 @@
 use crate::api::models::Balance;
-use crate::repositories::RepositoryResult;
+use crate::core::db::DbPool;
+use crate::repositories::{LinkFilter, RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_BALANCES_SQL: &str = r#"
+    SELECT exchange, account_id, available, available_change, currency,
+           hold_value, hold_change, relation_event, relation_event_id,
+           event_time, total, symbol, order_id, trade_id, updated_at
+    FROM balance
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+/// Balance changes caused by an order, trade or event id, or on a symbol.
+pub(crate) const SEARCH_BALANCES_SQL: &str = r#"
+    SELECT exchange, account_id, available, available_change, currency,
+           hold_value, hold_change, relation_event, relation_event_id,
+           event_time, total, symbol, order_id, trade_id, updated_at
+    FROM balance
+    WHERE order_id = $1 OR trade_id = $1 OR relation_event_id = $1 OR symbol = upper($1)
+    ORDER BY updated_at DESC
+    LIMIT $2
+"#;
+
+/// Balance changes linked by order id, symbol or currency.
+pub(crate) const GET_LINKED_BALANCES_SQL: &str = r#"
+    SELECT exchange, account_id, available, available_change, currency,
+           hold_value, hold_change, relation_event, relation_event_id,
+           event_time, total, symbol, order_id, trade_id, updated_at
+    FROM balance
+    WHERE ($1::text IS NULL OR exchange = $1)
+      AND (order_id = ANY($2) OR symbol = ANY($3) OR currency = ANY($4))
+    ORDER BY updated_at DESC
+    LIMIT $5
+"#;
 
 #[async_trait]
 pub trait BalanceRepository: Send + Sync {
-    async fn get_balances(&self, limit: i64) -> RepositoryResult<Vec<Balance>>;
+    async fn get_balances(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Balance>>;
+    async fn search_balances(&self, term: &str, limit: i64) -> RepositoryResult<Vec<Balance>>;
+    async fn get_linked_balances(&self, filter: &LinkFilter) -> RepositoryResult<Vec<Balance>>;
 }
 
 pub struct PostgresBalanceRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresBalanceRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl BalanceRepository for PostgresBalanceRepository {
-    async fn get_balances(&self, limit: i64) -> RepositoryResult<Vec<Balance>> {
-        let balances = sqlx::query_as::<_, Balance>(
-            r#"
-            SELECT exchange, account_id, available, available_change, currency, 
-                   hold_value, hold_change, relation_event, relation_event_id, 
-                   event_time, total, symbol, order_id, trade_id, updated_at
-            FROM balance
-            ORDER BY updated_at DESC
-            LIMIT $1
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_balances(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Balance>> {
+        let mut query =
+            sqlx::query_as::<_, Balance>(AssertSqlSafe(filter.wrap(GET_BALANCES_SQL, 1)))
+                .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let balances = query.fetch_all(self.pool.get()).await?;
+
+        Ok(balances)
+    }
+
+    async fn search_balances(&self, term: &str, limit: i64) -> RepositoryResult<Vec<Balance>> {
+        let balances = sqlx::query_as::<_, Balance>(SEARCH_BALANCES_SQL)
+            .bind(term)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(balances)
+    }
+
+    async fn get_linked_balances(&self, filter: &LinkFilter) -> RepositoryResult<Vec<Balance>> {
+        let balances = sqlx::query_as::<_, Balance>(GET_LINKED_BALANCES_SQL)
+            .bind(&filter.exchange)
+            .bind(&filter.order_ids)
+            .bind(&filter.symbols)
+            .bind(&filter.currencies)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(balances)
     }
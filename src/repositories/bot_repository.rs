@@ -1,19 +1,71 @@
-use crate::api::models::Bot;
-use crate::repositories::RepositoryResult;
+use crate::api::models::{Bot, BotOrderEvent};
+use crate::core::db::DbPool;
+use crate::repositories::{LinkFilter, RepositoryResult};
 use async_trait::async_trait;
-use sqlx::PgPool;
+
+pub(crate) const GET_BOTS_SQL: &str = r#"
+    SELECT exchange, entry_price, entry_client_oid, exit_tp_price,
+           exit_tp_order_id, exit_tp_client_oid, exit_sl_price,
+           exit_sl_order_id, exit_sl_client_oid, symbol, balance, updated_at
+    FROM bots
+    ORDER BY updated_at DESC;
+"#;
+
+/// Latest event of every entry, TP and SL order the bots reference. Entries
+/// are only known by client oid, exits by either id.
+pub(crate) const GET_BOT_ORDERS_SQL: &str = r#"
+    SELECT DISTINCT ON (o.exchange, o.order_id)
+           o.exchange, o.client_oid, o.order_id, o.type_, o.status, o.filled_size,
+           o.updated_at
+    FROM orderevent o
+    JOIN bots b ON b.exchange = o.exchange
+               AND (o.client_oid IN (b.entry_client_oid, b.exit_tp_client_oid, b.exit_sl_client_oid)
+                    OR o.order_id IN (b.exit_tp_order_id, b.exit_sl_order_id))
+    WHERE o.order_id IS NOT NULL
+    ORDER BY o.exchange, o.order_id, o.updated_at DESC
+"#;
+
+/// Bots referencing a client oid or order id, or trading a symbol.
+pub(crate) const SEARCH_BOTS_SQL: &str = r#"
+    SELECT exchange, entry_price, entry_client_oid, exit_tp_price,
+           exit_tp_order_id, exit_tp_client_oid, exit_sl_price,
+           exit_sl_order_id, exit_sl_client_oid, symbol, balance, updated_at
+    FROM bots
+    WHERE $1 IN (entry_client_oid, exit_tp_order_id, exit_tp_client_oid,
+                 exit_sl_order_id, exit_sl_client_oid)
+       OR symbol = upper($1)
+    ORDER BY updated_at DESC
+    LIMIT $2
+"#;
+
+/// Bots linked by one of their orders' ids or client oids, or by symbol.
+pub(crate) const GET_LINKED_BOTS_SQL: &str = r#"
+    SELECT exchange, entry_price, entry_client_oid, exit_tp_price,
+           exit_tp_order_id, exit_tp_client_oid, exit_sl_price,
+           exit_sl_order_id, exit_sl_client_oid, symbol, balance, updated_at
+    FROM bots
+    WHERE ($1::text IS NULL OR exchange = $1)
+      AND (exit_tp_order_id = ANY($2) OR exit_sl_order_id = ANY($2)
+           OR entry_client_oid = ANY($3) OR exit_tp_client_oid = ANY($3)
+           OR exit_sl_client_oid = ANY($3) OR symbol = ANY($4))
+    ORDER BY updated_at DESC
+    LIMIT $5
+"#;
 
 #[async_trait]
 pub trait BotRepository: Send + Sync {
     async fn get_bots(&self) -> RepositoryResult<Vec<Bot>>;
+    async fn search_bots(&self, term: &str, limit: i64) -> RepositoryResult<Vec<Bot>>;
+    async fn get_bot_orders(&self) -> RepositoryResult<Vec<BotOrderEvent>>;
+    async fn get_linked_bots(&self, filter: &LinkFilter) -> RepositoryResult<Vec<Bot>>;
 }
 
 pub struct PostgresBotRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresBotRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
@@ -21,17 +73,40 @@ impl PostgresBotRepository {
 #[async_trait]
 impl BotRepository for PostgresBotRepository {
     async fn get_bots(&self) -> RepositoryResult<Vec<Bot>> {
-        let bots = sqlx::query_as::<_, Bot>(
-            r#"
-            SELECT exchange, entry_price, entry_client_oid, exit_tp_price, 
-                   exit_tp_order_id, exit_tp_client_oid, exit_sl_price, 
-                   exit_sl_order_id, exit_sl_client_oid, symbol, balance, updated_at
-            FROM bots
-            ORDER BY updated_at DESC;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let bots = sqlx::query_as::<_, Bot>(GET_BOTS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(bots)
+    }
+
+    async fn get_bot_orders(&self) -> RepositoryResult<Vec<BotOrderEvent>> {
+        let orders = sqlx::query_as::<_, BotOrderEvent>(GET_BOT_ORDERS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(orders)
+    }
+
+    async fn search_bots(&self, term: &str, limit: i64) -> RepositoryResult<Vec<Bot>> {
+        let bots = sqlx::query_as::<_, Bot>(SEARCH_BOTS_SQL)
+            .bind(term)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(bots)
+    }
+
+    async fn get_linked_bots(&self, filter: &LinkFilter) -> RepositoryResult<Vec<Bot>> {
+        let bots = sqlx::query_as::<_, Bot>(GET_LINKED_BOTS_SQL)
+            .bind(&filter.exchange)
+            .bind(&filter.order_ids)
+            .bind(&filter.client_oids)
+            .bind(&filter.symbols)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(bots)
     }
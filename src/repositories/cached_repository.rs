@@ -0,0 +1,69 @@
+use crate::api::models::{Currency, Symbol, SymbolVolume, Ticker};
+use crate::core::cache::Cache;
+use crate::repositories::{
+    CurrencyRepository, RepositoryResult, SymbolRepository, TickerRepository,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Serves the symbol catalog from the cache, loading through `R` on a miss.
+/// Volumes come from the bot's own fills and are not cached.
+pub struct CachedSymbolRepository<R: SymbolRepository> {
+    repo: R,
+    all: Arc<Cache<Vec<Symbol>>>,
+}
+
+impl<R: SymbolRepository> CachedSymbolRepository<R> {
+    pub fn new(repo: R, all: Arc<Cache<Vec<Symbol>>>) -> Self {
+        Self { repo, all }
+    }
+}
+
+#[async_trait]
+impl<R: SymbolRepository> SymbolRepository for CachedSymbolRepository<R> {
+    async fn get_all_symbols(&self) -> RepositoryResult<Vec<Symbol>> {
+        self.all.get_or_load(|| self.repo.get_all_symbols()).await
+    }
+
+    async fn get_quote_volumes(&self, hours: i64) -> RepositoryResult<Vec<SymbolVolume>> {
+        self.repo.get_quote_volumes(hours).await
+    }
+}
+
+pub struct CachedTickerRepository<R: TickerRepository> {
+    repo: R,
+    tickers: Arc<Cache<Vec<Ticker>>>,
+}
+
+impl<R: TickerRepository> CachedTickerRepository<R> {
+    pub fn new(repo: R, tickers: Arc<Cache<Vec<Ticker>>>) -> Self {
+        Self { repo, tickers }
+    }
+}
+
+#[async_trait]
+impl<R: TickerRepository> TickerRepository for CachedTickerRepository<R> {
+    async fn get_tickers(&self) -> RepositoryResult<Vec<Ticker>> {
+        self.tickers.get_or_load(|| self.repo.get_tickers()).await
+    }
+}
+
+pub struct CachedCurrencyRepository<R: CurrencyRepository> {
+    repo: R,
+    currencies: Arc<Cache<Vec<Currency>>>,
+}
+
+impl<R: CurrencyRepository> CachedCurrencyRepository<R> {
+    pub fn new(repo: R, currencies: Arc<Cache<Vec<Currency>>>) -> Self {
+        Self { repo, currencies }
+    }
+}
+
+#[async_trait]
+impl<R: CurrencyRepository> CurrencyRepository for CachedCurrencyRepository<R> {
+    async fn get_currencies(&self) -> RepositoryResult<Vec<Currency>> {
+        self.currencies
+            .get_or_load(|| self.repo.get_currencies())
+            .await
+    }
+}
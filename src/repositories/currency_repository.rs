@@ -1,7 +1,14 @@
 use crate::api::models::Currency;
+use crate::core::db::DbPool;
 use crate::repositories::RepositoryResult;
 use async_trait::async_trait;
-use sqlx::PgPool;
+
+pub(crate) const GET_CURRENCIES_SQL: &str = r#"
+    SELECT exchange, currency, currency_name, full_name, precision,
+           is_margin_enabled, is_debit_enabled, updated_at
+    FROM currency
+    ORDER BY updated_at DESC;
+"#;
 
 #[async_trait]
 pub trait CurrencyRepository: Send + Sync {
@@ -9,11 +16,11 @@ pub trait CurrencyRepository: Send + Sync {
 }
 
 pub struct PostgresCurrencyRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresCurrencyRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
@@ -21,16 +28,9 @@ impl PostgresCurrencyRepository {
 #[async_trait]
 impl CurrencyRepository for PostgresCurrencyRepository {
     async fn get_currencies(&self) -> RepositoryResult<Vec<Currency>> {
-        let currencies = sqlx::query_as::<_, Currency>(
-            r#"
-            SELECT exchange, currency, currency_name, full_name, precision, 
-                   is_margin_enabled, is_debit_enabled, updated_at
-            FROM currency
-            ORDER BY updated_at DESC;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let currencies = sqlx::query_as::<_, Currency>(GET_CURRENCIES_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(currencies)
     }
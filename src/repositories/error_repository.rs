@@ -1,35 +1,47 @@
 use crate::api::models::Error;
-use crate::repositories::RepositoryResult;
+use crate::core::db::DbPool;
+use crate::repositories::{RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_ERRORS_SQL: &str = r#"
+    SELECT exchange, msg, updated_at
+    FROM errors
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
 
 #[async_trait]
 pub trait ErrorRepository: Send + Sync {
-    async fn get_errors(&self) -> RepositoryResult<Vec<Error>>;
+    async fn get_errors(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Error>>;
 }
 
 pub struct PostgresErrorRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresErrorRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl ErrorRepository for PostgresErrorRepository {
-    async fn get_errors(&self) -> RepositoryResult<Vec<Error>> {
-        let errors = sqlx::query_as::<_, Error>(
-            r#"
-            SELECT exchange, msg, updated_at
-            FROM errors
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_errors(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Error>> {
+        let mut query = sqlx::query_as::<_, Error>(AssertSqlSafe(filter.wrap(GET_ERRORS_SQL, 1)))
+            .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let errors = query.fetch_all(self.pool.get()).await?;
 
         Ok(errors)
     }
@@ -1,36 +1,47 @@
 use crate::api::models::Event;
-use crate::repositories::RepositoryResult;
+use crate::core::db::DbPool;
+use crate::repositories::{RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_EVENTS_SQL: &str = r#"
+    SELECT exchange, msg, updated_at
+    FROM events
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
 
 #[async_trait]
 pub trait EventRepository: Send + Sync {
-    async fn get_events(&self) -> RepositoryResult<Vec<Event>>;
+    async fn get_events(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Event>>;
 }
 
 pub struct PostgresEventRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresEventRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl EventRepository for PostgresEventRepository {
-    async fn get_events(&self) -> RepositoryResult<Vec<Event>> {
-        let events = sqlx::query_as::<_, Event>(
-            r#"
-            SELECT exchange, msg, updated_at
-            FROM events
-            ORDER BY updated_at
-            DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_events(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<Event>> {
+        let mut query = sqlx::query_as::<_, Event>(AssertSqlSafe(filter.wrap(GET_EVENTS_SQL, 1)))
+            .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let events = query.fetch_all(self.pool.get()).await?;
 
         Ok(events)
     }
@@ -0,0 +1,94 @@
+use crate::api::models::ExchangeActivity;
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+
+/// Every exchange seen in the bot's tables. `$1` is the error-rate window in
+/// seconds.
+pub(crate) const GET_EXCHANGE_ACTIVITY_SQL: &str = r#"
+    WITH ratios AS (
+        SELECT DISTINCT ON (exchange) exchange, total_asset, total_debt
+        FROM positionratio
+        ORDER BY exchange, updated_at DESC
+    ), bot_counts AS (
+        SELECT exchange, count(*) AS bots
+        FROM bots
+        WHERE exchange IS NOT NULL
+        GROUP BY exchange
+    ), error_counts AS (
+        SELECT exchange, count(*) AS errors
+        FROM errors
+        WHERE updated_at >= now() - $1 * interval '1 second'
+        GROUP BY exchange
+    ), event_counts AS (
+        SELECT exchange, count(*) AS events
+        FROM events
+        WHERE updated_at >= now() - $1 * interval '1 second'
+        GROUP BY exchange
+    ), updates AS (
+        SELECT exchange, max(updated_at) AS last_update
+        FROM (
+            SELECT exchange, max(updated_at) AS updated_at FROM orderevent GROUP BY exchange
+            UNION ALL
+            SELECT exchange, max(updated_at) FROM balance GROUP BY exchange
+            UNION ALL
+            SELECT exchange, max(updated_at) FROM events GROUP BY exchange
+            UNION ALL
+            SELECT exchange, max(updated_at) FROM positionratio GROUP BY exchange
+            UNION ALL
+            SELECT exchange, max(updated_at) FROM ticker GROUP BY exchange
+        ) u
+        GROUP BY exchange
+    ), exchanges AS (
+        SELECT exchange FROM ratios
+        UNION SELECT exchange FROM bot_counts
+        UNION SELECT exchange FROM error_counts
+        UNION SELECT exchange FROM event_counts
+        UNION SELECT exchange FROM updates
+    )
+    SELECT x.exchange, r.total_asset, r.total_debt,
+           coalesce(b.bots, 0) AS bots,
+           coalesce(er.errors, 0) AS errors,
+           coalesce(ev.events, 0) AS events,
+           u.last_update
+    FROM exchanges x
+    LEFT JOIN ratios r ON r.exchange = x.exchange
+    LEFT JOIN bot_counts b ON b.exchange = x.exchange
+    LEFT JOIN error_counts er ON er.exchange = x.exchange
+    LEFT JOIN event_counts ev ON ev.exchange = x.exchange
+    LEFT JOIN updates u ON u.exchange = x.exchange
+    ORDER BY x.exchange
+"#;
+
+#[async_trait]
+pub trait ExchangeRepository: Send + Sync {
+    async fn get_exchange_activity(
+        &self,
+        window_secs: i64,
+    ) -> RepositoryResult<Vec<ExchangeActivity>>;
+}
+
+pub struct PostgresExchangeRepository {
+    pool: DbPool,
+}
+
+impl PostgresExchangeRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ExchangeRepository for PostgresExchangeRepository {
+    async fn get_exchange_activity(
+        &self,
+        window_secs: i64,
+    ) -> RepositoryResult<Vec<ExchangeActivity>> {
+        let activity = sqlx::query_as::<_, ExchangeActivity>(GET_EXCHANGE_ACTIVITY_SQL)
+            .bind(window_secs)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(activity)
+    }
+}
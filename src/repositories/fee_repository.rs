@@ -0,0 +1,67 @@
+use crate::api::models::{ChargedFee, FeeSchedule};
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+
+pub(crate) const GET_FEE_SCHEDULES_SQL: &str = r#"
+    SELECT s.exchange, s.symbol, s.fee_category, s.fee_currency, s.quote_currency,
+           t.taker_fee_rate, t.maker_fee_rate, t.taker_coefficient, t.maker_coefficient,
+           s.taker_fee_coefficient, s.maker_fee_coefficient
+    FROM symbol s
+    LEFT JOIN ticker t ON t.exchange = s.exchange AND t.symbol = s.symbol
+    ORDER BY s.symbol
+"#;
+
+/// Matches paired with the quote-currency balance change they settled, from
+/// which the fee actually charged is implied. Only symbols whose fee is
+/// paid in the quote currency can be reconciled this way.
+pub(crate) const GET_CHARGED_FEES_SQL: &str = r#"
+    SELECT o.exchange, o.symbol, o.side, o.liquidity, o.trade_id,
+           o.match_size, o.match_price, b.available_change, o.updated_at
+    FROM orderevent o
+    JOIN symbol s ON s.exchange = o.exchange AND s.symbol = o.symbol
+    JOIN balance b ON b.exchange = o.exchange
+                  AND b.trade_id = o.trade_id
+                  AND b.relation_event = 'trade.setted'
+                  AND b.currency = s.quote_currency
+    WHERE o.type_ = 'match'
+      AND s.fee_currency = s.quote_currency
+    ORDER BY o.updated_at DESC
+    LIMIT $1
+"#;
+
+#[async_trait]
+pub trait FeeRepository: Send + Sync {
+    async fn get_fee_schedules(&self) -> RepositoryResult<Vec<FeeSchedule>>;
+    async fn get_charged_fees(&self, limit: i64) -> RepositoryResult<Vec<ChargedFee>>;
+}
+
+pub struct PostgresFeeRepository {
+    pool: DbPool,
+}
+
+impl PostgresFeeRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FeeRepository for PostgresFeeRepository {
+    async fn get_fee_schedules(&self) -> RepositoryResult<Vec<FeeSchedule>> {
+        let schedules = sqlx::query_as::<_, FeeSchedule>(GET_FEE_SCHEDULES_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(schedules)
+    }
+
+    async fn get_charged_fees(&self, limit: i64) -> RepositoryResult<Vec<ChargedFee>> {
+        let fees = sqlx::query_as::<_, ChargedFee>(GET_CHARGED_FEES_SQL)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(fees)
+    }
+}
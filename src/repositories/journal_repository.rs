@@ -0,0 +1,139 @@
+use crate::api::models::{JournalEntry, NewJournalEntry};
+use crate::core::db::DbPool;
+use crate::repositories::{RepositoryResult, escape_like};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub(crate) const GET_JOURNAL_ENTRIES_SQL: &str = r#"
+    SELECT id, created_at, bot_symbol, order_id, range_start, range_end, note, tags
+    FROM journal_entry
+    WHERE ($1::text IS NULL OR $1 = ANY (tags))
+      AND ($2::text IS NULL OR bot_symbol ILIKE '%' || $2 || '%' ESCAPE '\')
+      AND ($3::text IS NULL OR order_id = $3)
+      AND ($4::text IS NULL OR note ILIKE '%' || $4 || '%' ESCAPE '\')
+    ORDER BY created_at DESC, id DESC
+    LIMIT $5
+"#;
+
+/// Entries on any of the bot symbols `$1` or orders `$2`, or with a range
+/// overlapping `[$3, $4]`. Unlimited, as every row shown needs its notes.
+const GET_JOURNAL_ENTRIES_FOR_SQL: &str = r#"
+    SELECT id, created_at, bot_symbol, order_id, range_start, range_end, note, tags
+    FROM journal_entry
+    WHERE upper(bot_symbol) = ANY($1)
+       OR order_id = ANY($2)
+       OR (range_start <= $4 AND range_end >= $3)
+    ORDER BY created_at DESC, id DESC
+"#;
+
+const INSERT_JOURNAL_ENTRY_SQL: &str = r#"
+    INSERT INTO journal_entry (bot_symbol, order_id, range_start, range_end, note, tags)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    RETURNING id
+"#;
+
+const DELETE_JOURNAL_ENTRY_SQL: &str = "DELETE FROM journal_entry WHERE id = $1";
+
+#[derive(Default)]
+pub struct JournalFilter {
+    pub tag: Option<String>,
+    /// Substring match on the bot symbol, case-insensitive.
+    pub symbol: Option<String>,
+    pub order_id: Option<String>,
+    /// Substring match on the note, case-insensitive.
+    pub text: Option<String>,
+    pub limit: i64,
+}
+
+/// What the rows of a page can carry notes on.
+#[derive(Default)]
+pub struct JournalKeys {
+    /// Bot symbols, upper case.
+    pub symbols: Vec<String>,
+    pub order_ids: Vec<String>,
+    /// Earliest and latest time of the rows.
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl JournalKeys {
+    /// Earliest and latest of `times`.
+    pub fn span(
+        times: impl Iterator<Item = DateTime<Utc>>,
+    ) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        times.fold(None, |span, at| match span {
+            Some((from, to)) => Some((at.min(from), at.max(to))),
+            None => Some((at, at)),
+        })
+    }
+}
+
+#[async_trait]
+pub trait JournalRepository: Send + Sync {
+    async fn get_entries(&self, filter: &JournalFilter) -> RepositoryResult<Vec<JournalEntry>>;
+    /// Every entry on one of `keys`.
+    async fn get_entries_for(&self, keys: &JournalKeys) -> RepositoryResult<Vec<JournalEntry>>;
+    async fn create_entry(&self, entry: &NewJournalEntry) -> RepositoryResult<i64>;
+    /// `false` when there was no such entry.
+    async fn delete_entry(&self, id: i64) -> RepositoryResult<bool>;
+}
+
+pub struct PostgresJournalRepository {
+    pool: DbPool,
+}
+
+impl PostgresJournalRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JournalRepository for PostgresJournalRepository {
+    async fn get_entries(&self, filter: &JournalFilter) -> RepositoryResult<Vec<JournalEntry>> {
+        let entries = sqlx::query_as::<_, JournalEntry>(GET_JOURNAL_ENTRIES_SQL)
+            .bind(&filter.tag)
+            .bind(filter.symbol.as_deref().map(escape_like))
+            .bind(&filter.order_id)
+            .bind(filter.text.as_deref().map(escape_like))
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(entries)
+    }
+
+    async fn get_entries_for(&self, keys: &JournalKeys) -> RepositoryResult<Vec<JournalEntry>> {
+        let entries = sqlx::query_as::<_, JournalEntry>(GET_JOURNAL_ENTRIES_FOR_SQL)
+            .bind(&keys.symbols)
+            .bind(&keys.order_ids)
+            .bind(keys.span.map(|(from, _)| from))
+            .bind(keys.span.map(|(_, to)| to))
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(entries)
+    }
+
+    async fn create_entry(&self, entry: &NewJournalEntry) -> RepositoryResult<i64> {
+        let id = sqlx::query_scalar(INSERT_JOURNAL_ENTRY_SQL)
+            .bind(&entry.bot_symbol)
+            .bind(&entry.order_id)
+            .bind(entry.range.map(|(start, _)| start))
+            .bind(entry.range.map(|(_, end)| end))
+            .bind(&entry.note)
+            .bind(&entry.tags)
+            .fetch_one(self.pool.get())
+            .await?;
+
+        Ok(id)
+    }
+
+    async fn delete_entry(&self, id: i64) -> RepositoryResult<bool> {
+        let result = sqlx::query(DELETE_JOURNAL_ENTRY_SQL)
+            .bind(id)
+            .execute(self.pool.get())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
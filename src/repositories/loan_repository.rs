@@ -0,0 +1,75 @@
+use crate::api::models::{LoanEvent, PositionDebt};
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use crate::repositories::margin_repository::GET_LATEST_POSITION_DEBTS_SQL;
+use async_trait::async_trait;
+
+/// Borrows are the `borrow_size` the exchange reported for an auto-borrow
+/// order: quote currency for a buy, base currency for a sell. Repays are
+/// the fills of auto-repay orders, in the currency the fill delivers; how
+/// much of it went to the loan is decided by the service.
+pub(crate) const GET_LOAN_EVENTS_SQL: &str = r#"
+    WITH orders AS (
+        SELECT DISTINCT ON (s.exchange, s.args_client_oid)
+               s.exchange, s.args_client_oid, s.args_symbol, s.args_side, s.args_auto_repay,
+               CASE WHEN s.args_side = 'buy' THEN sym.quote_currency ELSE sym.base_currency END
+                   AS borrow_currency,
+               CASE WHEN s.args_side = 'buy' THEN sym.base_currency ELSE sym.quote_currency END
+                   AS proceeds_currency
+        FROM msgsend s
+        JOIN symbol sym ON sym.exchange = s.exchange AND sym.symbol = s.args_symbol
+        WHERE s.args_client_oid IS NOT NULL
+        ORDER BY s.exchange, s.args_client_oid, s.updated_at DESC
+    )
+    SELECT e.exchange, 'borrow' AS kind, o.borrow_currency AS currency,
+           e.borrow_size AS size, NULL::text AS price,
+           o.args_symbol AS symbol, o.args_side AS side, o.args_client_oid AS client_oid,
+           e.loan_apply_id, e.updated_at
+    FROM msgevent e
+    JOIN orders o ON o.exchange = e.exchange AND o.args_client_oid = e.client_oid
+    WHERE e.borrow_size IS NOT NULL AND e.loan_apply_id IS NOT NULL
+    UNION ALL
+    SELECT m.exchange, 'repay', o.proceeds_currency,
+           m.match_size, CASE WHEN o.args_side = 'sell' THEN m.match_price END,
+           o.args_symbol, o.args_side, o.args_client_oid,
+           NULL, m.updated_at
+    FROM orderevent m
+    JOIN orders o ON o.exchange = m.exchange AND o.args_client_oid = m.client_oid
+    WHERE m.type_ = 'match' AND o.args_auto_repay
+    ORDER BY updated_at
+"#;
+
+#[async_trait]
+pub trait LoanRepository: Send + Sync {
+    async fn get_loan_events(&self) -> RepositoryResult<Vec<LoanEvent>>;
+    async fn get_latest_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>>;
+}
+
+pub struct PostgresLoanRepository {
+    pool: DbPool,
+}
+
+impl PostgresLoanRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LoanRepository for PostgresLoanRepository {
+    async fn get_loan_events(&self) -> RepositoryResult<Vec<LoanEvent>> {
+        let events = sqlx::query_as::<_, LoanEvent>(GET_LOAN_EVENTS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(events)
+    }
+
+    async fn get_latest_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>> {
+        let debts = sqlx::query_as::<_, PositionDebt>(GET_LATEST_POSITION_DEBTS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(debts)
+    }
+}
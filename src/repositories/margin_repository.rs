@@ -0,0 +1,89 @@
+use crate::api::models::{LastPrice, PositionAsset, PositionDebt, PositionRatio};
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+
+pub(crate) const GET_LATEST_POSITION_RATIOS_SQL: &str = r#"
+    SELECT DISTINCT ON (exchange)
+           exchange, debt_ratio, total_asset, margin_coefficient_total_asset,
+           total_debt, updated_at
+    FROM positionratio
+    ORDER BY exchange, updated_at DESC
+"#;
+
+pub(crate) const GET_LATEST_POSITION_DEBTS_SQL: &str = r#"
+    SELECT DISTINCT ON (exchange, debt_symbol)
+           exchange, debt_symbol, debt_value, updated_at
+    FROM positiondebt
+    ORDER BY exchange, debt_symbol, updated_at DESC
+"#;
+
+pub(crate) const GET_LATEST_POSITION_ASSETS_SQL: &str = r#"
+    SELECT DISTINCT ON (exchange, asset_symbol)
+           exchange, asset_symbol, asset_total, asset_available, asset_hold, updated_at
+    FROM positionasset
+    ORDER BY exchange, asset_symbol, updated_at DESC
+"#;
+
+/// The exchange tables carry no market prices, so the last price the bot
+/// was matched at stands in for one.
+pub(crate) const GET_LAST_PRICES_SQL: &str = r#"
+    SELECT DISTINCT ON (exchange, symbol)
+           exchange, symbol, match_price AS price, updated_at
+    FROM orderevent
+    WHERE type_ = 'match' AND match_price IS NOT NULL
+    ORDER BY exchange, symbol, updated_at DESC
+"#;
+
+#[async_trait]
+pub trait MarginRepository: Send + Sync {
+    async fn get_latest_position_ratios(&self) -> RepositoryResult<Vec<PositionRatio>>;
+    async fn get_latest_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>>;
+    async fn get_latest_position_assets(&self) -> RepositoryResult<Vec<PositionAsset>>;
+    async fn get_last_prices(&self) -> RepositoryResult<Vec<LastPrice>>;
+}
+
+pub struct PostgresMarginRepository {
+    pool: DbPool,
+}
+
+impl PostgresMarginRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MarginRepository for PostgresMarginRepository {
+    async fn get_latest_position_ratios(&self) -> RepositoryResult<Vec<PositionRatio>> {
+        let ratios = sqlx::query_as::<_, PositionRatio>(GET_LATEST_POSITION_RATIOS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(ratios)
+    }
+
+    async fn get_latest_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>> {
+        let debts = sqlx::query_as::<_, PositionDebt>(GET_LATEST_POSITION_DEBTS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(debts)
+    }
+
+    async fn get_latest_position_assets(&self) -> RepositoryResult<Vec<PositionAsset>> {
+        let assets = sqlx::query_as::<_, PositionAsset>(GET_LATEST_POSITION_ASSETS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(assets)
+    }
+
+    async fn get_last_prices(&self) -> RepositoryResult<Vec<LastPrice>> {
+        let prices = sqlx::query_as::<_, LastPrice>(GET_LAST_PRICES_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(prices)
+    }
+}
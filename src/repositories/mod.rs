@@ -1,31 +1,397 @@
 pub mod balance_repository;
 pub mod bot_repository;
+pub mod cached_repository;
 pub mod currency_repository;
 pub mod error_repository;
 pub mod event_repository;
+pub mod exchange_repository;
+pub mod fee_repository;
+pub mod journal_repository;
+pub mod loan_repository;
+pub mod margin_repository;
 pub mod msgevent_repository;
 pub mod msgsend_repository;
 pub mod order_repository;
 pub mod pg_repository;
 pub mod position_repository;
+pub mod report_repository;
+pub mod stream_repository;
+pub mod symbol_change_repository;
 pub mod symbol_repository;
 pub mod ticker_repository;
+pub mod view_repository;
 
 pub use balance_repository::{BalanceRepository, PostgresBalanceRepository};
 pub use bot_repository::{BotRepository, PostgresBotRepository};
+pub use cached_repository::{
+    CachedCurrencyRepository, CachedSymbolRepository, CachedTickerRepository,
+};
 pub use currency_repository::{CurrencyRepository, PostgresCurrencyRepository};
 pub use error_repository::{ErrorRepository, PostgresErrorRepository};
 pub use event_repository::{EventRepository, PostgresEventRepository};
+pub use exchange_repository::{ExchangeRepository, PostgresExchangeRepository};
+pub use fee_repository::{FeeRepository, PostgresFeeRepository};
+pub use journal_repository::{
+    JournalFilter, JournalKeys, JournalRepository, PostgresJournalRepository,
+};
+pub use loan_repository::{LoanRepository, PostgresLoanRepository};
+pub use margin_repository::{MarginRepository, PostgresMarginRepository};
 pub use msgevent_repository::{MsgEventRepository, PostgresMsgEventRepository};
 pub use msgsend_repository::{MsgSendRepository, PostgresMsgSendRepository};
 pub use order_repository::{EventOrderRepository, PostgresEventOrderRepository};
-pub use pg_repository::{
-    ConnectionStatsRepository, PostgresPgRepository, QueryStatsRepository, TableSizeRepository,
-    TableStatsRepository,
+pub use pg_repository::{PgRepository, PostgresPgRepository};
+pub use position_repository::{PositionRepository, PositionSeries, PostgresPositionRepository};
+pub use report_repository::{PostgresReportRepository, ReportRepository};
+pub use stream_repository::{PostgresStreamRepository, StreamRepository};
+pub use symbol_change_repository::{
+    PostgresSymbolChangeRepository, SymbolChangeFilter, SymbolChangeRepository,
 };
-pub use position_repository::{PositionRepository, PostgresPositionRepository};
 pub use symbol_repository::{PostgresSymbolRepository, SymbolRepository};
 pub use ticker_repository::{PostgresTickerRepository, TickerRepository};
+pub use view_repository::{PostgresViewRepository, ViewRepository};
 
 use anyhow::Result;
+use std::borrow::Cow;
+use std::fmt::Write;
 pub type RepositoryResult<T> = Result<T, anyhow::Error>;
+
+/// Keys relating rows of the bot's tables to each other. A row matches when
+/// any of its keys is listed; keys a table lacks are ignored. Services
+/// return nothing for a filter without keys rather than every row.
+#[derive(Debug, Default, Clone)]
+pub struct LinkFilter {
+    pub exchange: Option<String>,
+    pub order_ids: Vec<String>,
+    pub client_oids: Vec<String>,
+    pub symbols: Vec<String>,
+    pub currencies: Vec<String>,
+    pub limit: i64,
+}
+
+impl LinkFilter {
+    pub fn has_keys(&self) -> bool {
+        !(self.order_ids.is_empty()
+            && self.client_oids.is_empty()
+            && self.symbols.is_empty()
+            && self.currencies.is_empty())
+    }
+}
+
+/// `value` matched literally by `LIKE`, whose escape character is `\`.
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Filters, sort and row limit of a generic table page, run in the page's
+/// query so they cover the whole table rather than its newest rows. Column
+/// names come from the page's column list, never from the request.
+#[derive(Debug, Clone)]
+pub struct TableFilter {
+    /// Rows whose column, as text, contains the value, case-insensitive.
+    pub contains: Vec<(&'static str, String)>,
+    pub sort: Option<TableSort>,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TableSort {
+    pub column: &'static str,
+    /// Sorts the column's text by its numeric value, non-numbers last.
+    pub numeric: bool,
+    pub descending: bool,
+}
+
+/// Rows a table page shows at most.
+pub const TABLE_PAGE_LIMIT: i64 = 1000;
+
+/// Text of a plain or scientific decimal number.
+const NUMBER_PATTERN: &str = r"^\s*[-+]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][-+]?[0-9]+)?\s*$";
+
+impl TableFilter {
+    pub fn new(limit: i64) -> Self {
+        Self {
+            contains: Vec::new(),
+            sort: None,
+            limit,
+        }
+    }
+
+    /// `sql`, selecting a table page's rows with `$1..$params` bound,
+    /// wrapped in the filters, sort and limit. The filters' values are
+    /// bound after those, in [`TableFilter::patterns`] order. Rows are
+    /// otherwise newest first.
+    pub fn wrap(&self, sql: &str, params: usize) -> String {
+        let mut out = format!(
+            "SELECT * FROM ({}) AS page",
+            sql.trim().trim_end_matches(';')
+        );
+        for (i, (column, _)) in self.contains.iter().enumerate() {
+            let _ = write!(
+                out,
+                "\n{} coalesce(page.{}::text, '') ILIKE ${} ESCAPE '\\'",
+                if i == 0 { "WHERE" } else { "  AND" },
+                column,
+                params + i + 1
+            );
+        }
+        out.push_str("\nORDER BY ");
+        if let Some(sort) = self.sort {
+            let direction = if sort.descending { "DESC" } else { "ASC" };
+            if sort.numeric {
+                let _ = write!(
+                    out,
+                    "CASE WHEN page.{c}::text ~ '{}' THEN page.{c}::text::numeric END",
+                    NUMBER_PATTERN,
+                    c = sort.column
+                );
+            } else {
+                let _ = write!(out, "page.{}", sort.column);
+            }
+            let _ = write!(out, " {} NULLS LAST, ", direction);
+        }
+        let _ = write!(out, "page.updated_at DESC\nLIMIT {}", self.limit);
+        out
+    }
+
+    /// `ILIKE` patterns matching the filters' values literally.
+    pub fn patterns(&self) -> impl Iterator<Item = String> + '_ {
+        self.contains
+            .iter()
+            .map(|(_, text)| format!("%{}%", escape_like(text)))
+    }
+}
+
+/// A read query issued by one of the repositories, exposed so the EXPLAIN
+/// viewer can profile exactly the SQL the dashboards run.
+pub struct RegisteredQuery {
+    pub name: &'static str,
+    pub sql: &'static str,
+    /// `BIGINT` values bound to `$1..$n`, mirroring what the service passes.
+    /// Placeholders after these are optional text filters such as the
+    /// exchange, bound as `NULL` to profile the unfiltered page.
+    pub params: &'static [i64],
+    /// A table page's rows, run through [`TableFilter::wrap`].
+    pub table: bool,
+}
+
+impl RegisteredQuery {
+    /// The SQL as the unfiltered page or service runs it.
+    pub fn statement(&self) -> Cow<'static, str> {
+        if self.table {
+            Cow::Owned(TableFilter::new(TABLE_PAGE_LIMIT).wrap(self.sql, self.placeholders()))
+        } else {
+            Cow::Borrowed(self.sql)
+        }
+    }
+
+    /// Highest `$n` placeholder in the SQL.
+    pub fn placeholders(&self) -> usize {
+        self.sql
+            .split('$')
+            .skip(1)
+            .filter_map(|rest| {
+                let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().ok()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+pub const REGISTERED_QUERIES: &[RegisteredQuery] = &[
+    RegisteredQuery {
+        name: "balance.get_balances",
+        sql: balance_repository::GET_BALANCES_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "bots.get_bots",
+        sql: bot_repository::GET_BOTS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "bots.get_bot_orders",
+        sql: bot_repository::GET_BOT_ORDERS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "currency.get_currencies",
+        sql: currency_repository::GET_CURRENCIES_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "errors.get_errors",
+        sql: error_repository::GET_ERRORS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "events.get_events",
+        sql: event_repository::GET_EVENTS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "exchanges.get_exchange_activity",
+        sql: exchange_repository::GET_EXCHANGE_ACTIVITY_SQL,
+        params: &[86400],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "fees.get_fee_schedules",
+        sql: fee_repository::GET_FEE_SCHEDULES_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "fees.get_charged_fees",
+        sql: fee_repository::GET_CHARGED_FEES_SQL,
+        params: &[1000],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "loans.get_loan_events",
+        sql: loan_repository::GET_LOAN_EVENTS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "margin.get_latest_position_ratios",
+        sql: margin_repository::GET_LATEST_POSITION_RATIOS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "margin.get_latest_position_debts",
+        sql: margin_repository::GET_LATEST_POSITION_DEBTS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "margin.get_latest_position_assets",
+        sql: margin_repository::GET_LATEST_POSITION_ASSETS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "margin.get_last_prices",
+        sql: margin_repository::GET_LAST_PRICES_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "msgevent.get_msgevents",
+        sql: msgevent_repository::GET_MSGEVENTS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "msgsend.get_msgsends",
+        sql: msgsend_repository::GET_MSGSENDS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "msgsend.get_msgsend_outcomes",
+        sql: msgsend_repository::GET_MSGSEND_OUTCOMES_SQL,
+        params: &[1000],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "orderevent.get_event_orders",
+        sql: order_repository::GET_EVENT_ORDERS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "positionasset.get_position_assets",
+        sql: position_repository::GET_POSITION_ASSETS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "positiondebt.get_position_debts",
+        sql: position_repository::GET_POSITION_DEBTS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "positionratio.get_position_ratios",
+        sql: position_repository::GET_POSITION_RATIOS_SQL,
+        params: &[],
+        table: true,
+    },
+    RegisteredQuery {
+        name: "positionratio.get_position_ratio_history",
+        sql: position_repository::GET_POSITION_RATIO_HISTORY_SQL,
+        params: &[0, 4102444800, 3600],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "positiondebt.get_position_debt_history",
+        sql: position_repository::GET_POSITION_DEBT_HISTORY_SQL,
+        params: &[0, 4102444800, 3600],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "positionasset.get_position_asset_history",
+        sql: position_repository::GET_POSITION_ASSET_HISTORY_SQL,
+        params: &[0, 4102444800, 3600],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "symbol.get_all_symbols",
+        sql: symbol_repository::GET_ALL_SYMBOLS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "symbol.get_quote_volumes",
+        sql: symbol_repository::GET_QUOTE_VOLUMES_SQL,
+        params: &[24],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "ticker.get_tickers",
+        sql: ticker_repository::GET_TICKERS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "pg.get_connections",
+        sql: pg_repository::GET_CONNECTIONS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "pg.get_table_info",
+        sql: pg_repository::GET_TABLE_INFO_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "pg.get_table_indexes",
+        sql: pg_repository::GET_TABLE_INDEXES_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "pg.get_stat_statements",
+        sql: pg_repository::GET_STAT_STATEMENTS_SQL,
+        params: &[],
+        table: false,
+    },
+    RegisteredQuery {
+        name: "pg.get_table_sizes",
+        sql: pg_repository::GET_TABLE_SIZES_SQL,
+        params: &[],
+        table: false,
+    },
+];
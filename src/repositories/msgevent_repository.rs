@@ -1,37 +1,97 @@
 use crate::api::models::MsgEvent;
-use crate::repositories::RepositoryResult;
+use crate::core::db::DbPool;
+use crate::repositories::{LinkFilter, RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_MSGEVENTS_SQL: &str = r#"
+    SELECT exchange, msg, code, borrow_size, client_oid, order_id,
+           loan_apply_id, limit_rate, reset_rate, remaining_rate,
+           in_time, out_time, updated_at
+    FROM msgevent
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+/// Exchange responses by client oid, order id or loan apply id.
+pub(crate) const SEARCH_MSGEVENTS_SQL: &str = r#"
+    SELECT exchange, msg, code, borrow_size, client_oid, order_id,
+           loan_apply_id, limit_rate, reset_rate, remaining_rate,
+           in_time, out_time, updated_at
+    FROM msgevent
+    WHERE client_oid = $1 OR order_id = $1 OR loan_apply_id = $1
+    ORDER BY updated_at DESC
+    LIMIT $2
+"#;
+
+/// Exchange responses linked by order id or client oid.
+pub(crate) const GET_LINKED_MSGEVENTS_SQL: &str = r#"
+    SELECT exchange, msg, code, borrow_size, client_oid, order_id,
+           loan_apply_id, limit_rate, reset_rate, remaining_rate,
+           in_time, out_time, updated_at
+    FROM msgevent
+    WHERE ($1::text IS NULL OR exchange = $1)
+      AND (order_id = ANY($2) OR client_oid = ANY($3))
+    ORDER BY updated_at DESC
+    LIMIT $4
+"#;
 
 #[async_trait]
 pub trait MsgEventRepository: Send + Sync {
-    async fn get_msgevents(&self) -> RepositoryResult<Vec<MsgEvent>>;
+    async fn get_msgevents(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<MsgEvent>>;
+    async fn search_msgevents(&self, term: &str, limit: i64) -> RepositoryResult<Vec<MsgEvent>>;
+    async fn get_linked_msgevents(&self, filter: &LinkFilter) -> RepositoryResult<Vec<MsgEvent>>;
 }
 
 pub struct PostgresMsgEventRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresMsgEventRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl MsgEventRepository for PostgresMsgEventRepository {
-    async fn get_msgevents(&self) -> RepositoryResult<Vec<MsgEvent>> {
-        let msgevents = sqlx::query_as::<_, MsgEvent>(
-            r#"
-            SELECT exchange, msg, code, borrow_size, client_oid, order_id, 
-                   loan_apply_id, limit_rate, reset_rate, remaining_rate, 
-                   in_time, out_time, updated_at
-            FROM msgevent
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_msgevents(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<MsgEvent>> {
+        let mut query =
+            sqlx::query_as::<_, MsgEvent>(AssertSqlSafe(filter.wrap(GET_MSGEVENTS_SQL, 1)))
+                .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let msgevents = query.fetch_all(self.pool.get()).await?;
+
+        Ok(msgevents)
+    }
+
+    async fn search_msgevents(&self, term: &str, limit: i64) -> RepositoryResult<Vec<MsgEvent>> {
+        let msgevents = sqlx::query_as::<_, MsgEvent>(SEARCH_MSGEVENTS_SQL)
+            .bind(term)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(msgevents)
+    }
+
+    async fn get_linked_msgevents(&self, filter: &LinkFilter) -> RepositoryResult<Vec<MsgEvent>> {
+        let msgevents = sqlx::query_as::<_, MsgEvent>(GET_LINKED_MSGEVENTS_SQL)
+            .bind(&filter.exchange)
+            .bind(&filter.order_ids)
+            .bind(&filter.client_oids)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(msgevents)
     }
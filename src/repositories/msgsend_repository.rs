@@ -1,37 +1,135 @@
-use crate::api::models::MsgSend;
-use crate::repositories::RepositoryResult;
+use crate::api::models::{MsgSend, MsgSendOutcome};
+use crate::core::db::DbPool;
+use crate::repositories::{LinkFilter, RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_MSGSENDS_SQL: &str = r#"
+    SELECT exchange, args_symbol, args_side, args_size, args_funds,
+           args_price, args_time_in_force, args_type, args_auto_borrow,
+           args_auto_repay, args_client_oid, args_order_id, updated_at
+    FROM msgsend
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+/// Sent orders with the latest exchange response for their client oid.
+pub(crate) const GET_MSGSEND_OUTCOMES_SQL: &str = r#"
+    SELECT s.exchange, s.args_symbol, s.args_side, s.args_size, s.args_funds,
+           s.args_price, s.args_type, s.args_client_oid, s.updated_at,
+           e.code AS response_code, e.msg AS response_msg,
+           m.match_price AS reference_price
+    FROM msgsend s
+    LEFT JOIN LATERAL (
+        SELECT code, msg
+        FROM msgevent e
+        WHERE e.exchange = s.exchange AND e.client_oid = s.args_client_oid
+        ORDER BY e.updated_at DESC
+        LIMIT 1
+    ) e ON true
+    LEFT JOIN LATERAL (
+        SELECT match_price
+        FROM orderevent m
+        WHERE m.exchange = s.exchange AND m.symbol = s.args_symbol
+          AND m.type_ = 'match' AND m.match_price IS NOT NULL
+          AND m.updated_at <= s.updated_at
+        ORDER BY m.updated_at DESC
+        LIMIT 1
+    ) m ON true
+    ORDER BY s.updated_at DESC
+    LIMIT $1
+"#;
+
+/// Sent orders by client oid, order id or symbol.
+pub(crate) const SEARCH_MSGSENDS_SQL: &str = r#"
+    SELECT exchange, args_symbol, args_side, args_size, args_funds,
+           args_price, args_time_in_force, args_type, args_auto_borrow,
+           args_auto_repay, args_client_oid, args_order_id, updated_at
+    FROM msgsend
+    WHERE args_client_oid = $1 OR args_order_id = $1 OR args_symbol = upper($1)
+    ORDER BY updated_at DESC
+    LIMIT $2
+"#;
+
+/// Sent orders linked by order id, client oid or symbol.
+pub(crate) const GET_LINKED_MSGSENDS_SQL: &str = r#"
+    SELECT exchange, args_symbol, args_side, args_size, args_funds,
+           args_price, args_time_in_force, args_type, args_auto_borrow,
+           args_auto_repay, args_client_oid, args_order_id, updated_at
+    FROM msgsend
+    WHERE ($1::text IS NULL OR exchange = $1)
+      AND (args_order_id = ANY($2) OR args_client_oid = ANY($3) OR args_symbol = ANY($4))
+    ORDER BY updated_at DESC
+    LIMIT $5
+"#;
 
 #[async_trait]
 pub trait MsgSendRepository: Send + Sync {
-    async fn get_msgsends(&self) -> RepositoryResult<Vec<MsgSend>>;
+    async fn get_msgsends(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<MsgSend>>;
+    async fn search_msgsends(&self, term: &str, limit: i64) -> RepositoryResult<Vec<MsgSend>>;
+    async fn get_msgsend_outcomes(&self, limit: i64) -> RepositoryResult<Vec<MsgSendOutcome>>;
+    async fn get_linked_msgsends(&self, filter: &LinkFilter) -> RepositoryResult<Vec<MsgSend>>;
 }
 
 pub struct PostgresMsgSendRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresMsgSendRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl MsgSendRepository for PostgresMsgSendRepository {
-    async fn get_msgsends(&self) -> RepositoryResult<Vec<MsgSend>> {
-        let msgsends = sqlx::query_as::<_, MsgSend>(
-            r#"
-            SELECT exchange, args_symbol, args_side, args_size, args_funds, 
-                   args_price, args_time_in_force, args_type, args_auto_borrow, 
-                   args_auto_repay, args_client_oid, args_order_id, updated_at
-            FROM msgsend
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_msgsends(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<MsgSend>> {
+        let mut query =
+            sqlx::query_as::<_, MsgSend>(AssertSqlSafe(filter.wrap(GET_MSGSENDS_SQL, 1)))
+                .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let msgsends = query.fetch_all(self.pool.get()).await?;
+
+        Ok(msgsends)
+    }
+
+    async fn get_msgsend_outcomes(&self, limit: i64) -> RepositoryResult<Vec<MsgSendOutcome>> {
+        let outcomes = sqlx::query_as::<_, MsgSendOutcome>(GET_MSGSEND_OUTCOMES_SQL)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(outcomes)
+    }
+
+    async fn search_msgsends(&self, term: &str, limit: i64) -> RepositoryResult<Vec<MsgSend>> {
+        let msgsends = sqlx::query_as::<_, MsgSend>(SEARCH_MSGSENDS_SQL)
+            .bind(term)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(msgsends)
+    }
+
+    async fn get_linked_msgsends(&self, filter: &LinkFilter) -> RepositoryResult<Vec<MsgSend>> {
+        let msgsends = sqlx::query_as::<_, MsgSend>(GET_LINKED_MSGSENDS_SQL)
+            .bind(&filter.exchange)
+            .bind(&filter.order_ids)
+            .bind(&filter.client_oids)
+            .bind(&filter.symbols)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(msgsends)
     }
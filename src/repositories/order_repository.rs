@@ -1,38 +1,115 @@
 use crate::api::models::EventOrder;
-use crate::repositories::RepositoryResult;
+use crate::core::db::DbPool;
+use crate::repositories::{LinkFilter, RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::AssertSqlSafe;
+
+pub(crate) const GET_EVENT_ORDERS_SQL: &str = r#"
+    SELECT exchange, status, type_, symbol, side, order_type, fee_type,
+           liquidity, price, order_id, client_oid, trade_id, origin_size,
+           size, filled_size, match_size, match_price, canceled_size,
+           old_size, remain_size, remain_funds, order_time, ts, updated_at
+    FROM orderevent
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+/// Events of an order or trade id, a client oid, or a symbol.
+pub(crate) const SEARCH_EVENT_ORDERS_SQL: &str = r#"
+    SELECT exchange, status, type_, symbol, side, order_type, fee_type,
+           liquidity, price, order_id, client_oid, trade_id, origin_size,
+           size, filled_size, match_size, match_price, canceled_size,
+           old_size, remain_size, remain_funds, order_time, ts, updated_at
+    FROM orderevent
+    WHERE order_id = $1 OR client_oid = $1 OR trade_id = $1 OR symbol = upper($1)
+    ORDER BY updated_at DESC
+    LIMIT $2
+"#;
+
+/// Events of orders linked by order id, client oid or symbol.
+pub(crate) const GET_LINKED_EVENT_ORDERS_SQL: &str = r#"
+    SELECT exchange, status, type_, symbol, side, order_type, fee_type,
+           liquidity, price, order_id, client_oid, trade_id, origin_size,
+           size, filled_size, match_size, match_price, canceled_size,
+           old_size, remain_size, remain_funds, order_time, ts, updated_at
+    FROM orderevent
+    WHERE ($1::text IS NULL OR exchange = $1)
+      AND (order_id = ANY($2) OR client_oid = ANY($3) OR symbol = ANY($4))
+    ORDER BY updated_at DESC
+    LIMIT $5
+"#;
 
 #[async_trait]
 pub trait EventOrderRepository: Send + Sync {
-    async fn get_event_orders(&self) -> RepositoryResult<Vec<EventOrder>>;
+    async fn get_event_orders(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<EventOrder>>;
+    async fn search_event_orders(
+        &self,
+        term: &str,
+        limit: i64,
+    ) -> RepositoryResult<Vec<EventOrder>>;
+    async fn get_linked_event_orders(
+        &self,
+        filter: &LinkFilter,
+    ) -> RepositoryResult<Vec<EventOrder>>;
 }
 
 pub struct PostgresEventOrderRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresEventOrderRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl EventOrderRepository for PostgresEventOrderRepository {
-    async fn get_event_orders(&self) -> RepositoryResult<Vec<EventOrder>> {
-        let event_orders = sqlx::query_as::<_, EventOrder>(
-            r#"
-            SELECT exchange, status, type_, symbol, side, order_type, fee_type, 
-                   liquidity, price, order_id, client_oid, trade_id, origin_size, 
-                   size, filled_size, match_size, match_price, canceled_size, 
-                   old_size, remain_size, remain_funds, order_time, ts, updated_at
-            FROM orderevent
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_event_orders(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<EventOrder>> {
+        let mut query =
+            sqlx::query_as::<_, EventOrder>(AssertSqlSafe(filter.wrap(GET_EVENT_ORDERS_SQL, 1)))
+                .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let event_orders = query.fetch_all(self.pool.get()).await?;
+
+        Ok(event_orders)
+    }
+
+    async fn search_event_orders(
+        &self,
+        term: &str,
+        limit: i64,
+    ) -> RepositoryResult<Vec<EventOrder>> {
+        let event_orders = sqlx::query_as::<_, EventOrder>(SEARCH_EVENT_ORDERS_SQL)
+            .bind(term)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(event_orders)
+    }
+
+    async fn get_linked_event_orders(
+        &self,
+        filter: &LinkFilter,
+    ) -> RepositoryResult<Vec<EventOrder>> {
+        let event_orders = sqlx::query_as::<_, EventOrder>(GET_LINKED_EVENT_ORDERS_SQL)
+            .bind(&filter.exchange)
+            .bind(&filter.order_ids)
+            .bind(&filter.client_oids)
+            .bind(&filter.symbols)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(event_orders)
     }
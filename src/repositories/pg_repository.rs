@@ -1,9 +1,63 @@
 use crate::api::models::{
-    PgConnection, PgStatStatements, PgStatTableSize, PgTableIndex, PgTableInfo,
+    PgArchiverStatus, PgConnection, PgExplain, PgReplicationSlot, PgReplicationStat,
+    PgStatSnapshot, PgStatSnapshotStatement, PgStatSnapshotTable, PgStatStatements,
+    PgStatTableSize, PgTableIndex, PgTableInfo, PgWalStatus,
 };
-use crate::core::error::AppResult;
+use crate::core::db::DbPool;
+use crate::core::error::{AppError, AppResult};
+use crate::repositories::RegisteredQuery;
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::types::Json;
+use sqlx::{AssertSqlSafe, PgPool};
+
+pub(crate) const GET_CONNECTIONS_SQL: &str = r#"
+    SELECT count(*) AS total_connections,
+           count(*) FILTER (WHERE state = 'active') AS active_connections
+    FROM pg_stat_activity;
+"#;
+
+pub(crate) const GET_TABLE_INFO_SQL: &str = r#"
+    SELECT schemaname, relname, seq_scan, seq_tup_read, idx_scan, idx_tup_fetch,
+           n_tup_ins, n_tup_upd, n_tup_del, n_live_tup, n_dead_tup
+    FROM pg_stat_user_tables;
+"#;
+
+pub(crate) const GET_TABLE_INDEXES_SQL: &str = r#"
+    SELECT schemaname, relname, idx_scan, idx_tup_read, idx_tup_fetch
+    FROM pg_stat_user_indexes;
+"#;
+
+pub(crate) const GET_STAT_STATEMENTS_SQL: &str = r#"
+    SELECT query, calls, total_exec_time, mean_exec_time, rows
+    FROM pg_stat_statements
+    ORDER BY total_exec_time DESC LIMIT 100;
+"#;
+
+pub(crate) const GET_TABLE_SIZES_SQL: &str = r#"
+    SELECT schemaname, relname,
+           pg_size_pretty(pg_total_relation_size(schemaname || '.' || relname)) AS total_size,
+           pg_size_pretty(pg_relation_size(schemaname || '.' || relname)) AS table_size,
+           pg_size_pretty(pg_indexes_size(schemaname || '.' || relname)) AS indexes_size,
+           pg_total_relation_size(schemaname || '.' || relname) AS total_bytes
+    FROM pg_stat_user_tables;
+"#;
+
+/// Current WAL position that works on both a primary and a standby, where
+/// `pg_current_wal_lsn()` raises "recovery is in progress".
+const CURRENT_LSN_SQL: &str =
+    "CASE WHEN pg_is_in_recovery() THEN pg_last_wal_receive_lsn() ELSE pg_current_wal_lsn() END";
+
+fn wal_status_sql() -> String {
+    format!(
+        r#"
+        SELECT $1::text AS server, pg_is_in_recovery() AS in_recovery,
+               ({CURRENT_LSN_SQL})::text AS current_lsn,
+               CASE WHEN pg_is_in_recovery()
+                    THEN EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp())::float8
+               END AS replay_delay_secs;
+        "#
+    )
+}
 
 #[async_trait]
 pub trait ConnectionStatsRepository: Send + Sync {
@@ -25,34 +79,87 @@ pub trait TableSizeRepository: Send + Sync {
     async fn get_table_sizes(&self) -> AppResult<Vec<PgStatTableSize>>;
 }
 
+#[async_trait]
+pub trait StatSnapshotRepository: Send + Sync {
+    async fn take_snapshot(&self) -> AppResult<PgStatSnapshot>;
+    /// The latest snapshots, newest first.
+    async fn get_snapshots(&self, limit: i64) -> AppResult<Vec<PgStatSnapshot>>;
+    async fn get_snapshots_by_id(&self, ids: &[i64]) -> AppResult<Vec<PgStatSnapshot>>;
+    /// The newest snapshot taken before `before`.
+    async fn get_snapshot_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Option<PgStatSnapshot>>;
+    async fn get_snapshot_statements(
+        &self,
+        snapshot_id: i64,
+    ) -> AppResult<Vec<PgStatSnapshotStatement>>;
+    async fn get_snapshot_tables(&self, snapshot_id: i64) -> AppResult<Vec<PgStatSnapshotTable>>;
+    async fn delete_snapshots_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<u64>;
+}
+
+#[async_trait]
+pub trait ExplainRepository: Send + Sync {
+    async fn explain_analyze(&self, query: &RegisteredQuery) -> AppResult<PgExplain>;
+}
+
+#[async_trait]
+pub trait ReplicationRepository: Send + Sync {
+    async fn get_replication_stats(&self) -> AppResult<Vec<PgReplicationStat>>;
+    async fn get_replication_slots(&self) -> AppResult<Vec<PgReplicationSlot>>;
+    async fn get_wal_status(&self) -> AppResult<PgWalStatus>;
+    /// `None` when no replica is configured.
+    async fn get_replica_wal_status(&self) -> AppResult<Option<PgWalStatus>>;
+    async fn get_archiver_status(&self) -> AppResult<PgArchiverStatus>;
+    /// Bytes of WAL per second between the two latest snapshots.
+    async fn get_wal_rate(&self) -> AppResult<Option<f64>>;
+}
+
 #[async_trait]
 pub trait PgRepository:
-    ConnectionStatsRepository + TableStatsRepository + QueryStatsRepository + TableSizeRepository
+    ConnectionStatsRepository
+    + TableStatsRepository
+    + QueryStatsRepository
+    + TableSizeRepository
+    + StatSnapshotRepository
+    + ExplainRepository
+    + ReplicationRepository
 {
 }
 
+/// Server statistics and snapshots use the assigned pool, which must point at
+/// the primary server; snapshots are written through the primary pool itself.
+/// EXPLAIN runs where the dashboard queries run, i.e. on the replica when one
+/// is set.
 pub struct PostgresPgRepository {
-    pool: PgPool,
+    pool: DbPool,
+    primary: DbPool,
+    replica: Option<DbPool>,
 }
 
 impl PostgresPgRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: DbPool, primary: DbPool, replica: Option<DbPool>) -> Self {
+        Self {
+            pool,
+            primary,
+            replica,
+        }
+    }
+
+    fn read_pool(&self) -> &PgPool {
+        self.replica.as_ref().unwrap_or(&self.pool).get()
     }
 }
 
 #[async_trait]
 impl ConnectionStatsRepository for PostgresPgRepository {
     async fn get_connections(&self) -> AppResult<Vec<PgConnection>> {
-        let connections = sqlx::query_as::<_, PgConnection>(
-            r#"
-            SELECT count(*) AS total_connections, 
-                   count(*) FILTER (WHERE state = 'active') AS active_connections
-            FROM pg_stat_activity;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let connections = sqlx::query_as::<_, PgConnection>(GET_CONNECTIONS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
         Ok(connections)
     }
 }
@@ -60,65 +167,314 @@ impl ConnectionStatsRepository for PostgresPgRepository {
 #[async_trait]
 impl TableStatsRepository for PostgresPgRepository {
     async fn get_table_info(&self) -> AppResult<Vec<PgTableInfo>> {
-        let info = sqlx::query_as::<_, PgTableInfo>(
+        let info = sqlx::query_as::<_, PgTableInfo>(GET_TABLE_INFO_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(info)
+    }
+    async fn get_table_indexes(&self) -> AppResult<Vec<PgTableIndex>> {
+        let indexes = sqlx::query_as::<_, PgTableIndex>(GET_TABLE_INDEXES_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(indexes)
+    }
+}
+
+#[async_trait]
+impl QueryStatsRepository for PostgresPgRepository {
+    async fn get_stat_statements(&self) -> AppResult<Vec<PgStatStatements>> {
+        let statements = sqlx::query_as::<_, PgStatStatements>(GET_STAT_STATEMENTS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(statements)
+    }
+}
+
+#[async_trait]
+impl TableSizeRepository for PostgresPgRepository {
+    async fn get_table_sizes(&self) -> AppResult<Vec<PgStatTableSize>> {
+        let sizes = sqlx::query_as::<_, PgStatTableSize>(GET_TABLE_SIZES_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(sizes)
+    }
+}
+
+#[async_trait]
+impl StatSnapshotRepository for PostgresPgRepository {
+    async fn take_snapshot(&self) -> AppResult<PgStatSnapshot> {
+        let mut tx = self.primary.get().begin().await?;
+
+        let snapshot = sqlx::query_as::<_, PgStatSnapshot>(AssertSqlSafe(format!(
             r#"
-            SELECT schemaname, relname, seq_scan, seq_tup_read, idx_scan, idx_tup_fetch, 
-                   n_tup_ins, n_tup_upd, n_tup_del, n_live_tup, n_dead_tup
+            INSERT INTO pg_stat_snapshot (wal_position)
+            VALUES (pg_wal_lsn_diff({CURRENT_LSN_SQL}, '0/0')::bigint)
+            RETURNING id, taken_at;
+            "#
+        )))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // pg_stat_statements keys rows by (userid, dbid, toplevel, queryid);
+        // collapse them to one row per query of the current database.
+        sqlx::query(
+            r#"
+            INSERT INTO pg_stat_snapshot_statement
+                (snapshot_id, queryid, query, calls, total_exec_time, rows)
+            SELECT $1, queryid, min(query), sum(calls), sum(total_exec_time), sum(rows)
+            FROM pg_stat_statements
+            WHERE queryid IS NOT NULL
+              AND dbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+            GROUP BY queryid;
+            "#,
+        )
+        .bind(snapshot.id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO pg_stat_snapshot_table
+                (snapshot_id, schemaname, relname, seq_scan, idx_scan, n_tup_ins, n_tup_upd,
+                 n_tup_del, n_live_tup, n_dead_tup, total_bytes)
+            SELECT $1, schemaname, relname, COALESCE(seq_scan, 0), COALESCE(idx_scan, 0),
+                   n_tup_ins, n_tup_upd, n_tup_del, n_live_tup, n_dead_tup,
+                   pg_total_relation_size(relid)
             FROM pg_stat_user_tables;
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(snapshot.id)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(info)
+        tx.commit().await?;
+
+        Ok(snapshot)
     }
-    async fn get_table_indexes(&self) -> AppResult<Vec<PgTableIndex>> {
-        let indexes = sqlx::query_as::<_, PgTableIndex>(
+
+    async fn get_snapshots(&self, limit: i64) -> AppResult<Vec<PgStatSnapshot>> {
+        let snapshots = sqlx::query_as::<_, PgStatSnapshot>(
             r#"
-            SELECT schemaname, relname, idx_scan, idx_tup_read, idx_tup_fetch
-            FROM pg_stat_user_indexes;
+            SELECT id, taken_at
+            FROM pg_stat_snapshot
+            ORDER BY taken_at DESC LIMIT $1;
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(limit)
+        .fetch_all(self.pool.get())
         .await?;
 
-        Ok(indexes)
+        Ok(snapshots)
     }
-}
 
-#[async_trait]
-impl QueryStatsRepository for PostgresPgRepository {
-    async fn get_stat_statements(&self) -> AppResult<Vec<PgStatStatements>> {
-        let statements = sqlx::query_as::<_, PgStatStatements>(
+    async fn get_snapshots_by_id(&self, ids: &[i64]) -> AppResult<Vec<PgStatSnapshot>> {
+        let snapshots = sqlx::query_as::<_, PgStatSnapshot>(
             r#"
-            SELECT query, calls, total_exec_time, mean_exec_time, rows
-            FROM pg_stat_statements
-            ORDER BY total_exec_time DESC LIMIT 100;
+            SELECT id, taken_at
+            FROM pg_stat_snapshot
+            WHERE id = ANY($1);
             "#,
         )
-        .fetch_all(&self.pool)
+        .bind(ids)
+        .fetch_all(self.pool.get())
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    async fn get_snapshot_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Option<PgStatSnapshot>> {
+        let snapshot = sqlx::query_as::<_, PgStatSnapshot>(
+            r#"
+            SELECT id, taken_at
+            FROM pg_stat_snapshot
+            WHERE taken_at < $1
+            ORDER BY taken_at DESC LIMIT 1;
+            "#,
+        )
+        .bind(before)
+        .fetch_optional(self.pool.get())
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    async fn get_snapshot_statements(
+        &self,
+        snapshot_id: i64,
+    ) -> AppResult<Vec<PgStatSnapshotStatement>> {
+        let statements = sqlx::query_as::<_, PgStatSnapshotStatement>(
+            r#"
+            SELECT queryid, query, calls, total_exec_time, rows
+            FROM pg_stat_snapshot_statement
+            WHERE snapshot_id = $1;
+            "#,
+        )
+        .bind(snapshot_id)
+        .fetch_all(self.pool.get())
         .await?;
 
         Ok(statements)
     }
+
+    async fn get_snapshot_tables(&self, snapshot_id: i64) -> AppResult<Vec<PgStatSnapshotTable>> {
+        let tables = sqlx::query_as::<_, PgStatSnapshotTable>(
+            r#"
+            SELECT schemaname, relname, seq_scan, idx_scan, n_tup_ins, n_tup_upd, n_tup_del,
+                   n_live_tup, n_dead_tup, total_bytes
+            FROM pg_stat_snapshot_table
+            WHERE snapshot_id = $1;
+            "#,
+        )
+        .bind(snapshot_id)
+        .fetch_all(self.pool.get())
+        .await?;
+
+        Ok(tables)
+    }
+
+    async fn delete_snapshots_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM pg_stat_snapshot WHERE taken_at < $1")
+            .bind(before)
+            .execute(self.primary.get())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
 }
 
 #[async_trait]
-impl TableSizeRepository for PostgresPgRepository {
-    async fn get_table_sizes(&self) -> AppResult<Vec<PgStatTableSize>> {
-        let sizes = sqlx::query_as::<_, PgStatTableSize>(
+impl ExplainRepository for PostgresPgRepository {
+    async fn explain_analyze(&self, query: &RegisteredQuery) -> AppResult<PgExplain> {
+        // ANALYZE executes the statement, so run it in a transaction that is
+        // always rolled back and capped by a local statement timeout.
+        let mut tx = self.read_pool().begin().await?;
+
+        sqlx::query("SET LOCAL statement_timeout = '30s'")
+            .execute(&mut *tx)
+            .await?;
+
+        let sql = format!(
+            "EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {}",
+            query.statement().trim().trim_end_matches(';')
+        );
+        let mut explain = sqlx::query_scalar::<_, Json<Vec<PgExplain>>>(AssertSqlSafe(sql));
+        for param in query.params {
+            explain = explain.bind(*param);
+        }
+        for _ in query.params.len()..query.placeholders() {
+            explain = explain.bind(None::<String>);
+        }
+        let Json(plans) = explain.fetch_one(&mut *tx).await?;
+
+        tx.rollback().await?;
+
+        plans
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal(format!("Empty plan for {}", query.name)))
+    }
+}
+
+#[async_trait]
+impl ReplicationRepository for PostgresPgRepository {
+    async fn get_replication_stats(&self) -> AppResult<Vec<PgReplicationStat>> {
+        let stats = sqlx::query_as::<_, PgReplicationStat>(AssertSqlSafe(format!(
             r#"
-            SELECT schemaname, relname, 
-                   pg_size_pretty(pg_total_relation_size(schemaname || '.' || relname)) AS total_size, 
-                   pg_size_pretty(pg_relation_size(schemaname || '.' || relname)) AS table_size, 
-                   pg_size_pretty(pg_indexes_size(schemaname || '.' || relname)) AS indexes_size
-            FROM pg_stat_user_tables;
+            SELECT application_name, client_addr::text AS client_addr, state, sync_state,
+                   EXTRACT(EPOCH FROM write_lag)::float8 AS write_lag_secs,
+                   EXTRACT(EPOCH FROM flush_lag)::float8 AS flush_lag_secs,
+                   EXTRACT(EPOCH FROM replay_lag)::float8 AS replay_lag_secs,
+                   pg_wal_lsn_diff({CURRENT_LSN_SQL}, replay_lsn)::bigint AS replay_lag_bytes
+            FROM pg_stat_replication
+            ORDER BY application_name;
             "#
+        )))
+        .fetch_all(self.pool.get())
+        .await?;
+
+        Ok(stats)
+    }
+
+    async fn get_replication_slots(&self) -> AppResult<Vec<PgReplicationSlot>> {
+        let slots = sqlx::query_as::<_, PgReplicationSlot>(AssertSqlSafe(format!(
+            r#"
+            SELECT slot_name::text AS slot_name, slot_type, active, wal_status,
+                   pg_wal_lsn_diff({CURRENT_LSN_SQL}, restart_lsn)::bigint AS retained_bytes
+            FROM pg_replication_slots
+            ORDER BY slot_name;
+            "#
+        )))
+        .fetch_all(self.pool.get())
+        .await?;
+
+        Ok(slots)
+    }
+
+    async fn get_wal_status(&self) -> AppResult<PgWalStatus> {
+        let status = sqlx::query_as::<_, PgWalStatus>(AssertSqlSafe(wal_status_sql()))
+            .bind("primary")
+            .fetch_one(self.pool.get())
+            .await?;
+
+        Ok(status)
+    }
+
+    async fn get_replica_wal_status(&self) -> AppResult<Option<PgWalStatus>> {
+        let Some(replica) = &self.replica else {
+            return Ok(None);
+        };
+
+        let status = sqlx::query_as::<_, PgWalStatus>(AssertSqlSafe(wal_status_sql()))
+            .bind("replica")
+            .fetch_one(replica.direct())
+            .await?;
+
+        Ok(Some(status))
+    }
+
+    async fn get_archiver_status(&self) -> AppResult<PgArchiverStatus> {
+        let status = sqlx::query_as::<_, PgArchiverStatus>(
+            r#"
+            SELECT current_setting('archive_mode') AS archive_mode, archived_count,
+                   last_archived_wal, last_archived_time, failed_count, last_failed_wal,
+                   last_failed_time
+            FROM pg_stat_archiver;
+            "#,
         )
-        .fetch_all(&self.pool)
+        .fetch_one(self.pool.get())
         .await?;
 
-        Ok(sizes)
+        Ok(status)
+    }
+
+    async fn get_wal_rate(&self) -> AppResult<Option<f64>> {
+        let rate = sqlx::query_scalar::<_, Option<f64>>(
+            r#"
+            WITH recent AS (
+                SELECT taken_at, wal_position
+                FROM pg_stat_snapshot
+                WHERE wal_position IS NOT NULL
+                ORDER BY taken_at DESC LIMIT 2
+            )
+            SELECT (max(wal_position) - min(wal_position))::float8
+                   / NULLIF(EXTRACT(EPOCH FROM max(taken_at) - min(taken_at))::float8, 0)
+            FROM recent;
+            "#,
+        )
+        .fetch_one(self.pool.get())
+        .await?;
+
+        Ok(rate)
     }
 }
 
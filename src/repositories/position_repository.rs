@@ -1,67 +1,214 @@
-use crate::api::models::{PositionAsset, PositionDebt, PositionRatio};
-use crate::repositories::RepositoryResult;
+use crate::api::models::{PositionAsset, PositionBucket, PositionDebt, PositionRatio};
+use crate::core::db::DbPool;
+use crate::repositories::{RepositoryResult, TableFilter};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use sqlx::AssertSqlSafe;
+use utoipa::ToSchema;
+
+pub(crate) const GET_POSITION_ASSETS_SQL: &str = r#"
+    SELECT exchange, asset_symbol, asset_total, asset_available, asset_hold, updated_at
+    FROM positionasset
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+pub(crate) const GET_POSITION_DEBTS_SQL: &str = r#"
+    SELECT exchange, debt_symbol, debt_value, updated_at
+    FROM positiondebt
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+pub(crate) const GET_POSITION_RATIOS_SQL: &str = r#"
+    SELECT exchange, debt_ratio, total_asset, margin_coefficient_total_asset,
+           total_debt, updated_at
+    FROM positionratio
+    WHERE ($1::text IS NULL OR lower(exchange) = lower($1))
+"#;
+
+/// History queries bucket rows in `[to_timestamp($1), to_timestamp($2))`
+/// into `$3`-second bins aligned to UTC midnight, optionally only for
+/// exchange `$4` and key `$5`. `last` is the value of the newest row in
+/// the bin.
+pub(crate) const GET_POSITION_RATIO_HISTORY_SQL: &str = r#"
+    SELECT date_bin(make_interval(secs => $3::float8), updated_at,
+                    TIMESTAMPTZ '2000-01-01 00:00:00+00') AS bucket,
+           exchange, v.key,
+           min(v.value) AS min, max(v.value) AS max, avg(v.value) AS avg,
+           (array_agg(v.value ORDER BY updated_at DESC))[1] AS last,
+           count(*) AS samples
+    FROM positionratio
+    CROSS JOIN LATERAL (VALUES ('debt_ratio', debt_ratio),
+                               ('total_asset', total_asset),
+                               ('total_debt', NULLIF(total_debt, '')::float8)) AS v(key, value)
+    WHERE updated_at >= to_timestamp($1::float8)
+      AND updated_at < to_timestamp($2::float8)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+      AND ($5::text IS NULL OR lower(v.key) = lower($5))
+    GROUP BY 1, 2, 3
+    ORDER BY 1, 2, 3
+"#;
+
+pub(crate) const GET_POSITION_DEBT_HISTORY_SQL: &str = r#"
+    SELECT date_bin(make_interval(secs => $3::float8), updated_at,
+                    TIMESTAMPTZ '2000-01-01 00:00:00+00') AS bucket,
+           exchange, v.key,
+           min(v.value) AS min, max(v.value) AS max, avg(v.value) AS avg,
+           (array_agg(v.value ORDER BY updated_at DESC))[1] AS last,
+           count(*) AS samples
+    FROM positiondebt
+    CROSS JOIN LATERAL (VALUES (debt_symbol, NULLIF(debt_value, '')::float8)) AS v(key, value)
+    WHERE updated_at >= to_timestamp($1::float8)
+      AND updated_at < to_timestamp($2::float8)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+      AND ($5::text IS NULL OR lower(v.key) = lower($5))
+    GROUP BY 1, 2, 3
+    ORDER BY 1, 2, 3
+"#;
+
+pub(crate) const GET_POSITION_ASSET_HISTORY_SQL: &str = r#"
+    SELECT date_bin(make_interval(secs => $3::float8), updated_at,
+                    TIMESTAMPTZ '2000-01-01 00:00:00+00') AS bucket,
+           exchange, v.key,
+           min(v.value) AS min, max(v.value) AS max, avg(v.value) AS avg,
+           (array_agg(v.value ORDER BY updated_at DESC))[1] AS last,
+           count(*) AS samples
+    FROM positionasset
+    CROSS JOIN LATERAL (VALUES (asset_symbol, NULLIF(asset_total, '')::float8)) AS v(key, value)
+    WHERE updated_at >= to_timestamp($1::float8)
+      AND updated_at < to_timestamp($2::float8)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+      AND ($5::text IS NULL OR lower(v.key) = lower($5))
+    GROUP BY 1, 2, 3
+    ORDER BY 1, 2, 3
+"#;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionSeries {
+    Ratio,
+    Debt,
+    Asset,
+}
+
+impl PositionSeries {
+    fn history_sql(self) -> &'static str {
+        match self {
+            PositionSeries::Ratio => GET_POSITION_RATIO_HISTORY_SQL,
+            PositionSeries::Debt => GET_POSITION_DEBT_HISTORY_SQL,
+            PositionSeries::Asset => GET_POSITION_ASSET_HISTORY_SQL,
+        }
+    }
+}
 
 #[async_trait]
 pub trait PositionRepository: Send + Sync {
-    async fn get_position_assets(&self) -> RepositoryResult<Vec<PositionAsset>>;
-    async fn get_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>>;
-    async fn get_position_ratios(&self) -> RepositoryResult<Vec<PositionRatio>>;
+    async fn get_position_assets(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionAsset>>;
+    async fn get_position_debts(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionDebt>>;
+    async fn get_position_ratios(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionRatio>>;
+    async fn get_position_history(
+        &self,
+        series: PositionSeries,
+        from_epoch: i64,
+        to_epoch: i64,
+        bucket_secs: i64,
+        exchange: Option<&str>,
+        key: Option<&str>,
+    ) -> RepositoryResult<Vec<PositionBucket>>;
 }
 
 pub struct PostgresPositionRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresPositionRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
 
 #[async_trait]
 impl PositionRepository for PostgresPositionRepository {
-    async fn get_position_assets(&self) -> RepositoryResult<Vec<PositionAsset>> {
-        let positions = sqlx::query_as::<_, PositionAsset>(
-            r#"
-            SELECT exchange, asset_symbol, asset_total, asset_available, asset_hold, updated_at
-            FROM positionasset
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_position_assets(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionAsset>> {
+        let mut query = sqlx::query_as::<_, PositionAsset>(AssertSqlSafe(
+            filter.wrap(GET_POSITION_ASSETS_SQL, 1),
+        ))
+        .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let positions = query.fetch_all(self.pool.get()).await?;
 
         Ok(positions)
     }
 
-    async fn get_position_debts(&self) -> RepositoryResult<Vec<PositionDebt>> {
-        let positions = sqlx::query_as::<_, PositionDebt>(
-            r#"
-            SELECT exchange, debt_symbol, debt_value, updated_at
-            FROM positiondebt
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_position_debts(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionDebt>> {
+        let mut query = sqlx::query_as::<_, PositionDebt>(AssertSqlSafe(
+            filter.wrap(GET_POSITION_DEBTS_SQL, 1),
+        ))
+        .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let positions = query.fetch_all(self.pool.get()).await?;
 
         Ok(positions)
     }
 
-    async fn get_position_ratios(&self) -> RepositoryResult<Vec<PositionRatio>> {
-        let positions = sqlx::query_as::<_, PositionRatio>(
-            r#"
-            SELECT exchange, debt_ratio, total_asset, margin_coefficient_total_asset, 
-                   total_debt, updated_at
-            FROM positionratio
-            ORDER BY updated_at DESC LIMIT 1000;
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_position_ratios(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> RepositoryResult<Vec<PositionRatio>> {
+        let mut query = sqlx::query_as::<_, PositionRatio>(AssertSqlSafe(
+            filter.wrap(GET_POSITION_RATIOS_SQL, 1),
+        ))
+        .bind(exchange);
+        for pattern in filter.patterns() {
+            query = query.bind(pattern);
+        }
+        let positions = query.fetch_all(self.pool.get()).await?;
 
         Ok(positions)
     }
+
+    async fn get_position_history(
+        &self,
+        series: PositionSeries,
+        from_epoch: i64,
+        to_epoch: i64,
+        bucket_secs: i64,
+        exchange: Option<&str>,
+        key: Option<&str>,
+    ) -> RepositoryResult<Vec<PositionBucket>> {
+        let buckets = sqlx::query_as::<_, PositionBucket>(series.history_sql())
+            .bind(from_epoch)
+            .bind(to_epoch)
+            .bind(bucket_secs)
+            .bind(exchange)
+            .bind(key)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(buckets)
+    }
 }
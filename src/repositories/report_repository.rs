@@ -0,0 +1,297 @@
+use crate::api::models::{
+    DebtRatioRange, ErrorCount, NewReport, RateLimitIncidents, Report, ReportEntry, TableBytes,
+};
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+
+const GET_REPORTS_SQL: &str = r#"
+    SELECT id, created_at, period, period_start, period_end, delivered_at, delivery_error
+    FROM report
+    WHERE ($1::text IS NULL OR period = $1)
+    ORDER BY period_start DESC, id DESC
+    LIMIT $2
+"#;
+
+const GET_REPORT_SQL: &str = r#"
+    SELECT id, created_at, period, period_start, period_end, html, markdown,
+           delivered_at, delivery_error
+    FROM report
+    WHERE id = $1
+"#;
+
+const GET_LATEST_PERIOD_START_SQL: &str = "SELECT max(period_start) FROM report WHERE period = $1";
+
+/// Reports whose webhook delivery failed, due for a retry and tried fewer
+/// than `$2` times, longest due first.
+const GET_FAILED_DELIVERIES_SQL: &str = r#"
+    SELECT id, created_at, period, period_start, period_end, html, markdown,
+           delivered_at, delivery_error
+    FROM report
+    WHERE delivered_at IS NULL AND delivery_error IS NOT NULL
+      AND delivery_attempts < $2 AND next_delivery_at <= now()
+    ORDER BY next_delivery_at, id
+    LIMIT $1
+"#;
+
+/// Another instance may have stored the same period first; it then owns the
+/// delivery and nothing is returned here.
+const INSERT_REPORT_SQL: &str = r#"
+    INSERT INTO report (period, period_start, period_end, html, markdown, tables)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (period, period_start) DO NOTHING
+    RETURNING id
+"#;
+
+/// A failure is retried after 5 minutes, doubling per attempt up to a day.
+const SET_REPORT_DELIVERY_SQL: &str = r#"
+    UPDATE report
+    SET delivered_at = CASE WHEN $2::text IS NULL THEN now() END,
+        delivery_error = $2,
+        delivery_attempts = delivery_attempts + 1,
+        next_delivery_at = CASE WHEN $2::text IS NOT NULL THEN
+            now() + least(interval '5 minutes' * power(2, delivery_attempts), interval '1 day')
+        END
+    WHERE id = $1
+"#;
+
+/// Backfilled reports store no table sizes and are skipped.
+const GET_PREVIOUS_TABLE_BYTES_SQL: &str = r#"
+    SELECT tables
+    FROM report
+    WHERE period = $1 AND period_start < $2 AND tables <> '[]'::jsonb
+    ORDER BY period_start DESC
+    LIMIT 1
+"#;
+
+const GET_TOP_ERRORS_SQL: &str = r#"
+    SELECT exchange, msg, count(*) AS count, max(updated_at) AS last_at
+    FROM errors
+    WHERE updated_at >= $1 AND updated_at < $2
+    GROUP BY exchange, msg
+    ORDER BY count DESC, last_at DESC
+    LIMIT $3
+"#;
+
+const GET_DEBT_RATIO_RANGES_SQL: &str = r#"
+    SELECT exchange, min(debt_ratio) AS min_ratio, max(debt_ratio) AS max_ratio,
+           avg(debt_ratio) AS avg_ratio, count(*) AS samples
+    FROM positionratio
+    WHERE updated_at >= $1 AND updated_at < $2
+    GROUP BY exchange
+    ORDER BY exchange
+"#;
+
+/// KuCoin answers a rate limited request with code 429000, and reports an
+/// exhausted quota in the remaining rate; the bot logs other exchanges'
+/// HTTP 429s as errors.
+const GET_RATE_LIMIT_INCIDENTS_SQL: &str = r#"
+    SELECT exchange, 'msgevent' AS source, count(*) AS count,
+           min(updated_at) AS first_at, max(updated_at) AS last_at
+    FROM msgevent
+    WHERE updated_at >= $1 AND updated_at < $2
+      AND (code = '429000' OR remaining_rate = 0)
+    GROUP BY exchange
+    UNION ALL
+    SELECT exchange, 'errors' AS source, count(*) AS count,
+           min(updated_at) AS first_at, max(updated_at) AS last_at
+    FROM errors
+    WHERE updated_at >= $1 AND updated_at < $2
+      AND (msg ILIKE '%429%' OR msg ILIKE '%too many requests%' OR msg ILIKE '%rate limit%')
+    GROUP BY exchange
+    ORDER BY count DESC
+"#;
+
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn get_reports(
+        &self,
+        period: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<ReportEntry>>;
+    async fn get_report(&self, id: i64) -> RepositoryResult<Option<Report>>;
+    /// Start of the newest stored report of `period`.
+    async fn get_latest_period_start(
+        &self,
+        period: &str,
+    ) -> RepositoryResult<Option<DateTime<Utc>>>;
+    /// Failed deliveries due for a retry, of at most `max_attempts` tries.
+    async fn get_failed_deliveries(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+    ) -> RepositoryResult<Vec<Report>>;
+    /// `None` when the period was already stored.
+    async fn create_report(&self, report: &NewReport) -> RepositoryResult<Option<i64>>;
+    /// Records a delivery, or its failure when `error` is set.
+    async fn set_delivery(&self, id: i64, error: Option<&str>) -> RepositoryResult<()>;
+    /// Table sizes stored with the latest report of `period` before `start`.
+    async fn get_previous_table_bytes(
+        &self,
+        period: &str,
+        start: DateTime<Utc>,
+    ) -> RepositoryResult<Option<Vec<TableBytes>>>;
+    async fn get_top_errors(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<ErrorCount>>;
+    async fn get_debt_ratio_ranges(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> RepositoryResult<Vec<DebtRatioRange>>;
+    async fn get_rate_limit_incidents(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> RepositoryResult<Vec<RateLimitIncidents>>;
+}
+
+pub struct PostgresReportRepository {
+    pool: DbPool,
+}
+
+impl PostgresReportRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ReportRepository for PostgresReportRepository {
+    async fn get_reports(
+        &self,
+        period: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<ReportEntry>> {
+        let reports = sqlx::query_as::<_, ReportEntry>(GET_REPORTS_SQL)
+            .bind(period)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(reports)
+    }
+
+    async fn get_report(&self, id: i64) -> RepositoryResult<Option<Report>> {
+        let report = sqlx::query_as::<_, Report>(GET_REPORT_SQL)
+            .bind(id)
+            .fetch_optional(self.pool.get())
+            .await?;
+
+        Ok(report)
+    }
+
+    async fn get_latest_period_start(
+        &self,
+        period: &str,
+    ) -> RepositoryResult<Option<DateTime<Utc>>> {
+        let start = sqlx::query_scalar(GET_LATEST_PERIOD_START_SQL)
+            .bind(period)
+            .fetch_one(self.pool.get())
+            .await?;
+
+        Ok(start)
+    }
+
+    async fn get_failed_deliveries(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+    ) -> RepositoryResult<Vec<Report>> {
+        let reports = sqlx::query_as::<_, Report>(GET_FAILED_DELIVERIES_SQL)
+            .bind(limit)
+            .bind(max_attempts)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(reports)
+    }
+
+    async fn create_report(&self, report: &NewReport) -> RepositoryResult<Option<i64>> {
+        let id = sqlx::query_scalar(INSERT_REPORT_SQL)
+            .bind(&report.period)
+            .bind(report.period_start)
+            .bind(report.period_end)
+            .bind(&report.html)
+            .bind(&report.markdown)
+            .bind(Json(&report.tables))
+            .fetch_optional(self.pool.get())
+            .await?;
+
+        Ok(id)
+    }
+
+    async fn set_delivery(&self, id: i64, error: Option<&str>) -> RepositoryResult<()> {
+        sqlx::query(SET_REPORT_DELIVERY_SQL)
+            .bind(id)
+            .bind(error)
+            .execute(self.pool.get())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_previous_table_bytes(
+        &self,
+        period: &str,
+        start: DateTime<Utc>,
+    ) -> RepositoryResult<Option<Vec<TableBytes>>> {
+        let tables: Option<Json<Vec<TableBytes>>> =
+            sqlx::query_scalar(GET_PREVIOUS_TABLE_BYTES_SQL)
+                .bind(period)
+                .bind(start)
+                .fetch_optional(self.pool.get())
+                .await?;
+
+        Ok(tables.map(|Json(tables)| tables))
+    }
+
+    async fn get_top_errors(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<ErrorCount>> {
+        let errors = sqlx::query_as::<_, ErrorCount>(GET_TOP_ERRORS_SQL)
+            .bind(from)
+            .bind(to)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(errors)
+    }
+
+    async fn get_debt_ratio_ranges(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> RepositoryResult<Vec<DebtRatioRange>> {
+        let ranges = sqlx::query_as::<_, DebtRatioRange>(GET_DEBT_RATIO_RANGES_SQL)
+            .bind(from)
+            .bind(to)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(ranges)
+    }
+
+    async fn get_rate_limit_incidents(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> RepositoryResult<Vec<RateLimitIncidents>> {
+        let incidents = sqlx::query_as::<_, RateLimitIncidents>(GET_RATE_LIMIT_INCIDENTS_SQL)
+            .bind(from)
+            .bind(to)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(incidents)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::api::models::{Error, EventOrder, PositionRatio, StreamRow};
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Rows after the `($1, $2)` keyset of `updated_at` and `ctid` and, when
+/// `$3` is set, at or before `$3`, oldest first so a caller can continue
+/// from the last one. `updated_at` alone is not unique, and these tables
+/// have no key of their own.
+const GET_ORDERS_SINCE_SQL: &str = r#"
+    SELECT exchange, status, type_, symbol, side, order_type, fee_type,
+           liquidity, price, order_id, client_oid, trade_id, origin_size,
+           size, filled_size, match_size, match_price, canceled_size,
+           old_size, remain_size, remain_funds, order_time, ts, updated_at,
+           ctid::text AS row_id
+    FROM orderevent
+    WHERE (updated_at, ctid) > ($1, $2::tid)
+      AND ($3::timestamptz IS NULL OR updated_at <= $3)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+      AND ($5::text IS NULL OR lower(symbol) = lower($5))
+    ORDER BY updated_at, ctid
+    LIMIT $6
+"#;
+
+const GET_ERRORS_SINCE_SQL: &str = r#"
+    SELECT exchange, msg, updated_at, ctid::text AS row_id
+    FROM errors
+    WHERE (updated_at, ctid) > ($1, $2::tid)
+      AND ($3::timestamptz IS NULL OR updated_at <= $3)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+    ORDER BY updated_at, ctid
+    LIMIT $5
+"#;
+
+const GET_POSITION_RATIOS_SINCE_SQL: &str = r#"
+    SELECT exchange, debt_ratio, total_asset, margin_coefficient_total_asset,
+           total_debt, updated_at, ctid::text AS row_id
+    FROM positionratio
+    WHERE (updated_at, ctid) > ($1, $2::tid)
+      AND ($3::timestamptz IS NULL OR updated_at <= $3)
+      AND ($4::text IS NULL OR lower(exchange) = lower($4))
+    ORDER BY updated_at, ctid
+    LIMIT $5
+"#;
+
+/// Where a page of rows starts: after this `updated_at` and `ctid`.
+pub type RowKey = (DateTime<Utc>, String);
+
+#[async_trait]
+pub trait StreamRepository: Send + Sync {
+    async fn get_orders_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        symbol: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<EventOrder>>>;
+    async fn get_errors_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<Error>>>;
+    async fn get_position_ratios_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<PositionRatio>>>;
+}
+
+pub struct PostgresStreamRepository {
+    pool: DbPool,
+}
+
+impl PostgresStreamRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl StreamRepository for PostgresStreamRepository {
+    async fn get_orders_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        symbol: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<EventOrder>>> {
+        let orders = sqlx::query_as::<_, StreamRow<EventOrder>>(GET_ORDERS_SINCE_SQL)
+            .bind(after.0)
+            .bind(&after.1)
+            .bind(until)
+            .bind(exchange)
+            .bind(symbol)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(orders)
+    }
+
+    async fn get_errors_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<Error>>> {
+        let errors = sqlx::query_as::<_, StreamRow<Error>>(GET_ERRORS_SINCE_SQL)
+            .bind(after.0)
+            .bind(&after.1)
+            .bind(until)
+            .bind(exchange)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(errors)
+    }
+
+    async fn get_position_ratios_since(
+        &self,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        exchange: Option<&str>,
+        limit: i64,
+    ) -> RepositoryResult<Vec<StreamRow<PositionRatio>>> {
+        let ratios = sqlx::query_as::<_, StreamRow<PositionRatio>>(GET_POSITION_RATIOS_SINCE_SQL)
+            .bind(after.0)
+            .bind(&after.1)
+            .bind(until)
+            .bind(exchange)
+            .bind(limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(ratios)
+    }
+}
@@ -0,0 +1,214 @@
+use crate::api::models::{
+    NewSymbolChange, SymbolCatalogEntry, SymbolCatalogSnapshot, SymbolChange,
+};
+use crate::core::db::DbPool;
+use crate::repositories::{RepositoryResult, escape_like};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub(crate) const GET_CATALOG_SQL: &str = r#"
+    SELECT exchange, symbol, to_jsonb(s) - 'updated_at' AS fields
+    FROM symbol s
+"#;
+
+pub(crate) const GET_SYMBOL_CHANGES_SQL: &str = r#"
+    SELECT c.id, c.snapshot_id, s.taken_at, c.exchange, c.symbol, c.kind,
+           c.field, c.old_value, c.new_value
+    FROM symbol_change c
+    JOIN symbol_catalog_snapshot s ON s.id = c.snapshot_id
+    WHERE ($1::text IS NULL OR c.symbol ILIKE '%' || $1 || '%')
+      AND ($2::text IS NULL OR c.field = $2)
+      AND ($3::text IS NULL OR c.kind = $3)
+    ORDER BY c.id DESC
+    LIMIT $4
+"#;
+
+/// Keeps the latest snapshot, which the next one is diffed against.
+const DELETE_SNAPSHOTS_BEFORE_SQL: &str = r#"
+    DELETE FROM symbol_catalog_snapshot
+    WHERE taken_at < $1
+      AND id <> (SELECT max(id) FROM symbol_catalog_snapshot)
+"#;
+
+pub struct SymbolChangeFilter {
+    /// Substring match on the symbol, case-insensitive.
+    pub symbol: Option<String>,
+    pub field: Option<String>,
+    pub kind: Option<String>,
+    pub limit: i64,
+}
+
+#[async_trait]
+pub trait SymbolChangeRepository: Send + Sync {
+    /// The live `symbol` table in snapshot form.
+    async fn get_catalog(&self) -> RepositoryResult<Vec<SymbolCatalogEntry>>;
+    async fn get_latest_snapshot(&self) -> RepositoryResult<Option<SymbolCatalogSnapshot>>;
+    async fn get_snapshot_entries(
+        &self,
+        snapshot_id: i64,
+    ) -> RepositoryResult<Vec<SymbolCatalogEntry>>;
+    /// Stores the catalog and the changes against the previous snapshot
+    /// in one transaction.
+    async fn save_snapshot(
+        &self,
+        entries: &[SymbolCatalogEntry],
+        changes: &[NewSymbolChange],
+    ) -> RepositoryResult<SymbolCatalogSnapshot>;
+    async fn get_changes(&self, filter: &SymbolChangeFilter)
+    -> RepositoryResult<Vec<SymbolChange>>;
+    /// Drops snapshots taken before `before`, with their entries and
+    /// changes, except the latest one.
+    async fn delete_snapshots_before(&self, before: DateTime<Utc>) -> RepositoryResult<u64>;
+}
+
+pub struct PostgresSymbolChangeRepository {
+    pool: DbPool,
+}
+
+impl PostgresSymbolChangeRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SymbolChangeRepository for PostgresSymbolChangeRepository {
+    async fn get_catalog(&self) -> RepositoryResult<Vec<SymbolCatalogEntry>> {
+        let entries = sqlx::query_as::<_, SymbolCatalogEntry>(GET_CATALOG_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(entries)
+    }
+
+    async fn get_latest_snapshot(&self) -> RepositoryResult<Option<SymbolCatalogSnapshot>> {
+        let snapshot = sqlx::query_as::<_, SymbolCatalogSnapshot>(
+            r#"
+            SELECT id, taken_at, symbol_count
+            FROM symbol_catalog_snapshot
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(self.pool.get())
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    async fn get_snapshot_entries(
+        &self,
+        snapshot_id: i64,
+    ) -> RepositoryResult<Vec<SymbolCatalogEntry>> {
+        let entries = sqlx::query_as::<_, SymbolCatalogEntry>(
+            r#"
+            SELECT exchange, symbol, fields
+            FROM symbol_catalog_entry
+            WHERE snapshot_id = $1
+            "#,
+        )
+        .bind(snapshot_id)
+        .fetch_all(self.pool.get())
+        .await?;
+
+        Ok(entries)
+    }
+
+    async fn save_snapshot(
+        &self,
+        entries: &[SymbolCatalogEntry],
+        changes: &[NewSymbolChange],
+    ) -> RepositoryResult<SymbolCatalogSnapshot> {
+        let mut tx = self.pool.get().begin().await?;
+
+        let snapshot = sqlx::query_as::<_, SymbolCatalogSnapshot>(
+            r#"
+            INSERT INTO symbol_catalog_snapshot (symbol_count)
+            VALUES ($1)
+            RETURNING id, taken_at, symbol_count
+            "#,
+        )
+        .bind(entries.len() as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO symbol_catalog_entry (snapshot_id, exchange, symbol, fields)
+            SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::jsonb[])
+            "#,
+        )
+        .bind(snapshot.id)
+        .bind(
+            entries
+                .iter()
+                .map(|e| e.exchange.clone())
+                .collect::<Vec<_>>(),
+        )
+        .bind(entries.iter().map(|e| e.symbol.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.fields.clone()).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO symbol_change
+                (snapshot_id, exchange, symbol, kind, field, old_value, new_value)
+            SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[],
+                                     $6::text[], $7::text[])
+            "#,
+        )
+        .bind(snapshot.id)
+        .bind(
+            changes
+                .iter()
+                .map(|c| c.exchange.clone())
+                .collect::<Vec<_>>(),
+        )
+        .bind(changes.iter().map(|c| c.symbol.clone()).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.kind).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.field.clone()).collect::<Vec<_>>())
+        .bind(
+            changes
+                .iter()
+                .map(|c| c.old_value.clone())
+                .collect::<Vec<_>>(),
+        )
+        .bind(
+            changes
+                .iter()
+                .map(|c| c.new_value.clone())
+                .collect::<Vec<_>>(),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(snapshot)
+    }
+
+    async fn get_changes(
+        &self,
+        filter: &SymbolChangeFilter,
+    ) -> RepositoryResult<Vec<SymbolChange>> {
+        let changes = sqlx::query_as::<_, SymbolChange>(GET_SYMBOL_CHANGES_SQL)
+            .bind(filter.symbol.as_deref().map(escape_like))
+            .bind(&filter.field)
+            .bind(&filter.kind)
+            .bind(filter.limit)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(changes)
+    }
+
+    async fn delete_snapshots_before(&self, before: DateTime<Utc>) -> RepositoryResult<u64> {
+        let result = sqlx::query(DELETE_SNAPSHOTS_BEFORE_SQL)
+            .bind(before)
+            .execute(self.pool.get())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
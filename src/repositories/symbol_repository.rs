@@ -1,20 +1,41 @@
-use crate::api::models::Symbol;
+use crate::api::models::{Symbol, SymbolVolume};
+use crate::core::db::DbPool;
 use crate::repositories::RepositoryResult;
 use async_trait::async_trait;
-use sqlx::PgPool;
+
+pub(crate) const GET_ALL_SYMBOLS_SQL: &str = r#"
+    SELECT exchange, symbol, symbol_name, base_currency, quote_currency,
+           fee_currency, market, base_min_size, quote_min_size, base_max_size,
+           quote_max_size, base_increment, quote_increment, price_increment,
+           price_limit_rate, min_funds, is_margin_enabled, enable_trading,
+           fee_category, maker_fee_coefficient, taker_fee_coefficient, st, updated_at
+    FROM symbol
+    ORDER BY updated_at DESC
+"#;
+
+/// Quote volume the bot matched per symbol over the last `$1` hours.
+pub(crate) const GET_QUOTE_VOLUMES_SQL: &str = r#"
+    SELECT exchange, symbol,
+           COALESCE(sum(NULLIF(match_size, '')::numeric
+                        * NULLIF(match_price, '')::numeric), 0)::float8 AS quote_volume
+    FROM orderevent
+    WHERE type_ = 'match'
+      AND updated_at >= now() - make_interval(hours => $1::int)
+    GROUP BY exchange, symbol
+"#;
 
 #[async_trait]
 pub trait SymbolRepository: Send + Sync {
     async fn get_all_symbols(&self) -> RepositoryResult<Vec<Symbol>>;
-    async fn get_tradeable_symbols(&self) -> RepositoryResult<Vec<Symbol>>;
+    async fn get_quote_volumes(&self, hours: i64) -> RepositoryResult<Vec<SymbolVolume>>;
 }
 
 pub struct PostgresSymbolRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresSymbolRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
@@ -22,44 +43,19 @@ impl PostgresSymbolRepository {
 #[async_trait]
 impl SymbolRepository for PostgresSymbolRepository {
     async fn get_all_symbols(&self) -> RepositoryResult<Vec<Symbol>> {
-        let symbols = sqlx::query_as::<_, Symbol>(
-            r#"
-            SELECT exchange, symbol, symbol_name, base_currency, quote_currency, 
-                   fee_currency, market, base_min_size, quote_min_size, base_max_size, 
-                   quote_max_size, base_increment, quote_increment, price_increment, 
-                   price_limit_rate, min_funds, is_margin_enabled, enable_trading, 
-                   fee_category, maker_fee_coefficient, taker_fee_coefficient, st, updated_at
-            FROM symbol
-            ORDER BY updated_at DESC
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let symbols = sqlx::query_as::<_, Symbol>(GET_ALL_SYMBOLS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(symbols)
     }
 
-    async fn get_tradeable_symbols(&self) -> RepositoryResult<Vec<Symbol>> {
-        let symbols = sqlx::query_as::<_, Symbol>(
-            r#"
-            SELECT exchange, symbol, symbol_name, base_currency, quote_currency, 
-                   fee_currency, market, base_min_size, quote_min_size, base_max_size, 
-                   quote_max_size, base_increment, quote_increment, price_increment, 
-                   price_limit_rate, min_funds, is_margin_enabled, enable_trading, 
-                   fee_category, maker_fee_coefficient, taker_fee_coefficient, st, updated_at
-            FROM symbol
-            WHERE is_margin_enabled = true 
-              AND enable_trading = true 
-              AND fee_category = 1 
-              AND quote_currency = 'USDT' 
-              AND base_currency <> 'USDC' 
-              AND base_currency <> 'KCS' 
-            ORDER BY updated_at DESC
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_quote_volumes(&self, hours: i64) -> RepositoryResult<Vec<SymbolVolume>> {
+        let volumes = sqlx::query_as::<_, SymbolVolume>(GET_QUOTE_VOLUMES_SQL)
+            .bind(hours)
+            .fetch_all(self.pool.get())
+            .await?;
 
-        Ok(symbols)
+        Ok(volumes)
     }
 }
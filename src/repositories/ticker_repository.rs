@@ -1,7 +1,14 @@
 use crate::api::models::Ticker;
+use crate::core::db::DbPool;
 use crate::repositories::RepositoryResult;
 use async_trait::async_trait;
-use sqlx::PgPool;
+
+pub(crate) const GET_TICKERS_SQL: &str = r#"
+    SELECT exchange, symbol, symbol_name, taker_fee_rate, maker_fee_rate,
+           taker_coefficient, maker_coefficient, updated_at
+    FROM ticker
+    ORDER BY updated_at DESC
+"#;
 
 #[async_trait]
 pub trait TickerRepository: Send + Sync {
@@ -9,11 +16,11 @@ pub trait TickerRepository: Send + Sync {
 }
 
 pub struct PostgresTickerRepository {
-    pool: PgPool,
+    pool: DbPool,
 }
 
 impl PostgresTickerRepository {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 }
@@ -21,16 +28,9 @@ impl PostgresTickerRepository {
 #[async_trait]
 impl TickerRepository for PostgresTickerRepository {
     async fn get_tickers(&self) -> RepositoryResult<Vec<Ticker>> {
-        let tickers = sqlx::query_as::<_, Ticker>(
-            r#"
-            SELECT exchange, symbol, symbol_name, taker_fee_rate, maker_fee_rate, 
-                   taker_coefficient, maker_coefficient, updated_at
-            FROM ticker
-            ORDER BY updated_at DESC
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        let tickers = sqlx::query_as::<_, Ticker>(GET_TICKERS_SQL)
+            .fetch_all(self.pool.get())
+            .await?;
 
         Ok(tickers)
     }
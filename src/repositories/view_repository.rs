@@ -0,0 +1,135 @@
+use crate::api::models::TableView;
+use crate::core::db::DbPool;
+use crate::repositories::RepositoryResult;
+use async_trait::async_trait;
+
+const GET_VIEWS_SQL: &str = r#"
+    SELECT id, created_at, page, name, query, is_default
+    FROM table_view
+    WHERE page = $1
+    ORDER BY name
+"#;
+
+const CLEAR_DEFAULT_SQL: &str = "UPDATE table_view SET is_default = false WHERE page = $1";
+
+/// Re-saving keeps an existing default; unsetting goes through `SET_DEFAULT_SQL`.
+const SAVE_VIEW_SQL: &str = r#"
+    INSERT INTO table_view (page, name, query, is_default)
+    VALUES ($1, $2, $3, $4)
+    ON CONFLICT (page, name) DO UPDATE
+    SET query = EXCLUDED.query, is_default = table_view.is_default OR EXCLUDED.is_default
+    RETURNING id, created_at, page, name, query, is_default
+"#;
+
+const DELETE_VIEW_SQL: &str = r#"
+    DELETE FROM table_view
+    WHERE id = $1
+    RETURNING id, created_at, page, name, query, is_default
+"#;
+
+/// Clears the default of the page that view `$1` belongs to.
+const CLEAR_DEFAULT_OF_VIEW_PAGE_SQL: &str = r#"
+    UPDATE table_view SET is_default = false
+    WHERE page = (SELECT page FROM table_view WHERE id = $1)
+"#;
+
+const SET_DEFAULT_SQL: &str = r#"
+    UPDATE table_view SET is_default = $2
+    WHERE id = $1
+    RETURNING id, created_at, page, name, query, is_default
+"#;
+
+#[async_trait]
+pub trait ViewRepository: Send + Sync {
+    async fn get_views(&self, page: &str) -> RepositoryResult<Vec<TableView>>;
+    /// Creates the view, or replaces the query of the page's view with the
+    /// same name. `is_default` only ever makes the view the default.
+    async fn save_view(
+        &self,
+        page: &str,
+        name: &str,
+        query: &str,
+        is_default: bool,
+    ) -> RepositoryResult<TableView>;
+    /// `None` when there was no such view.
+    async fn delete_view(&self, id: i64) -> RepositoryResult<Option<TableView>>;
+    /// Makes the view its page's default, or clears the page's default.
+    async fn set_default(&self, id: i64, is_default: bool) -> RepositoryResult<Option<TableView>>;
+}
+
+pub struct PostgresViewRepository {
+    pool: DbPool,
+}
+
+impl PostgresViewRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ViewRepository for PostgresViewRepository {
+    async fn get_views(&self, page: &str) -> RepositoryResult<Vec<TableView>> {
+        let views = sqlx::query_as::<_, TableView>(GET_VIEWS_SQL)
+            .bind(page)
+            .fetch_all(self.pool.get())
+            .await?;
+
+        Ok(views)
+    }
+
+    async fn save_view(
+        &self,
+        page: &str,
+        name: &str,
+        query: &str,
+        is_default: bool,
+    ) -> RepositoryResult<TableView> {
+        let mut tx = self.pool.get().begin().await?;
+
+        if is_default {
+            sqlx::query(CLEAR_DEFAULT_SQL)
+                .bind(page)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let view = sqlx::query_as::<_, TableView>(SAVE_VIEW_SQL)
+            .bind(page)
+            .bind(name)
+            .bind(query)
+            .bind(is_default)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(view)
+    }
+
+    async fn delete_view(&self, id: i64) -> RepositoryResult<Option<TableView>> {
+        let view = sqlx::query_as::<_, TableView>(DELETE_VIEW_SQL)
+            .bind(id)
+            .fetch_optional(self.pool.get())
+            .await?;
+
+        Ok(view)
+    }
+
+    async fn set_default(&self, id: i64, is_default: bool) -> RepositoryResult<Option<TableView>> {
+        let mut tx = self.pool.get().begin().await?;
+
+        sqlx::query(CLEAR_DEFAULT_OF_VIEW_PAGE_SQL)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        let view = sqlx::query_as::<_, TableView>(SET_DEFAULT_SQL)
+            .bind(id)
+            .bind(is_default)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(view)
+    }
+}
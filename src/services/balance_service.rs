@@ -1,6 +1,6 @@
 use crate::api::models::Balance;
 use crate::core::error::AppResult;
-use crate::repositories::BalanceRepository;
+use crate::repositories::{BalanceRepository, LinkFilter, TableFilter};
 
 pub struct BalanceService<R: BalanceRepository> {
     repo: R,
@@ -11,7 +11,31 @@ impl<R: BalanceRepository> BalanceService<R> {
         Self { repo }
     }
 
-    pub async fn get_balances(&self, limit: i64) -> AppResult<Vec<Balance>> {
-        self.repo.get_balances(limit).await.map_err(Into::into)
+    pub async fn get_balances(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<Balance>> {
+        self.repo
+            .get_balances(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn search_balances(&self, term: &str, limit: i64) -> AppResult<Vec<Balance>> {
+        self.repo
+            .search_balances(term, limit)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_linked_balances(&self, filter: &LinkFilter) -> AppResult<Vec<Balance>> {
+        if !filter.has_keys() {
+            return Ok(Vec::new());
+        }
+        self.repo
+            .get_linked_balances(filter)
+            .await
+            .map_err(Into::into)
     }
 }
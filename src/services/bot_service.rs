@@ -1,46 +1,596 @@
-use crate::api::models::Bot;
+use crate::api::models::{Bot, BotOrderEvent};
 use crate::core::error::AppResult;
-use crate::repositories::BotRepository;
+use crate::repositories::{BotRepository, LinkFilter};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Balance every bot starts with.
+const INIT_BALANCE: f64 = 20.0;
+/// Symbols listed as best and worst performers, by average trade return.
+const RANKED_SYMBOLS: usize = 5;
 
 pub struct BotService<R: BotRepository> {
     repo: R,
+    stuck_after: Duration,
 }
 
 pub struct BotsWithStats {
-    pub bots: Vec<(usize, Bot)>,
+    pub bots: Vec<(usize, Bot, BotLifecycle)>,
     pub init_balance: f64,
     pub final_balance: f64,
+    pub state_counts: Vec<(BotState, usize)>,
+}
+
+impl BotsWithStats {
+    /// Inconsistent or stuck bots.
+    pub fn flagged(&self) -> impl Iterator<Item = &(usize, Bot, BotLifecycle)> {
+        self.bots
+            .iter()
+            .filter(|(_, _, l)| l.stuck || l.state == BotState::Inconsistent)
+    }
+
+    /// Bots not yet exited, by exchange.
+    pub fn open_by_exchange(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for (_, bot, lifecycle) in &self.bots {
+            if let (Some(exchange), true) = (&bot.exchange, lifecycle.state.is_open()) {
+                *counts.entry(exchange.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
 }
 
 impl<R: BotRepository> BotService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+    pub fn new(repo: R, stuck_after: Duration) -> Self {
+        Self { repo, stuck_after }
     }
 
     pub async fn get_bots(&self) -> AppResult<Vec<Bot>> {
         self.repo.get_bots().await.map_err(Into::into)
     }
 
-    pub async fn get_bots_with_stats(&self) -> AppResult<BotsWithStats> {
-        let bots = self.get_bots().await?;
+    /// Bots on `exchange`, or on every exchange for `None`.
+    pub async fn get_bots_with_stats(&self, exchange: Option<&str>) -> AppResult<BotsWithStats> {
+        let bots = self.get_bots().await?.into_iter().filter(|bot| {
+            exchange.is_none_or(|e| {
+                bot.exchange
+                    .as_deref()
+                    .is_some_and(|b| b.eq_ignore_ascii_case(e))
+            })
+        });
+        let orders = self.repo.get_bot_orders().await?;
+        let orders = OrderIndex::new(&orders);
+        let now = Utc::now();
 
-        let bots_with_index: Vec<(usize, Bot)> = bots
-            .into_iter()
+        let bots_with_index: Vec<(usize, Bot, BotLifecycle)> = bots
             .enumerate()
-            .map(|(i, v)| (i + 1, v))
+            .map(|(i, v)| {
+                let lifecycle = classify(&v, &orders, now, self.stuck_after);
+                (i + 1, v, lifecycle)
+            })
             .collect();
 
         let final_balance = bots_with_index
             .iter()
-            .filter_map(|(_, bot)| bot.balance.as_ref().and_then(|s| s.parse::<f64>().ok()))
+            .filter_map(|(_, bot, _)| bot.balance.as_ref().and_then(|s| s.parse::<f64>().ok()))
             .sum();
 
-        let init_balance = (20 * bots_with_index.len()) as f64;
+        let init_balance = INIT_BALANCE * bots_with_index.len() as f64;
+
+        let state_counts = BotState::ALL
+            .into_iter()
+            .map(|state| {
+                let count = bots_with_index
+                    .iter()
+                    .filter(|(_, _, l)| l.state == state)
+                    .count();
+                (state, count)
+            })
+            .collect();
 
         Ok(BotsWithStats {
             bots: bots_with_index,
             init_balance,
             final_balance,
+            state_counts,
+        })
+    }
+
+    /// Exited bots grouped by `group`, plus the best and worst symbols by
+    /// average trade return. Bots that have not exited yet have no outcome
+    /// to report. P&L is left out of period groupings, see
+    /// [`BotOutcome::pnl`].
+    pub async fn get_performance(
+        &self,
+        group: PerformanceGroup,
+        exchange: Option<&str>,
+    ) -> AppResult<PerformanceReport> {
+        let stats = self.get_bots_with_stats(exchange).await?;
+        let outcomes = stats
+            .bots
+            .iter()
+            .filter_map(|(_, bot, lifecycle)| BotOutcome::new(bot, lifecycle))
+            .collect();
+
+        Ok(performance_report(group, outcomes, !group.is_period()))
+    }
+
+    /// Like [`Self::get_performance`], for bots that exited in `[from, to)`
+    /// on any exchange. Being a period, it has no bot P&L, only trade P&L.
+    pub async fn get_performance_between(
+        &self,
+        group: PerformanceGroup,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> AppResult<PerformanceReport> {
+        let stats = self.get_bots_with_stats(None).await?;
+        let outcomes = stats
+            .bots
+            .iter()
+            .filter_map(|(_, bot, lifecycle)| BotOutcome::new(bot, lifecycle))
+            .filter(|outcome| outcome.exited_at >= from && outcome.exited_at < to)
+            .collect();
+
+        Ok(performance_report(group, outcomes, false))
+    }
+
+    pub async fn search_bots(&self, term: &str, limit: i64) -> AppResult<Vec<Bot>> {
+        self.repo.search_bots(term, limit).await.map_err(Into::into)
+    }
+
+    pub async fn get_linked_bots(&self, filter: &LinkFilter) -> AppResult<Vec<Bot>> {
+        if !filter.has_keys() {
+            return Ok(Vec::new());
+        }
+        self.repo.get_linked_bots(filter).await.map_err(Into::into)
+    }
+}
+
+/// `with_pnl` only when every outcome counts in full, as bot P&L is not
+/// per trade.
+fn performance_report(
+    group: PerformanceGroup,
+    outcomes: Vec<BotOutcome>,
+    with_pnl: bool,
+) -> PerformanceReport {
+    let mut ranked: Vec<PerformanceRow> = summarize(&outcomes, PerformanceGroup::Symbol, with_pnl)
+        .into_iter()
+        .filter(|row| row.avg_return_pct.is_some())
+        .collect();
+    let avg_return = |row: &PerformanceRow| row.avg_return_pct.unwrap_or_default();
+    ranked.sort_by(|a, b| avg_return(b).total_cmp(&avg_return(a)));
+    let best = ranked.len().min(RANKED_SYMBOLS);
+    // Symbols listed as best are not listed again as worst.
+    let worst_symbols = ranked[best..]
+        .iter()
+        .rev()
+        .take(RANKED_SYMBOLS)
+        .cloned()
+        .collect();
+    ranked.truncate(best);
+
+    PerformanceReport {
+        group,
+        rows: summarize(&outcomes, group, with_pnl),
+        best_symbols: ranked,
+        worst_symbols,
+        outcomes,
+        with_pnl,
+    }
+}
+
+fn summarize(
+    outcomes: &[BotOutcome],
+    group: PerformanceGroup,
+    with_pnl: bool,
+) -> Vec<PerformanceRow> {
+    summarize_by(outcomes, |outcome| group.key(outcome), with_pnl)
+}
+
+fn summarize_by(
+    outcomes: &[BotOutcome],
+    key: impl Fn(&BotOutcome) -> String,
+    with_pnl: bool,
+) -> Vec<PerformanceRow> {
+    let mut groups: BTreeMap<String, Vec<&BotOutcome>> = BTreeMap::new();
+    for outcome in outcomes {
+        groups.entry(key(outcome)).or_default().push(outcome);
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, outcomes)| {
+            let returns: Vec<f64> = outcomes.iter().filter_map(|o| o.return_pct).collect();
+            let holdings: Vec<i64> = outcomes.iter().filter_map(|o| o.holding_secs).collect();
+            let wins = outcomes.iter().filter(|o| o.is_win()).count();
+            let trade_pnls: Vec<f64> = outcomes.iter().filter_map(|o| o.trade_pnl).collect();
+
+            PerformanceRow {
+                key,
+                trades: outcomes.len(),
+                wins,
+                win_rate: wins as f64 / outcomes.len() as f64 * 100.0,
+                avg_return_pct: (!returns.is_empty())
+                    .then(|| returns.iter().sum::<f64>() / returns.len() as f64),
+                pnl: with_pnl.then(|| outcomes.iter().filter_map(|o| o.pnl).sum()),
+                trade_pnl: (!trade_pnls.is_empty()).then(|| trade_pnls.iter().sum()),
+                priced_trades: trade_pnls.len(),
+                avg_holding_secs: (!holdings.is_empty())
+                    .then(|| holdings.iter().sum::<i64>() / holdings.len() as i64),
+            }
+        })
+        .collect()
+}
+
+/// Orders by exchange and client oid or order id.
+struct OrderIndex<'a> {
+    by_client_oid: HashMap<(&'a str, &'a str), &'a BotOrderEvent>,
+    by_order_id: HashMap<(&'a str, &'a str), &'a BotOrderEvent>,
+}
+
+impl<'a> OrderIndex<'a> {
+    fn new(orders: &'a [BotOrderEvent]) -> Self {
+        Self {
+            by_client_oid: orders
+                .iter()
+                .filter_map(|o| Some(((o.exchange.as_str(), o.client_oid.as_deref()?), o)))
+                .collect(),
+            by_order_id: orders
+                .iter()
+                .map(|o| ((o.exchange.as_str(), o.order_id.as_str()), o))
+                .collect(),
+        }
+    }
+
+    fn order(&self, exchange: &str, order_id: Option<&str>, client_oid: Option<&str>) -> Order {
+        let event = order_id
+            .and_then(|id| self.by_order_id.get(&(exchange, id)))
+            .or_else(|| client_oid.and_then(|oid| self.by_client_oid.get(&(exchange, oid))));
+
+        match event {
+            Some(event) => Order {
+                placed: true,
+                status: match (event.type_.as_deref(), event.status.as_deref()) {
+                    (Some("filled"), _) | (Some("match"), Some("done")) => OrderStatus::Filled,
+                    (Some("canceled"), _) => OrderStatus::Canceled,
+                    _ => OrderStatus::Open,
+                },
+                at: Some(event.updated_at),
+                filled_size: event.filled_size.as_deref().and_then(|v| v.parse().ok()),
+            },
+            None => Order {
+                placed: order_id.is_some() || client_oid.is_some(),
+                status: OrderStatus::Open,
+                at: None,
+                filled_size: None,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OrderStatus {
+    Open,
+    Filled,
+    Canceled,
+}
+
+/// An order as far as the bot row and its latest event tell. An order
+/// without events is taken to be open.
+struct Order {
+    placed: bool,
+    status: OrderStatus,
+    at: Option<DateTime<Utc>>,
+    filled_size: Option<f64>,
+}
+
+impl Order {
+    fn is(&self, status: OrderStatus) -> bool {
+        self.placed && self.status == status
+    }
+}
+
+/// Where a bot is in its entry, protect, exit cycle. Exits are expected to
+/// be one-cancels-the-other: once one fills the other must not stay open.
+fn classify(
+    bot: &Bot,
+    orders: &OrderIndex,
+    now: DateTime<Utc>,
+    stuck_after: Duration,
+) -> BotLifecycle {
+    let exchange = bot.exchange.as_deref().unwrap_or_default();
+    let entry = orders.order(exchange, None, bot.entry_client_oid.as_deref());
+    let tp = orders.order(
+        exchange,
+        bot.exit_tp_order_id.as_deref(),
+        bot.exit_tp_client_oid.as_deref(),
+    );
+    let sl = orders.order(
+        exchange,
+        bot.exit_sl_order_id.as_deref(),
+        bot.exit_sl_client_oid.as_deref(),
+    );
+    let has_exits = tp.placed || sl.placed;
+    let latest = |orders: &[&Order]| orders.iter().filter_map(|o| o.at).max();
+
+    let inconsistent = |reason: &str, at: Option<DateTime<Utc>>| {
+        (BotState::Inconsistent, at, Some(reason.to_string()))
+    };
+    let (state, at, reason) = if has_exits && !entry.placed {
+        inconsistent("exit orders without an entry", latest(&[&tp, &sl]))
+    } else if tp.is(OrderStatus::Filled) && sl.is(OrderStatus::Filled) {
+        inconsistent("both TP and SL filled", latest(&[&tp, &sl]))
+    } else if has_exits && entry.is(OrderStatus::Canceled) {
+        inconsistent("exit orders for a canceled entry", latest(&[&tp, &sl]))
+    } else if tp.is(OrderStatus::Filled) {
+        if sl.is(OrderStatus::Open) {
+            inconsistent("SL still open after the TP filled", tp.at)
+        } else {
+            (BotState::ExitedTp, tp.at, None)
+        }
+    } else if sl.is(OrderStatus::Filled) {
+        if tp.is(OrderStatus::Open) {
+            inconsistent("TP still open after the SL filled", sl.at)
+        } else {
+            (BotState::ExitedSl, sl.at, None)
+        }
+    } else if tp.is(OrderStatus::Canceled) || sl.is(OrderStatus::Canceled) {
+        inconsistent("exit canceled without a fill", latest(&[&tp, &sl]))
+    } else if has_exits {
+        (BotState::ExitsPlaced, latest(&[&tp, &sl]), None)
+    } else if entry.is(OrderStatus::Filled) {
+        (BotState::Entered, entry.at, None)
+    } else {
+        (BotState::WaitingEntry, entry.at, None)
+    };
+
+    let since = at.unwrap_or(bot.updated_at);
+    let seconds_in_state = (now - since).num_seconds().max(0);
+    // Idle bots without an entry order are waiting by design.
+    let transitional = state == BotState::Entered
+        || (state == BotState::WaitingEntry && entry.is(OrderStatus::Open));
+
+    BotLifecycle {
+        state,
+        entered_at: entry.is(OrderStatus::Filled).then_some(entry.at).flatten(),
+        exit_size: match state {
+            BotState::ExitedTp => tp.filled_size,
+            BotState::ExitedSl => sl.filled_size,
+            _ => None,
+        },
+        since,
+        seconds_in_state,
+        stuck: transitional && seconds_in_state as u64 > stuck_after.as_secs(),
+        reason,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotState {
+    WaitingEntry,
+    Entered,
+    ExitsPlaced,
+    ExitedTp,
+    ExitedSl,
+    Inconsistent,
+}
+
+impl BotState {
+    const ALL: [BotState; 6] = [
+        BotState::WaitingEntry,
+        BotState::Entered,
+        BotState::ExitsPlaced,
+        BotState::ExitedTp,
+        BotState::ExitedSl,
+        BotState::Inconsistent,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotState::WaitingEntry => "waiting entry",
+            BotState::Entered => "entered",
+            BotState::ExitsPlaced => "TP/SL placed",
+            BotState::ExitedTp => "exited via TP",
+            BotState::ExitedSl => "exited via SL",
+            BotState::Inconsistent => "inconsistent",
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        !matches!(self, BotState::ExitedTp | BotState::ExitedSl)
+    }
+}
+
+pub struct BotLifecycle {
+    pub state: BotState,
+    /// Fill time of the entry order, when known.
+    pub entered_at: Option<DateTime<Utc>>,
+    /// Filled size of the exit order of an exited bot, when known.
+    pub exit_size: Option<f64>,
+    /// Time of the order event that led to the state, else the bot's
+    /// `updated_at`.
+    pub since: DateTime<Utc>,
+    pub seconds_in_state: i64,
+    pub stuck: bool,
+    /// Why the bot is inconsistent.
+    pub reason: Option<String>,
+}
+
+impl BotLifecycle {
+    pub fn time_in_state(&self) -> String {
+        format_duration(self.seconds_in_state)
+    }
+}
+
+fn format_duration(s: i64) -> String {
+    match s {
+        ..60 => format!("{}s", s),
+        60..3_600 => format!("{}m {}s", s / 60, s % 60),
+        3_600..86_400 => format!("{}h {}m", s / 3_600, s % 3_600 / 60),
+        _ => format!("{}d {}h", s / 86_400, s % 86_400 / 3_600),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PerformanceGroup {
+    #[default]
+    Symbol,
+    Exchange,
+    Day,
+    Week,
+    Month,
+    Exit,
+}
+
+impl PerformanceGroup {
+    /// Periods are those of the exit fill, in UTC.
+    fn key(self, outcome: &BotOutcome) -> String {
+        match self {
+            PerformanceGroup::Symbol => outcome.symbol.clone(),
+            PerformanceGroup::Exchange => outcome.exchange.clone(),
+            PerformanceGroup::Day => outcome.exited_at.format("%Y-%m-%d").to_string(),
+            PerformanceGroup::Week => outcome.exited_at.format("%G-W%V").to_string(),
+            PerformanceGroup::Month => outcome.exited_at.format("%Y-%m").to_string(),
+            PerformanceGroup::Exit => outcome.exit.to_string(),
+        }
+    }
+
+    /// Day, week or month.
+    fn is_period(self) -> bool {
+        matches!(
+            self,
+            PerformanceGroup::Day | PerformanceGroup::Week | PerformanceGroup::Month
+        )
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PerformanceGroup::Symbol => "symbol",
+            PerformanceGroup::Exchange => "exchange",
+            PerformanceGroup::Day => "day",
+            PerformanceGroup::Week => "week",
+            PerformanceGroup::Month => "month",
+            PerformanceGroup::Exit => "exit",
+        }
+    }
+}
+
+/// The last exit of a bot.
+#[derive(Serialize, ToSchema)]
+pub struct BotOutcome {
+    pub exchange: String,
+    pub symbol: String,
+    /// `tp` or `sl`.
+    pub exit: &'static str,
+    pub entry_price: Option<f64>,
+    pub exit_price: Option<f64>,
+    /// Price return of the trade; long when the TP is above the SL.
+    pub return_pct: Option<f64>,
+    /// Balance against the initial balance: the bot's P&L over all of its
+    /// trades, not only this one.
+    pub pnl: Option<f64>,
+    /// This trade's own P&L before fees: the price move times the exit's
+    /// filled size.
+    pub trade_pnl: Option<f64>,
+    pub entered_at: Option<DateTime<Utc>>,
+    pub exited_at: DateTime<Utc>,
+    pub holding_secs: Option<i64>,
+}
+
+impl BotOutcome {
+    fn new(bot: &Bot, lifecycle: &BotLifecycle) -> Option<Self> {
+        let (exit, exit_price) = match lifecycle.state {
+            BotState::ExitedTp => ("tp", &bot.exit_tp_price),
+            BotState::ExitedSl => ("sl", &bot.exit_sl_price),
+            _ => return None,
+        };
+        let price = |value: &Option<String>| value.as_deref().and_then(|v| v.parse::<f64>().ok());
+        let entry_price = price(&bot.entry_price);
+        let exit_price = price(exit_price);
+        let direction = match (price(&bot.exit_tp_price), price(&bot.exit_sl_price)) {
+            (Some(tp), Some(sl)) if tp < sl => -1.0,
+            _ => 1.0,
+        };
+
+        Some(Self {
+            exchange: bot.exchange.clone().unwrap_or_default(),
+            symbol: bot.symbol.clone().unwrap_or_default(),
+            exit,
+            entry_price,
+            exit_price,
+            return_pct: entry_price
+                .zip(exit_price)
+                .filter(|(entry, _)| *entry > 0.0)
+                .map(|(entry, exit)| (exit - entry) / entry * direction * 100.0),
+            pnl: price(&bot.balance).map(|balance| balance - INIT_BALANCE),
+            trade_pnl: entry_price
+                .zip(exit_price)
+                .zip(lifecycle.exit_size)
+                .map(|((entry, exit), size)| (exit - entry) * size * direction),
+            entered_at: lifecycle.entered_at,
+            exited_at: lifecycle.since,
+            holding_secs: lifecycle
+                .entered_at
+                .map(|entered| (lifecycle.since - entered).num_seconds().max(0)),
         })
     }
+
+    /// A positive trade return, or a TP exit when prices are missing.
+    fn is_win(&self) -> bool {
+        match self.return_pct {
+            Some(r) => r > 0.0,
+            None => self.exit == "tp",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct PerformanceRow {
+    pub key: String,
+    pub trades: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub avg_return_pct: Option<f64>,
+    /// Summed bot P&L; `None` where only some of the bots' trades count.
+    pub pnl: Option<f64>,
+    /// Summed [`BotOutcome::trade_pnl`] of the `priced_trades` that have
+    /// one; `None` when none has.
+    pub trade_pnl: Option<f64>,
+    pub priced_trades: usize,
+    pub avg_holding_secs: Option<i64>,
+}
+
+impl PerformanceRow {
+    pub fn avg_holding(&self) -> Option<String> {
+        self.avg_holding_secs.map(format_duration)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PerformanceReport {
+    pub group: PerformanceGroup,
+    pub rows: Vec<PerformanceRow>,
+    pub best_symbols: Vec<PerformanceRow>,
+    pub worst_symbols: Vec<PerformanceRow>,
+    pub outcomes: Vec<BotOutcome>,
+    #[serde(skip)]
+    with_pnl: bool,
+}
+
+impl PerformanceReport {
+    /// The outcomes grouped by another `group`.
+    pub fn regroup(&self, group: PerformanceGroup) -> Vec<PerformanceRow> {
+        summarize(&self.outcomes, group, self.with_pnl && !group.is_period())
+    }
+
+    /// All outcomes in one row keyed `all`; `None` without any.
+    pub fn total(&self) -> Option<PerformanceRow> {
+        summarize_by(&self.outcomes, |_| "all".to_string(), self.with_pnl).pop()
+    }
 }
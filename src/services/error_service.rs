@@ -1,6 +1,6 @@
 use crate::api::models::Error;
 use crate::core::error::AppResult;
-use crate::repositories::ErrorRepository;
+use crate::repositories::{ErrorRepository, TableFilter};
 
 pub struct ErrorService<R: ErrorRepository> {
     repo: R,
@@ -11,7 +11,14 @@ impl<R: ErrorRepository> ErrorService<R> {
         Self { repo }
     }
 
-    pub async fn get_errors(&self) -> AppResult<Vec<Error>> {
-        self.repo.get_errors().await.map_err(Into::into)
+    pub async fn get_errors(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<Error>> {
+        self.repo
+            .get_errors(exchange, filter)
+            .await
+            .map_err(Into::into)
     }
 }
@@ -1,6 +1,6 @@
 use crate::api::models::Event;
 use crate::core::error::AppResult;
-use crate::repositories::EventRepository;
+use crate::repositories::{EventRepository, TableFilter};
 
 pub struct EventService<R: EventRepository> {
     repo: R,
@@ -11,7 +11,14 @@ impl<R: EventRepository> EventService<R> {
         Self { repo }
     }
 
-    pub async fn get_events(&self) -> AppResult<Vec<Event>> {
-        self.repo.get_events().await.map_err(Into::into)
+    pub async fn get_events(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<Event>> {
+        self.repo
+            .get_events(exchange, filter)
+            .await
+            .map_err(Into::into)
     }
 }
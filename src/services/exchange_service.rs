@@ -0,0 +1,187 @@
+use crate::api::models::ExchangeActivity;
+use crate::core::error::AppResult;
+use crate::core::exchange;
+use crate::repositories::ExchangeRepository;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+pub struct ExchangeService<R: ExchangeRepository> {
+    repo: R,
+    summary_window: Duration,
+    stale_after: Duration,
+}
+
+impl<R: ExchangeRepository> ExchangeService<R> {
+    pub fn new(repo: R, summary_window: Duration, stale_after: Duration) -> Self {
+        Self {
+            repo,
+            summary_window,
+            stale_after,
+        }
+    }
+
+    /// One summary per exchange found in the data or configured, plus the
+    /// cross-exchange totals. `open_bots` counts the bots not yet exited by
+    /// exchange.
+    pub async fn get_overview(
+        &self,
+        open_bots: &HashMap<String, usize>,
+    ) -> AppResult<ExchangeOverview> {
+        let activity = self
+            .repo
+            .get_exchange_activity(self.summary_window.as_secs() as i64)
+            .await?;
+
+        Ok(build_overview(
+            activity,
+            open_bots,
+            self.summary_window,
+            self.stale_after,
+            Utc::now(),
+        ))
+    }
+}
+
+fn build_overview(
+    activity: Vec<ExchangeActivity>,
+    open_bots: &HashMap<String, usize>,
+    window: Duration,
+    stale_after: Duration,
+    now: DateTime<Utc>,
+) -> ExchangeOverview {
+    let window_hours = window.as_secs_f64() / 3600.0;
+    let mut summaries: Vec<ExchangeSummary> = activity
+        .into_iter()
+        .map(|a| {
+            let total_debt = a.total_debt.as_deref().and_then(|d| d.parse::<f64>().ok());
+            let age_secs = a.last_update.map(|t| (now - t).num_seconds().max(0));
+            ExchangeSummary {
+                display_name: exchange::display_name(&a.exchange).to_string(),
+                fee_currency: exchange::get(&a.exchange).and_then(|e| e.fee_currency.clone()),
+                equity: a
+                    .total_asset
+                    .zip(total_debt)
+                    .map(|(asset, debt)| asset - debt),
+                total_asset: a.total_asset,
+                total_debt,
+                bots: a.bots,
+                open_bots: open_bots.get(&a.exchange).copied().unwrap_or(0),
+                errors: a.errors,
+                events: a.events,
+                errors_per_hour: a.errors as f64 / window_hours,
+                error_share_pct: (a.errors + a.events > 0)
+                    .then(|| a.errors as f64 * 100.0 / (a.errors + a.events) as f64),
+                last_update: a.last_update,
+                age_secs,
+                stale: age_secs.is_none_or(|age| age as u64 > stale_after.as_secs()),
+                exchange: a.exchange,
+            }
+        })
+        .collect();
+
+    for info in exchange::all() {
+        if !summaries
+            .iter()
+            .any(|s| s.exchange.eq_ignore_ascii_case(&info.name))
+        {
+            summaries.push(ExchangeSummary::empty(
+                &info.name,
+                &info.display_name,
+                info.fee_currency.clone(),
+            ));
+        }
+    }
+    summaries.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    let errors: i64 = summaries.iter().map(|s| s.errors).sum();
+    let events: i64 = summaries.iter().map(|s| s.events).sum();
+    let totals = ExchangeTotals {
+        exchanges: summaries.len(),
+        equity: summaries.iter().filter_map(|s| s.equity).sum(),
+        bots: summaries.iter().map(|s| s.bots).sum(),
+        open_bots: summaries.iter().map(|s| s.open_bots).sum(),
+        errors,
+        events,
+        errors_per_hour: errors as f64 / window_hours,
+        error_share_pct: (errors + events > 0)
+            .then(|| errors as f64 * 100.0 / (errors + events) as f64),
+        stale: summaries.iter().filter(|s| s.stale).count(),
+        oldest_update: summaries.iter().filter_map(|s| s.last_update).min(),
+    };
+
+    ExchangeOverview {
+        window_secs: window.as_secs(),
+        summaries,
+        totals,
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeSummary {
+    pub exchange: String,
+    pub display_name: String,
+    pub fee_currency: Option<String>,
+    /// Latest position ratio's total asset minus total debt.
+    pub equity: Option<f64>,
+    pub total_asset: Option<f64>,
+    pub total_debt: Option<f64>,
+    pub bots: i64,
+    pub open_bots: usize,
+    /// Within the summary window.
+    pub errors: i64,
+    pub events: i64,
+    pub errors_per_hour: f64,
+    /// Errors as a share of errors and events.
+    pub error_share_pct: Option<f64>,
+    pub last_update: Option<DateTime<Utc>>,
+    pub age_secs: Option<i64>,
+    pub stale: bool,
+}
+
+impl ExchangeSummary {
+    fn empty(exchange: &str, display_name: &str, fee_currency: Option<String>) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            display_name: display_name.to_string(),
+            fee_currency,
+            equity: None,
+            total_asset: None,
+            total_debt: None,
+            bots: 0,
+            open_bots: 0,
+            errors: 0,
+            events: 0,
+            errors_per_hour: 0.0,
+            error_share_pct: None,
+            last_update: None,
+            age_secs: None,
+            stale: true,
+        }
+    }
+}
+
+/// Rollup across all exchanges. Equity is only summed where known, in each
+/// exchange's own valuation currency.
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeTotals {
+    pub exchanges: usize,
+    pub equity: f64,
+    pub bots: i64,
+    pub open_bots: usize,
+    pub errors: i64,
+    pub events: i64,
+    pub errors_per_hour: f64,
+    pub error_share_pct: Option<f64>,
+    pub stale: usize,
+    pub oldest_update: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExchangeOverview {
+    pub window_secs: u64,
+    pub summaries: Vec<ExchangeSummary>,
+    pub totals: ExchangeTotals,
+}
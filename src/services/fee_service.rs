@@ -0,0 +1,241 @@
+use crate::api::models::{ChargedFee, FeeSchedule};
+use crate::core::error::AppResult;
+use crate::repositories::FeeRepository;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const CHARGED_FEES_LIMIT: i64 = 1000;
+/// Implied rates further than this from the expected rate get flagged.
+const RATE_TOLERANCE: f64 = 0.00001;
+
+pub struct FeeService<R: FeeRepository> {
+    repo: R,
+}
+
+impl<R: FeeRepository> FeeService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    pub async fn get_effective_fees(&self) -> AppResult<Vec<EffectiveFee>> {
+        Ok(self
+            .repo
+            .get_fee_schedules()
+            .await?
+            .iter()
+            .map(EffectiveFee::from_schedule)
+            .collect())
+    }
+
+    /// Recent matches with the fee implied by their settlement next to the
+    /// rate the schedule says should apply.
+    pub async fn get_fee_comparisons(
+        &self,
+        fees: &[EffectiveFee],
+    ) -> AppResult<Vec<FeeComparison>> {
+        Ok(self
+            .repo
+            .get_charged_fees(CHARGED_FEES_LIMIT)
+            .await?
+            .into_iter()
+            .filter_map(|charged| {
+                let effective = fees
+                    .iter()
+                    .find(|f| f.exchange == charged.exchange && f.symbol == charged.symbol);
+                FeeComparison::new(charged, effective)
+            })
+            .collect())
+    }
+
+    /// `None` when the symbol is unknown.
+    pub async fn calculate(&self, request: &FeeRequest) -> AppResult<Option<FeeQuote>> {
+        let fees = self.get_effective_fees().await?;
+        let Some(fee) = fees.into_iter().find(|f| {
+            f.exchange.eq_ignore_ascii_case(&request.exchange)
+                && f.symbol.eq_ignore_ascii_case(&request.symbol)
+        }) else {
+            return Ok(None);
+        };
+
+        Ok(Some(FeeQuote::new(fee, request)))
+    }
+}
+
+fn parse(value: Option<&str>) -> Option<f64> {
+    value.and_then(|v| v.parse().ok())
+}
+
+/// Base rate times coefficient. The ticker's coefficient is preferred since
+/// it comes with the rate; the symbol's own is the fallback.
+fn effective_rate(rate: Option<&str>, coefficient: Option<&str>, fallback: &str) -> Option<f64> {
+    let coefficient = parse(coefficient).or_else(|| fallback.parse().ok())?;
+    Some(parse(rate)? * coefficient)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+#[derive(Serialize)]
+pub struct EffectiveFee {
+    pub exchange: String,
+    pub symbol: String,
+    pub fee_category: i16,
+    pub fee_currency: String,
+    /// `None` when the symbol has no ticker row or an unparsable rate.
+    pub maker_rate: Option<f64>,
+    pub taker_rate: Option<f64>,
+}
+
+impl EffectiveFee {
+    fn from_schedule(schedule: &FeeSchedule) -> Self {
+        Self {
+            exchange: schedule.exchange.clone(),
+            symbol: schedule.symbol.clone(),
+            fee_category: schedule.fee_category,
+            fee_currency: schedule.fee_currency.clone(),
+            maker_rate: effective_rate(
+                schedule.maker_fee_rate.as_deref(),
+                schedule.maker_coefficient.as_deref(),
+                &schedule.maker_fee_coefficient,
+            ),
+            taker_rate: effective_rate(
+                schedule.taker_fee_rate.as_deref(),
+                schedule.taker_coefficient.as_deref(),
+                &schedule.taker_fee_coefficient,
+            ),
+        }
+    }
+
+    pub fn rate(&self, liquidity: Liquidity) -> Option<f64> {
+        match liquidity {
+            Liquidity::Maker => self.maker_rate,
+            Liquidity::Taker => self.taker_rate,
+        }
+    }
+}
+
+pub struct FeeComparison {
+    pub charged: ChargedFee,
+    pub notional: f64,
+    pub implied_fee: f64,
+    pub implied_rate: f64,
+    pub expected_rate: Option<f64>,
+    pub mismatch: bool,
+}
+
+impl FeeComparison {
+    /// A buy debits notional plus fee, a sell credits notional minus fee.
+    fn new(charged: ChargedFee, effective: Option<&EffectiveFee>) -> Option<Self> {
+        let size = parse(charged.match_size.as_deref())?;
+        let price = parse(charged.match_price.as_deref())?;
+        let change: f64 = charged.available_change.parse().ok()?;
+        let notional = size * price;
+        if notional == 0.0 {
+            return None;
+        }
+
+        let implied_fee = match charged.side.as_str() {
+            "buy" => -change - notional,
+            "sell" => notional - change,
+            _ => return None,
+        };
+        let implied_rate = implied_fee / notional;
+
+        let liquidity = match charged.liquidity.as_deref() {
+            Some("maker") => Some(Liquidity::Maker),
+            Some("taker") => Some(Liquidity::Taker),
+            _ => None,
+        };
+        let expected_rate = effective.zip(liquidity).and_then(|(f, l)| f.rate(l));
+
+        Some(Self {
+            mismatch: expected_rate.is_some_and(|r| (r - implied_rate).abs() > RATE_TOLERANCE),
+            charged,
+            notional,
+            implied_fee,
+            implied_rate,
+            expected_rate,
+        })
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeeRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+    pub price: f64,
+    /// Defaults to taker for both legs.
+    pub entry: Option<Liquidity>,
+    pub exit: Option<Liquidity>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FeeQuote {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    pub size: f64,
+    pub price: f64,
+    pub notional: f64,
+    pub fee_currency: String,
+    pub maker_rate: Option<f64>,
+    pub taker_rate: Option<f64>,
+    pub entry: Liquidity,
+    pub exit: Liquidity,
+    /// `None` when the needed rate is unknown.
+    pub entry_fee: Option<f64>,
+    /// Fee of closing at the break-even price.
+    pub exit_fee: Option<f64>,
+    /// Price at which closing the position nets zero after both fees.
+    pub break_even_price: Option<f64>,
+    pub break_even_move_pct: Option<f64>,
+}
+
+impl FeeQuote {
+    fn new(fee: EffectiveFee, request: &FeeRequest) -> Self {
+        let entry = request.entry.unwrap_or(Liquidity::Taker);
+        let exit = request.exit.unwrap_or(Liquidity::Taker);
+        let notional = request.size * request.price;
+
+        let rates = fee.rate(entry).zip(fee.rate(exit));
+        // Long: exit * (1 - out) = entry * (1 + in); short mirrors it.
+        let break_even_price = rates.map(|(rate_in, rate_out)| match request.side {
+            Side::Buy => request.price * (1.0 + rate_in) / (1.0 - rate_out),
+            Side::Sell => request.price * (1.0 - rate_in) / (1.0 + rate_out),
+        });
+
+        Self {
+            exchange: fee.exchange,
+            symbol: fee.symbol,
+            side: request.side,
+            size: request.size,
+            price: request.price,
+            notional,
+            fee_currency: fee.fee_currency,
+            maker_rate: fee.maker_rate,
+            taker_rate: fee.taker_rate,
+            entry,
+            exit,
+            entry_fee: rates.map(|(rate_in, _)| notional * rate_in),
+            break_even_move_pct: break_even_price.map(|p| (p / request.price - 1.0) * 100.0),
+            exit_fee: break_even_price
+                .zip(rates)
+                .map(|(p, (_, rate_out))| p * request.size * rate_out),
+            break_even_price,
+        }
+    }
+}
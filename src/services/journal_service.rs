@@ -0,0 +1,205 @@
+use crate::api::models::{EventOrder, JournalEntry, NewJournalEntry};
+use crate::core::error::AppResult;
+use crate::repositories::{JournalFilter, JournalKeys, JournalRepository};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const JOURNAL_LIMIT: i64 = 1000;
+const MAX_NOTE_LEN: usize = 10_000;
+
+pub struct JournalService<R: JournalRepository> {
+    repo: R,
+}
+
+impl<R: JournalRepository> JournalService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    pub async fn get_entries(&self, query: &JournalQuery) -> AppResult<Vec<JournalEntry>> {
+        let filter = JournalFilter {
+            tag: query
+                .tag
+                .as_deref()
+                .and_then(non_empty)
+                .map(str::to_lowercase),
+            symbol: query
+                .symbol
+                .as_deref()
+                .and_then(non_empty)
+                .map(str::to_string),
+            order_id: query
+                .order_id
+                .as_deref()
+                .and_then(non_empty)
+                .map(str::to_string),
+            text: query.q.as_deref().and_then(non_empty).map(str::to_string),
+            limit: JOURNAL_LIMIT,
+        };
+        self.repo.get_entries(&filter).await.map_err(Into::into)
+    }
+
+    /// The entries on `keys`, for annotating the bots and order pages.
+    pub async fn get_index(&self, keys: &JournalKeys) -> AppResult<JournalIndex> {
+        let entries = self.repo.get_entries_for(keys).await?;
+        Ok(JournalIndex { entries })
+    }
+
+    pub async fn create_entry(&self, entry: &NewJournalEntry) -> AppResult<i64> {
+        self.repo.create_entry(entry).await.map_err(Into::into)
+    }
+
+    pub async fn delete_entry(&self, id: i64) -> AppResult<bool> {
+        self.repo.delete_entry(id).await.map_err(Into::into)
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct JournalQuery {
+    pub tag: Option<String>,
+    pub symbol: Option<String>,
+    pub order_id: Option<String>,
+    /// Text searched for in notes.
+    pub q: Option<String>,
+}
+
+/// The add-entry form. Exactly one of bot symbol, order id or range must be
+/// filled in; range bounds are `datetime-local` values taken as UTC.
+#[derive(Deserialize)]
+pub struct JournalForm {
+    #[serde(default)]
+    pub bot_symbol: String,
+    #[serde(default)]
+    pub order_id: String,
+    #[serde(default)]
+    pub range_start: String,
+    #[serde(default)]
+    pub range_end: String,
+    pub note: String,
+    /// Comma separated.
+    #[serde(default)]
+    pub tags: String,
+}
+
+impl JournalForm {
+    pub fn into_entry(self) -> Result<NewJournalEntry, String> {
+        let note = self.note.trim().to_string();
+        if note.is_empty() {
+            return Err("note must not be empty".to_string());
+        }
+        if note.len() > MAX_NOTE_LEN {
+            return Err(format!("note is longer than {} bytes", MAX_NOTE_LEN));
+        }
+
+        let range = match (non_empty(&self.range_start), non_empty(&self.range_end)) {
+            (None, None) => None,
+            (Some(start), Some(end)) => {
+                let (start, end) = (parse_time(start)?, parse_time(end)?);
+                if end < start {
+                    return Err("range_end must not be before range_start".to_string());
+                }
+                Some((start, end))
+            }
+            _ => return Err("a range needs both range_start and range_end".to_string()),
+        };
+        let bot_symbol = non_empty(&self.bot_symbol).map(str::to_uppercase);
+        let order_id = non_empty(&self.order_id).map(str::to_string);
+
+        let targets = [bot_symbol.is_some(), order_id.is_some(), range.is_some()];
+        if targets.iter().filter(|t| **t).count() != 1 {
+            return Err("attach the note to exactly one of a bot, an order or a range".to_string());
+        }
+
+        let mut tags: Vec<String> = self
+            .tags
+            .split(',')
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        Ok(NewJournalEntry {
+            bot_symbol,
+            order_id,
+            range,
+            note,
+            tags,
+        })
+    }
+}
+
+/// Accepts RFC 3339 and the browser's `YYYY-MM-DDTHH:MM[:SS]` as UTC.
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").map(|t| t.and_utc()))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").map(|t| t.and_utc()))
+        .map_err(|_| format!("invalid time {}", value))
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|v| !v.is_empty())
+}
+
+/// An order event with the journal notes that apply to it.
+#[derive(Serialize)]
+pub struct AnnotatedOrder {
+    #[serde(flatten)]
+    pub order: EventOrder,
+    /// Notes and their tags, `; ` separated.
+    pub notes: Option<String>,
+}
+
+/// Journal entries matched to the rows of other pages.
+pub struct JournalIndex {
+    entries: Vec<JournalEntry>,
+}
+
+impl JournalIndex {
+    /// Notes on the bot's symbol or on a range covering its last update.
+    pub fn for_bot(&self, symbol: &Option<String>, at: &DateTime<Utc>) -> Vec<&JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|e| {
+                matches!((&e.bot_symbol, symbol), (Some(a), Some(b)) if a.eq_ignore_ascii_case(b))
+                    || in_range(e, at)
+            })
+            .collect()
+    }
+
+    /// Notes on the order or on a range covering the event.
+    pub fn for_order(&self, order_id: &str, at: &DateTime<Utc>) -> Vec<&JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.order_id.as_deref() == Some(order_id) || in_range(e, at))
+            .collect()
+    }
+
+    pub fn annotate_orders(&self, orders: Vec<EventOrder>) -> Vec<AnnotatedOrder> {
+        orders
+            .into_iter()
+            .map(|order| {
+                let notes: Vec<String> = self
+                    .for_order(&order.order_id, &order.updated_at)
+                    .into_iter()
+                    .map(|e| {
+                        e.tags.iter().fold(e.note.clone(), |mut note, tag| {
+                            note.push_str(" #");
+                            note.push_str(tag);
+                            note
+                        })
+                    })
+                    .collect();
+                AnnotatedOrder {
+                    order,
+                    notes: (!notes.is_empty()).then(|| notes.join("; ")),
+                }
+            })
+            .collect()
+    }
+}
+
+fn in_range(entry: &JournalEntry, at: &DateTime<Utc>) -> bool {
+    matches!((entry.range_start, entry.range_end), (Some(start), Some(end)) if start <= *at && *at <= end)
+}
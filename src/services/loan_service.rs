@@ -0,0 +1,170 @@
+use crate::api::models::{LoanEvent, PositionDebt};
+use crate::config::LoanConfig;
+use crate::core::error::AppResult;
+use crate::repositories::LoanRepository;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+const LEDGER_LIMIT: usize = 1000;
+/// Relative difference to the `positiondebt` snapshot still counted as
+/// reconciled, since the exchange rounds accrued interest.
+const RECONCILE_TOLERANCE: f64 = 0.01;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+pub struct LoanService<R: LoanRepository> {
+    repo: R,
+    config: LoanConfig,
+}
+
+impl<R: LoanRepository> LoanService<R> {
+    pub fn new(repo: R, config: LoanConfig) -> Self {
+        Self { repo, config }
+    }
+
+    pub async fn get_ledger(&self) -> AppResult<LoanLedger> {
+        let events = self.repo.get_loan_events().await?;
+        let debts = self.repo.get_latest_position_debts().await?;
+
+        Ok(build_ledger(events, &debts, &self.config, Utc::now()))
+    }
+}
+
+/// Replays the events in order per exchange and currency. A repay only
+/// counts up to the principal then outstanding, and interest accrues on the
+/// outstanding principal between events and up to `now`.
+fn build_ledger(
+    events: Vec<LoanEvent>,
+    debts: &[PositionDebt],
+    config: &LoanConfig,
+    now: DateTime<Utc>,
+) -> LoanLedger {
+    let mut balances: BTreeMap<(String, String), LoanBalance> = BTreeMap::new();
+    let mut entries = Vec::new();
+
+    for event in events {
+        let Some(amount) = event_amount(&event) else {
+            continue;
+        };
+        let balance = balances
+            .entry((event.exchange.clone(), event.currency.clone()))
+            .or_insert_with(|| {
+                LoanBalance::new(&event.exchange, &event.currency, event.updated_at)
+            });
+        balance.accrue(config.daily_rate(&event.currency), event.updated_at);
+
+        let applied = if event.kind == "borrow" {
+            balance.borrowed += amount;
+            balance.outstanding += amount;
+            amount
+        } else {
+            let applied = amount.min(balance.outstanding);
+            balance.repaid += applied;
+            balance.outstanding -= applied;
+            applied
+        };
+        if applied <= 0.0 {
+            continue;
+        }
+
+        entries.push(LedgerEntry {
+            amount: applied,
+            outstanding: balance.outstanding,
+            event,
+        });
+    }
+
+    for balance in balances.values_mut() {
+        balance.accrue(config.daily_rate(&balance.currency), now);
+    }
+    for debt in debts {
+        let balance = balances
+            .entry((debt.exchange.clone(), debt.debt_symbol.clone()))
+            .or_insert_with(|| LoanBalance::new(&debt.exchange, &debt.debt_symbol, now));
+        balance.snapshot = debt.debt_value.parse().ok();
+        balance.snapshot_at = Some(debt.updated_at);
+    }
+
+    entries.reverse();
+    entries.truncate(LEDGER_LIMIT);
+
+    LoanLedger {
+        balances: balances.into_values().collect(),
+        entries,
+    }
+}
+
+/// Amount in the loan currency; `None` if the logged sizes do not parse.
+fn event_amount(event: &LoanEvent) -> Option<f64> {
+    let size: f64 = event.size.as_deref()?.parse().ok()?;
+    match event.price.as_deref() {
+        Some(price) => Some(size * price.parse::<f64>().ok()?),
+        None => Some(size),
+    }
+}
+
+pub struct LedgerEntry {
+    pub event: LoanEvent,
+    /// Principal borrowed or repaid by the event.
+    pub amount: f64,
+    /// Principal outstanding after the event.
+    pub outstanding: f64,
+}
+
+pub struct LoanBalance {
+    pub exchange: String,
+    pub currency: String,
+    pub borrowed: f64,
+    pub repaid: f64,
+    pub outstanding: f64,
+    /// Estimated from the configured daily rate.
+    pub interest: f64,
+    /// Latest `positiondebt` value for the currency.
+    pub snapshot: Option<f64>,
+    pub snapshot_at: Option<DateTime<Utc>>,
+    accrued_to: DateTime<Utc>,
+}
+
+impl LoanBalance {
+    fn new(exchange: &str, currency: &str, at: DateTime<Utc>) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            currency: currency.to_string(),
+            borrowed: 0.0,
+            repaid: 0.0,
+            outstanding: 0.0,
+            interest: 0.0,
+            snapshot: None,
+            snapshot_at: None,
+            accrued_to: at,
+        }
+    }
+
+    fn accrue(&mut self, daily_rate: f64, to: DateTime<Utc>) {
+        let days = (to - self.accrued_to).num_milliseconds() as f64 / 1000.0 / SECONDS_PER_DAY;
+        if days > 0.0 {
+            self.interest += self.outstanding * daily_rate * days;
+            self.accrued_to = to;
+        }
+    }
+
+    /// Snapshot minus reconstructed principal and interest.
+    pub fn difference(&self) -> Option<f64> {
+        self.snapshot
+            .map(|snapshot| snapshot - (self.outstanding + self.interest))
+    }
+
+    pub fn reconciled(&self) -> bool {
+        match (self.snapshot, self.difference()) {
+            (Some(snapshot), Some(difference)) => {
+                difference.abs() <= (snapshot.abs() * RECONCILE_TOLERANCE).max(1e-8)
+            }
+            _ => self.outstanding == 0.0,
+        }
+    }
+}
+
+pub struct LoanLedger {
+    pub balances: Vec<LoanBalance>,
+    /// Newest first.
+    pub entries: Vec<LedgerEntry>,
+}
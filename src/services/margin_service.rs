@@ -0,0 +1,244 @@
+use crate::api::models::{PositionAsset, PositionDebt, PositionRatio};
+use crate::config::MarginConfig;
+use crate::core::error::AppResult;
+use crate::repositories::MarginRepository;
+use std::collections::{BTreeMap, HashMap};
+
+pub struct MarginService<R: MarginRepository> {
+    repo: R,
+    config: MarginConfig,
+}
+
+impl<R: MarginRepository> MarginService<R> {
+    pub fn new(repo: R, config: MarginConfig) -> Self {
+        Self { repo, config }
+    }
+
+    pub fn config(&self) -> &MarginConfig {
+        &self.config
+    }
+
+    /// Current risk of every exchange with a position ratio, stressed by
+    /// `shock_pct` percent in both directions.
+    pub async fn get_margin_risk(&self, shock_pct: f64) -> AppResult<Vec<ExchangeRisk>> {
+        let ratios = self.repo.get_latest_position_ratios().await?;
+        let debts = self.repo.get_latest_position_debts().await?;
+        let assets = self.repo.get_latest_position_assets().await?;
+        let prices: HashMap<(String, String), f64> = self
+            .repo
+            .get_last_prices()
+            .await?
+            .into_iter()
+            .filter_map(|p| Some(((p.exchange, p.symbol), p.price.parse().ok()?)))
+            .collect();
+
+        Ok(ratios
+            .into_iter()
+            .map(|ratio| {
+                let debts: Vec<&PositionDebt> = debts
+                    .iter()
+                    .filter(|d| d.exchange == ratio.exchange)
+                    .collect();
+                let assets: Vec<&PositionAsset> = assets
+                    .iter()
+                    .filter(|a| a.exchange == ratio.exchange)
+                    .collect();
+                self.assess(ratio, &debts, &assets, &prices, shock_pct / 100.0)
+            })
+            .collect())
+    }
+
+    /// Uses the exchange's own totals and splits them into a part that moves
+    /// with prices (holdings and debts in currencies other than the quote
+    /// currency, valued at the last match price) and a part that does not.
+    /// Under a uniform price move `s` the ratio becomes
+    /// `(debt + moving_debt * s) / (assets + moving_assets * s)`.
+    fn assess(
+        &self,
+        ratio: PositionRatio,
+        debts: &[&PositionDebt],
+        assets: &[&PositionAsset],
+        prices: &HashMap<(String, String), f64>,
+        shock: f64,
+    ) -> ExchangeRisk {
+        let quote = self.config.quote_currency.as_str();
+        let price = |currency: &str| {
+            if currency == quote {
+                Some(1.0)
+            } else {
+                prices
+                    .get(&(ratio.exchange.clone(), format!("{}-{}", currency, quote)))
+                    .copied()
+            }
+        };
+
+        let amounts = debts
+            .iter()
+            .map(|d| (&d.debt_symbol, &d.debt_value, true))
+            .chain(
+                assets
+                    .iter()
+                    .map(|a| (&a.asset_symbol, &a.asset_total, false)),
+            );
+        let mut exposures: BTreeMap<&str, CurrencyExposure> = BTreeMap::new();
+        for (currency, amount, is_debt) in amounts {
+            let amount = amount.parse::<f64>().unwrap_or(0.0);
+            let exposure = exposures
+                .entry(currency)
+                .or_insert_with(|| CurrencyExposure {
+                    currency: currency.clone(),
+                    debt: 0.0,
+                    asset: 0.0,
+                    price: price(currency),
+                });
+            if is_debt {
+                exposure.debt += amount;
+            } else {
+                exposure.asset += amount;
+            }
+        }
+        let exposures: Vec<CurrencyExposure> = exposures.into_values().collect();
+
+        let total_asset = ratio.total_asset;
+        let total_debt = ratio
+            .total_debt
+            .parse()
+            .unwrap_or(ratio.debt_ratio * ratio.total_asset);
+        let moving = |amount: fn(&CurrencyExposure) -> f64| {
+            exposures
+                .iter()
+                .filter(|e| e.currency != quote)
+                .filter_map(|e| e.price.map(|p| amount(e) * p))
+                .sum::<f64>()
+        };
+        let book = Book {
+            assets: total_asset,
+            debt: total_debt,
+            moving_assets: moving(|e| e.asset).min(total_asset),
+            moving_debt: moving(|e| e.debt).min(total_debt),
+        };
+
+        let stress = [-shock, 0.0, shock]
+            .into_iter()
+            .map(|s| {
+                let debt_ratio = book.ratio_after(s);
+                StressPoint {
+                    shock_pct: s * 100.0,
+                    debt_ratio,
+                    status: self.status(debt_ratio),
+                }
+            })
+            .collect();
+
+        ExchangeRisk {
+            status: self.status(Some(ratio.debt_ratio)),
+            move_to_margin_call_pct: book
+                .move_to(ratio.debt_ratio, self.config.margin_call_ratio)
+                .map(|s| s * 100.0),
+            move_to_liquidation_pct: book
+                .move_to(ratio.debt_ratio, self.config.liquidation_ratio)
+                .map(|s| s * 100.0),
+            unpriced: exposures
+                .iter()
+                .filter(|e| e.price.is_none())
+                .map(|e| e.currency.clone())
+                .collect(),
+            ratio,
+            exposures,
+            stress,
+        }
+    }
+
+    /// `None` stands for a wiped-out book, which is past liquidation.
+    fn status(&self, debt_ratio: Option<f64>) -> RiskStatus {
+        match debt_ratio {
+            Some(r) if r < self.config.margin_call_ratio => RiskStatus::Safe,
+            Some(r) if r < self.config.liquidation_ratio => RiskStatus::MarginCall,
+            _ => RiskStatus::Liquidation,
+        }
+    }
+}
+
+struct Book {
+    assets: f64,
+    debt: f64,
+    moving_assets: f64,
+    moving_debt: f64,
+}
+
+impl Book {
+    fn ratio_after(&self, shock: f64) -> Option<f64> {
+        let assets = self.assets + self.moving_assets * shock;
+        (assets > 0.0).then(|| (self.debt + self.moving_debt * shock) / assets)
+    }
+
+    /// Uniform price move, as a fraction, that takes the ratio to
+    /// `threshold`; zero once it is reached and `None` when no move can.
+    fn move_to(&self, current: f64, threshold: f64) -> Option<f64> {
+        if current >= threshold {
+            return Some(0.0);
+        }
+        let sensitivity = self.moving_debt - threshold * self.moving_assets;
+        if sensitivity == 0.0 {
+            return None;
+        }
+        let shock = (threshold * self.assets - self.debt) / sensitivity;
+        (shock > -1.0).then_some(shock)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RiskStatus {
+    Safe,
+    MarginCall,
+    Liquidation,
+}
+
+impl RiskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskStatus::Safe => "safe",
+            RiskStatus::MarginCall => "margin call",
+            RiskStatus::Liquidation => "liquidation",
+        }
+    }
+}
+
+/// A currency's debt next to the holding that covers it.
+pub struct CurrencyExposure {
+    pub currency: String,
+    pub debt: f64,
+    pub asset: f64,
+    /// In the quote currency; `None` when the bot never traded the pair.
+    pub price: Option<f64>,
+}
+
+impl CurrencyExposure {
+    /// Share of the debt the same-currency holding could repay.
+    pub fn coverage(&self) -> Option<f64> {
+        (self.debt > 0.0).then(|| self.asset / self.debt)
+    }
+
+    pub fn net_value(&self) -> Option<f64> {
+        self.price.map(|p| (self.asset - self.debt) * p)
+    }
+}
+
+pub struct StressPoint {
+    pub shock_pct: f64,
+    pub debt_ratio: Option<f64>,
+    pub status: RiskStatus,
+}
+
+pub struct ExchangeRisk {
+    pub ratio: PositionRatio,
+    pub status: RiskStatus,
+    pub exposures: Vec<CurrencyExposure>,
+    /// Signed percent price move of every non-quote currency that brings
+    /// the debt ratio to the threshold.
+    pub move_to_margin_call_pct: Option<f64>,
+    pub move_to_liquidation_pct: Option<f64>,
+    pub stress: Vec<StressPoint>,
+    /// Currencies without a price, left out of the stress test.
+    pub unpriced: Vec<String>,
+}
@@ -3,25 +3,45 @@ pub mod bot_service;
 pub mod currency_service;
 pub mod error_service;
 pub mod event_service;
+pub mod exchange_service;
+pub mod fee_service;
+pub mod journal_service;
+pub mod loan_service;
+pub mod margin_service;
 pub mod msgevent_service;
 pub mod msgsend_service;
 pub mod order_service;
+pub mod order_validation_service;
 pub mod pg_service;
 pub mod position_service;
+pub mod report_service;
 pub mod static_service;
+pub mod stream_service;
+pub mod symbol_change_service;
 pub mod symbol_service;
 pub mod ticker_service;
+pub mod view_service;
 
 pub use balance_service::BalanceService;
 pub use bot_service::BotService;
 pub use currency_service::CurrencyService;
 pub use error_service::ErrorService;
 pub use event_service::EventService;
+pub use exchange_service::ExchangeService;
+pub use fee_service::FeeService;
+pub use journal_service::JournalService;
+pub use loan_service::LoanService;
+pub use margin_service::MarginService;
 pub use msgevent_service::MsgEventService;
 pub use msgsend_service::MsgSendService;
 pub use order_service::OrderService;
+pub use order_validation_service::OrderValidationService;
 pub use pg_service::PgService;
 pub use position_service::PositionService;
+pub use report_service::ReportService;
 pub use static_service::StaticService;
+pub use stream_service::StreamService;
+pub use symbol_change_service::SymbolChangeService;
 pub use symbol_service::SymbolService;
 pub use ticker_service::TickerService;
+pub use view_service::ViewService;
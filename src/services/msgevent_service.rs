@@ -1,6 +1,6 @@
 use crate::api::models::MsgEvent;
 use crate::core::error::AppResult;
-use crate::repositories::MsgEventRepository;
+use crate::repositories::{LinkFilter, MsgEventRepository, TableFilter};
 
 pub struct MsgEventService<R: MsgEventRepository> {
     repo: R,
@@ -11,7 +11,31 @@ impl<R: MsgEventRepository> MsgEventService<R> {
         Self { repo }
     }
 
-    pub async fn get_msgevents(&self) -> AppResult<Vec<MsgEvent>> {
-        self.repo.get_msgevents().await.map_err(Into::into)
+    pub async fn get_msgevents(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<MsgEvent>> {
+        self.repo
+            .get_msgevents(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn search_msgevents(&self, term: &str, limit: i64) -> AppResult<Vec<MsgEvent>> {
+        self.repo
+            .search_msgevents(term, limit)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_linked_msgevents(&self, filter: &LinkFilter) -> AppResult<Vec<MsgEvent>> {
+        if !filter.has_keys() {
+            return Ok(Vec::new());
+        }
+        self.repo
+            .get_linked_msgevents(filter)
+            .await
+            .map_err(Into::into)
     }
 }
@@ -1,6 +1,6 @@
 use crate::api::models::MsgSend;
 use crate::core::error::AppResult;
-use crate::repositories::MsgSendRepository;
+use crate::repositories::{LinkFilter, MsgSendRepository, TableFilter};
 
 pub struct MsgSendService<R: MsgSendRepository> {
     repo: R,
@@ -11,7 +11,31 @@ impl<R: MsgSendRepository> MsgSendService<R> {
         Self { repo }
     }
 
-    pub async fn get_msgsends(&self) -> AppResult<Vec<MsgSend>> {
-        self.repo.get_msgsends().await.map_err(Into::into)
+    pub async fn get_msgsends(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<MsgSend>> {
+        self.repo
+            .get_msgsends(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn search_msgsends(&self, term: &str, limit: i64) -> AppResult<Vec<MsgSend>> {
+        self.repo
+            .search_msgsends(term, limit)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_linked_msgsends(&self, filter: &LinkFilter) -> AppResult<Vec<MsgSend>> {
+        if !filter.has_keys() {
+            return Ok(Vec::new());
+        }
+        self.repo
+            .get_linked_msgsends(filter)
+            .await
+            .map_err(Into::into)
     }
 }
@@ -1,6 +1,6 @@
 use crate::api::models::EventOrder;
 use crate::core::error::AppResult;
-use crate::repositories::EventOrderRepository;
+use crate::repositories::{EventOrderRepository, LinkFilter, TableFilter};
 
 pub struct OrderService<R: EventOrderRepository> {
     repo: R,
@@ -11,7 +11,31 @@ impl<R: EventOrderRepository> OrderService<R> {
         Self { repo }
     }
 
-    pub async fn get_event_orders(&self) -> AppResult<Vec<EventOrder>> {
-        self.repo.get_event_orders().await.map_err(Into::into)
+    pub async fn get_event_orders(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<EventOrder>> {
+        self.repo
+            .get_event_orders(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn search_event_orders(&self, term: &str, limit: i64) -> AppResult<Vec<EventOrder>> {
+        self.repo
+            .search_event_orders(term, limit)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_linked_event_orders(&self, filter: &LinkFilter) -> AppResult<Vec<EventOrder>> {
+        if !filter.has_keys() {
+            return Ok(Vec::new());
+        }
+        self.repo
+            .get_linked_event_orders(filter)
+            .await
+            .map_err(Into::into)
     }
 }
@@ -0,0 +1,437 @@
+use crate::api::models::{MsgSendOutcome, Symbol};
+use crate::core::error::AppResult;
+use crate::repositories::{MsgSendRepository, SymbolRepository};
+use crate::services::fee_service::Side;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+
+const RETROSPECTIVE_LIMIT: i64 = 1000;
+/// `msgevent.code` of an accepted order.
+const SUCCESS_CODE: &str = "200000";
+
+pub struct OrderValidationService<S: SymbolRepository, M: MsgSendRepository> {
+    symbols: S,
+    msgsend: M,
+}
+
+impl<S: SymbolRepository, M: MsgSendRepository> OrderValidationService<S, M> {
+    pub fn new(symbols: S, msgsend: M) -> Self {
+        Self { symbols, msgsend }
+    }
+
+    /// `None` when the symbol is not in the catalog.
+    pub async fn validate(&self, order: &OrderRequest) -> AppResult<Option<OrderValidation>> {
+        let symbols = self.symbols.get_all_symbols().await?;
+
+        Ok(symbols
+            .iter()
+            .find(|s| {
+                s.exchange.eq_ignore_ascii_case(&order.exchange)
+                    && s.symbol.eq_ignore_ascii_case(&order.symbol)
+            })
+            .map(|symbol| validate_order(symbol, order)))
+    }
+
+    /// Replays recent `msgsend` rows through the validator against the
+    /// current catalog and lines them up with the exchange's response. The
+    /// last match price before each row is its `price_limit_rate` reference.
+    pub async fn get_retrospective(&self) -> AppResult<ValidationRetrospective> {
+        let symbols = self.symbols.get_all_symbols().await?;
+        let outcomes = self
+            .msgsend
+            .get_msgsend_outcomes(RETROSPECTIVE_LIMIT)
+            .await?;
+
+        let rows: Vec<RetrospectiveRow> = outcomes
+            .into_iter()
+            .map(|outcome| {
+                let violations = match replay(&outcome, &symbols) {
+                    Ok(validation) => validation
+                        .violations
+                        .into_iter()
+                        .map(|v| v.message)
+                        .collect(),
+                    Err(reason) => vec![reason],
+                };
+                let rejected = outcome
+                    .response_code
+                    .as_deref()
+                    .is_some_and(|code| code != SUCCESS_CODE);
+
+                RetrospectiveRow {
+                    outcome,
+                    violations,
+                    rejected,
+                }
+            })
+            .collect();
+
+        let count = |f: fn(&RetrospectiveRow) -> bool| rows.iter().filter(|r| f(r)).count();
+        Ok(ValidationRetrospective {
+            flagged: count(|r| !r.violations.is_empty()),
+            rejected: count(|r| r.rejected),
+            flagged_and_rejected: count(|r| r.rejected && !r.violations.is_empty()),
+            rows,
+        })
+    }
+}
+
+/// Rebuilds the order from the logged arguments; `Err` says why it could
+/// not be checked.
+fn replay(outcome: &MsgSendOutcome, symbols: &[Symbol]) -> Result<OrderValidation, String> {
+    let symbol_name = outcome.args_symbol.as_deref().ok_or("no symbol")?;
+    let symbol = symbols
+        .iter()
+        .find(|s| s.exchange == outcome.exchange && s.symbol == symbol_name)
+        .ok_or_else(|| {
+            format!(
+                "{} is not in the {} symbol catalog",
+                symbol_name, outcome.exchange
+            )
+        })?;
+
+    let side = match outcome.args_side.as_deref() {
+        Some("buy") => Side::Buy,
+        Some("sell") => Side::Sell,
+        other => return Err(format!("unknown side {:?}", other)),
+    };
+    let decimal = |field: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| Decimal::from_str(v).map_err(|_| format!("{} {} is not a number", field, v)))
+            .transpose()
+    };
+
+    // Market orders carry no price even if the bot logged one.
+    let is_market = outcome.args_type.as_deref() == Some("market");
+    let order = OrderRequest {
+        exchange: outcome.exchange.clone(),
+        symbol: symbol_name.to_string(),
+        side,
+        price: if is_market {
+            None
+        } else {
+            decimal("price", &outcome.args_price)?
+        },
+        size: decimal("size", &outcome.args_size)?,
+        funds: decimal("funds", &outcome.args_funds)?,
+        reference_price: outcome
+            .reference_price
+            .as_deref()
+            .and_then(|p| Decimal::from_str(p).ok()),
+    };
+
+    Ok(validate_order(symbol, &order))
+}
+
+/// Checks `order` against the symbol's trading rules. Rules whose column
+/// does not parse are skipped. Suggested values are rounded to the nearest
+/// increment and clamped into the allowed range. Decimal operators panic on
+/// overflow, so all arithmetic is checked; a value too large to check is a
+/// violation itself.
+fn validate_order(symbol: &Symbol, order: &OrderRequest) -> OrderValidation {
+    let rule = |value: &str| Decimal::from_str(value).ok().filter(|d| *d > Decimal::ZERO);
+    let base_min = rule(&symbol.base_min_size);
+    let base_max = rule(&symbol.base_max_size);
+    let base_increment = rule(&symbol.base_increment);
+    let quote_min = rule(&symbol.quote_min_size);
+    let quote_max = rule(&symbol.quote_max_size);
+    let quote_increment = rule(&symbol.quote_increment);
+    let price_increment = rule(&symbol.price_increment);
+    let price_limit_rate = rule(&symbol.price_limit_rate);
+    let min_funds = symbol.min_funds.as_deref().and_then(rule);
+
+    let mut violations = Vec::new();
+    let mut suggestion = OrderSuggestion {
+        price: order.price,
+        size: order.size,
+        funds: order.funds,
+    };
+
+    match (order.price, order.size, order.funds) {
+        (Some(_), None, _) => violations.push(Violation::new("size", "limit orders need a size")),
+        (Some(_), _, Some(_)) => {
+            violations.push(Violation::new("funds", "funds only apply to market orders"))
+        }
+        (None, None, None) | (None, Some(_), Some(_)) => violations.push(Violation::new(
+            "size",
+            "market orders need exactly one of size or funds",
+        )),
+        _ => {}
+    }
+
+    if let Some(price) = order.price {
+        if price <= Decimal::ZERO {
+            violations.push(Violation::new("price", "price must be positive"));
+        } else {
+            if let Some(increment) = price_increment {
+                match round_to(price, increment) {
+                    Some(nearest) if nearest == price => {}
+                    Some(nearest) => {
+                        violations.push(Violation::new(
+                            "price",
+                            format!(
+                                "price {} is not a multiple of price_increment {}",
+                                price, increment
+                            ),
+                        ));
+                        suggestion.price = Some(nearest.max(increment));
+                    }
+                    None => violations.push(too_large("price", price)),
+                }
+            }
+
+            if let Some((reference, rate)) = order.reference_price.zip(price_limit_rate) {
+                let limit = match order.side {
+                    Side::Buy => Decimal::ONE.checked_add(rate),
+                    Side::Sell => Decimal::ONE.checked_sub(rate),
+                }
+                .and_then(|factor| reference.checked_mul(factor));
+                let outside = |limit: Decimal| match order.side {
+                    Side::Buy => price > limit,
+                    Side::Sell => price < limit,
+                };
+                match limit {
+                    Some(limit) if outside(limit) => {
+                        violations.push(Violation::new(
+                            "price",
+                            format!(
+                                "price {} is beyond the {} limit {} (price_limit_rate {} of {})",
+                                price,
+                                side_name(order.side),
+                                limit.normalize(),
+                                rate,
+                                reference
+                            ),
+                        ));
+                        let limit = price_increment
+                            .and_then(|increment| match order.side {
+                                Side::Buy => floor_to(limit, increment),
+                                Side::Sell => ceil_to(limit, increment),
+                            })
+                            .unwrap_or(limit);
+                        suggestion.price = Some(limit.normalize());
+                    }
+                    Some(_) => {}
+                    None => violations.push(too_large("reference_price", reference)),
+                }
+            }
+        }
+    }
+
+    if let Some(size) = order.size {
+        let checked = check_amount(
+            "size",
+            size,
+            ("base_min_size", base_min),
+            ("base_max_size", base_max),
+            ("base_increment", base_increment),
+            &mut violations,
+        );
+        suggestion.size = Some(checked);
+    }
+
+    if let Some(funds) = order.funds {
+        let checked = check_amount(
+            "funds",
+            funds,
+            ("quote_min_size", quote_min),
+            ("quote_max_size", quote_max),
+            ("quote_increment", quote_increment),
+            &mut violations,
+        );
+        suggestion.funds = Some(checked);
+    }
+
+    if let Some(min_funds) = min_funds {
+        // An order value too large to compute is not below min_funds.
+        let notional = match (order.price, order.size) {
+            (Some(price), Some(size)) => price.checked_mul(size),
+            _ => order.funds,
+        };
+        if let Some(notional) = notional.filter(|n| *n > Decimal::ZERO && *n < min_funds) {
+            violations.push(Violation::new(
+                "funds",
+                format!(
+                    "order value {} is below min_funds {}",
+                    notional.normalize(),
+                    min_funds
+                ),
+            ));
+            match (suggestion.price, suggestion.size) {
+                (Some(price), Some(_)) if price > Decimal::ZERO => {
+                    if let Some(size) = min_funds.checked_div(price) {
+                        let size = base_increment
+                            .and_then(|inc| ceil_to(size, inc))
+                            .unwrap_or(size);
+                        suggestion.size = Some(size.normalize());
+                    }
+                }
+                _ => {
+                    let funds = quote_increment
+                        .and_then(|inc| ceil_to(min_funds, inc))
+                        .unwrap_or(min_funds);
+                    suggestion.funds = Some(funds.normalize());
+                }
+            }
+        }
+    }
+
+    OrderValidation {
+        symbol: symbol.symbol.clone(),
+        valid: violations.is_empty(),
+        violations,
+        suggestion,
+    }
+}
+
+/// Min, max and increment checks shared by size and funds; returns the
+/// nearest valid amount.
+fn check_amount(
+    field: &'static str,
+    value: Decimal,
+    (min_name, min): (&str, Option<Decimal>),
+    (max_name, max): (&str, Option<Decimal>),
+    (increment_name, increment): (&str, Option<Decimal>),
+    violations: &mut Vec<Violation>,
+) -> Decimal {
+    let mut nearest = value;
+
+    if let Some(increment) = increment {
+        match round_to(value, increment) {
+            Some(rounded) if rounded == value => {}
+            Some(rounded) => {
+                violations.push(Violation::new(
+                    field,
+                    format!(
+                        "{} {} is not a multiple of {} {}",
+                        field, value, increment_name, increment
+                    ),
+                ));
+                nearest = rounded;
+            }
+            None => violations.push(too_large(field, value)),
+        }
+    }
+    if let Some(min) = min
+        && value < min
+    {
+        violations.push(Violation::new(
+            field,
+            format!("{} {} is below {} {}", field, value, min_name, min),
+        ));
+        nearest = increment.and_then(|inc| ceil_to(min, inc)).unwrap_or(min);
+    }
+    if let Some(max) = max
+        && value > max
+    {
+        violations.push(Violation::new(
+            field,
+            format!("{} {} is above {} {}", field, value, max_name, max),
+        ));
+        nearest = increment.and_then(|inc| floor_to(max, inc)).unwrap_or(max);
+    }
+
+    nearest.normalize()
+}
+
+fn too_large(field: &'static str, value: Decimal) -> Violation {
+    Violation::new(field, format!("{} {} is too large to check", field, value))
+}
+
+/// `None` when the value has too many increments to count.
+fn round_to(value: Decimal, increment: Decimal) -> Option<Decimal> {
+    let steps = value
+        .checked_div(increment)?
+        .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+    Some(steps.checked_mul(increment)?.normalize())
+}
+
+fn ceil_to(value: Decimal, increment: Decimal) -> Option<Decimal> {
+    Some(
+        value
+            .checked_div(increment)?
+            .ceil()
+            .checked_mul(increment)?
+            .normalize(),
+    )
+}
+
+fn floor_to(value: Decimal, increment: Decimal) -> Option<Decimal> {
+    Some(
+        value
+            .checked_div(increment)?
+            .floor()
+            .checked_mul(increment)?
+            .normalize(),
+    )
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+/// A proposed order. A price makes it a limit order, which needs a size;
+/// without one it is a market order with either size or funds.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub size: Option<Decimal>,
+    pub funds: Option<Decimal>,
+    /// Last traded price, enabling the `price_limit_rate` check.
+    pub reference_price: Option<Decimal>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Violation {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderSuggestion {
+    pub price: Option<Decimal>,
+    pub size: Option<Decimal>,
+    pub funds: Option<Decimal>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderValidation {
+    pub symbol: String,
+    pub valid: bool,
+    pub violations: Vec<Violation>,
+    /// The order with every violating value replaced by the nearest valid one.
+    pub suggestion: OrderSuggestion,
+}
+
+pub struct RetrospectiveRow {
+    pub outcome: MsgSendOutcome,
+    pub violations: Vec<String>,
+    /// The exchange answered with a non-success code.
+    pub rejected: bool,
+}
+
+pub struct ValidationRetrospective {
+    pub rows: Vec<RetrospectiveRow>,
+    pub flagged: usize,
+    pub rejected: usize,
+    pub flagged_and_rejected: usize,
+}
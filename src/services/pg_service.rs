@@ -1,28 +1,26 @@
 use crate::api::models::{
-    PgConnection, PgStatStatements, PgStatTableSize, PgTableIndex, PgTableInfo,
-};
-use crate::core::error::AppResult;
-use crate::repositories::{
-    ConnectionStatsRepository, QueryStatsRepository, TableSizeRepository, TableStatsRepository,
+    PgArchiverStatus, PgConnection, PgExplain, PgPlanNode, PgReplicationSlot, PgReplicationStat,
+    PgStatSnapshot, PgStatSnapshotStatement, PgStatSnapshotTable, PgStatStatements,
+    PgStatTableSize, PgTableIndex, PgTableInfo, PgWalStatus,
 };
+use crate::core::error::{AppError, AppResult};
+use crate::repositories::{PgRepository, REGISTERED_QUERIES};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error};
+
+const TOP_REGRESSIONS: usize = 20;
+/// Snapshots offered in the diff pickers.
+const SNAPSHOT_LIST_LIMIT: i64 = 1000;
+/// Estimated vs actual row counts differing by this factor get flagged.
+const ESTIMATE_MISMATCH_FACTOR: f64 = 10.0;
 
-pub struct PgService<R>
-where
-    R: ConnectionStatsRepository
-        + TableStatsRepository
-        + QueryStatsRepository
-        + TableSizeRepository,
-{
+pub struct PgService<R: PgRepository> {
     repo: R,
 }
 
-impl<R> PgService<R>
-where
-    R: ConnectionStatsRepository
-        + TableStatsRepository
-        + QueryStatsRepository
-        + TableSizeRepository,
-{
+impl<R: PgRepository> PgService<R> {
     pub fn new(repo: R) -> Self {
         Self { repo }
     }
@@ -64,6 +62,326 @@ where
             sizes,
         })
     }
+
+    /// Collects replication, slot, WAL and archiver state. Each part is
+    /// optional: a standalone server has no standbys or slots, and managed
+    /// setups may deny access to some views, so failures are reported per
+    /// section instead of failing the page.
+    pub async fn get_replication_status(&self) -> PgReplicationStatus {
+        let (replicas, slots, primary, replica, archiver, wal_rate) = tokio::join!(
+            self.repo.get_replication_stats(),
+            self.repo.get_replication_slots(),
+            self.repo.get_wal_status(),
+            self.repo.get_replica_wal_status(),
+            self.repo.get_archiver_status(),
+            self.repo.get_wal_rate(),
+        );
+
+        let mut errors = Vec::new();
+        let replicas = section(&mut errors, "pg_stat_replication", replicas).unwrap_or_default();
+        let slots = section(&mut errors, "pg_replication_slots", slots).unwrap_or_default();
+        let servers = [
+            section(&mut errors, "primary wal status", primary),
+            section(&mut errors, "replica wal status", replica).flatten(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let archiver = section(&mut errors, "pg_stat_archiver", archiver);
+        let wal_rate = section(&mut errors, "wal rate", wal_rate).flatten();
+
+        PgReplicationStatus {
+            servers,
+            replicas,
+            slots,
+            archiver,
+            wal_rate,
+            errors,
+        }
+    }
+
+    /// Periodically snapshots `pg_stat_statements` and table stats and drops
+    /// snapshots older than `retention`. Never returns.
+    pub async fn run_sampler(&self, interval: Duration, retention: Duration) {
+        let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            match self.repo.take_snapshot().await {
+                Ok(snapshot) => debug!("Took pg_stat snapshot {}", snapshot.id),
+                Err(e) => error!("Failed to take pg_stat snapshot: {}", e),
+            }
+
+            let cutoff = chrono::Utc::now() - retention;
+            match self.repo.delete_snapshots_before(cutoff).await {
+                Ok(0) => {}
+                Ok(n) => debug!("Pruned {} pg_stat snapshots", n),
+                Err(e) => error!("Failed to prune pg_stat snapshots: {}", e),
+            }
+        }
+    }
+
+    /// Lists the latest snapshots and diffs `from` against `to`. Missing ids
+    /// default to the latest snapshot and the one before `to`; `None` when
+    /// an id is not stored.
+    pub async fn get_snapshot_history(
+        &self,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> AppResult<Option<PgSnapshotHistory>> {
+        let mut snapshots = self.repo.get_snapshots(SNAPSHOT_LIST_LIMIT).await?;
+
+        // Requested snapshots may be older than the listed ones.
+        let ids: Vec<i64> = from.into_iter().chain(to).collect();
+        let requested = self.repo.get_snapshots_by_id(&ids).await?;
+        if ids.iter().any(|id| !requested.iter().any(|s| s.id == *id)) {
+            return Ok(None);
+        }
+        let find = |id: i64| requested.iter().find(|s| s.id == id).cloned();
+
+        let to = match to {
+            Some(id) => find(id),
+            None => snapshots.first().cloned(),
+        };
+        let from = match (from, &to) {
+            (Some(id), _) => find(id),
+            (None, Some(to)) => self.repo.get_snapshot_before(to.taken_at).await?,
+            (None, None) => None,
+        };
+        for snapshot in from.iter().chain(&to) {
+            if !snapshots.iter().any(|s| s.id == snapshot.id) {
+                snapshots.push(snapshot.clone());
+            }
+        }
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.taken_at));
+
+        let diff = match (from, to) {
+            (Some(from), Some(to)) => {
+                let (from_statements, to_statements, from_tables, to_tables) = tokio::try_join!(
+                    self.repo.get_snapshot_statements(from.id),
+                    self.repo.get_snapshot_statements(to.id),
+                    self.repo.get_snapshot_tables(from.id),
+                    self.repo.get_snapshot_tables(to.id),
+                )?;
+
+                let statements = diff_statements(from_statements, to_statements);
+                let mut regressions: Vec<StatementDelta> = statements
+                    .iter()
+                    .filter(|s| s.mean_change_pct.is_some_and(|pct| pct > 0.0))
+                    .cloned()
+                    .collect();
+                regressions.sort_by(|a, b| {
+                    let pct = |d: &StatementDelta| d.mean_change_pct.unwrap_or(0.0);
+                    pct(b).total_cmp(&pct(a))
+                });
+                regressions.truncate(TOP_REGRESSIONS);
+
+                Some(PgSnapshotDiff {
+                    from_id: from.id,
+                    from_taken_at: from.taken_at,
+                    to_id: to.id,
+                    to_taken_at: to.taken_at,
+                    statements,
+                    regressions,
+                    tables: diff_tables(from_tables, to_tables),
+                })
+            }
+            _ => None,
+        };
+
+        Ok(Some(PgSnapshotHistory { snapshots, diff }))
+    }
+
+    /// Every registered query, not yet explained.
+    pub fn registered_queries(&self) -> Vec<ExplainResult> {
+        REGISTERED_QUERIES
+            .iter()
+            .map(|query| ExplainResult {
+                name: query.name,
+                sql: query.statement().into_owned(),
+                plan: None,
+                error: None,
+            })
+            .collect()
+    }
+
+    /// Runs `EXPLAIN ANALYZE` for every registered query, one at a time so
+    /// the profiling itself does not spike load. Failures are kept per query.
+    pub async fn explain_all(&self) -> Vec<ExplainResult> {
+        let mut results = Vec::with_capacity(REGISTERED_QUERIES.len());
+        for query in REGISTERED_QUERIES {
+            let result = self.repo.explain_analyze(query).await;
+            results.push(ExplainResult {
+                name: query.name,
+                sql: query.statement().into_owned(),
+                plan: result.as_ref().ok().map(flatten_plan),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        results
+    }
+
+    pub async fn explain_query(&self, name: &str) -> AppResult<ExplainResult> {
+        let query = REGISTERED_QUERIES
+            .iter()
+            .find(|q| q.name == name)
+            .ok_or_else(|| AppError::Internal(format!("Unknown query {}", name)))?;
+
+        let result = self.repo.explain_analyze(query).await;
+        Ok(ExplainResult {
+            name: query.name,
+            sql: query.statement().into_owned(),
+            plan: result.as_ref().ok().map(flatten_plan),
+            error: result.err().map(|e| e.to_string()),
+        })
+    }
+}
+
+fn section<T>(errors: &mut Vec<String>, name: &str, result: AppResult<T>) -> Option<T> {
+    result
+        .map_err(|e| errors.push(format!("{}: {}", name, e)))
+        .ok()
+}
+
+fn flatten_plan(explain: &PgExplain) -> QueryPlan {
+    fn walk(node: &PgPlanNode, depth: usize, out: &mut Vec<PlanNodeRow>) {
+        let estimate_factor = if node.actual_loops > 0.0 {
+            let (est, act) = (node.plan_rows.max(1.0), node.actual_rows.max(1.0));
+            est.max(act) / est.min(act)
+        } else {
+            1.0
+        };
+
+        out.push(PlanNodeRow {
+            depth,
+            node_type: node.node_type.clone(),
+            target: node
+                .index_name
+                .clone()
+                .or_else(|| node.relation_name.clone()),
+            actual_total_time: node.actual_total_time,
+            actual_loops: node.actual_loops,
+            actual_rows: node.actual_rows,
+            plan_rows: node.plan_rows,
+            shared_hit_blocks: node.shared_hit_blocks,
+            shared_read_blocks: node.shared_read_blocks,
+            estimate_factor,
+            mismatch: estimate_factor >= ESTIMATE_MISMATCH_FACTOR,
+        });
+
+        for child in &node.plans {
+            walk(child, depth + 1, out);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    walk(&explain.plan, 0, &mut nodes);
+
+    let worst_estimate_factor = nodes.iter().map(|n| n.estimate_factor).fold(1.0, f64::max);
+    QueryPlan {
+        planning_time: explain.planning_time,
+        execution_time: explain.execution_time,
+        worst_estimate_factor,
+        mismatch: worst_estimate_factor >= ESTIMATE_MISMATCH_FACTOR,
+        nodes,
+    }
+}
+
+/// Counters only grow until `pg_stat_statements_reset()`; when the later
+/// value is smaller the earlier snapshot predates a reset and counts as zero.
+fn counter_delta(before: i64, after: i64) -> i64 {
+    if after >= before {
+        after - before
+    } else {
+        after
+    }
+}
+
+fn diff_statements(
+    from: Vec<PgStatSnapshotStatement>,
+    to: Vec<PgStatSnapshotStatement>,
+) -> Vec<StatementDelta> {
+    let before: HashMap<i64, PgStatSnapshotStatement> =
+        from.into_iter().map(|s| (s.queryid, s)).collect();
+
+    let mut deltas: Vec<StatementDelta> = to
+        .into_iter()
+        .filter_map(|after| {
+            let prev = before
+                .get(&after.queryid)
+                .filter(|prev| prev.calls <= after.calls);
+
+            let (calls, time, rows) = match prev {
+                Some(prev) => (prev.calls, prev.total_exec_time, prev.rows),
+                None => (0, 0.0, 0),
+            };
+
+            let delta_calls = counter_delta(calls, after.calls);
+            if delta_calls == 0 {
+                return None;
+            }
+            let delta_exec_time = after.total_exec_time - time;
+            let mean_interval = delta_exec_time / delta_calls as f64;
+            let mean_before = (calls > 0).then(|| time / calls as f64);
+            let mean_change_pct = mean_before
+                .filter(|mean| *mean > 0.0)
+                .map(|mean| (mean_interval - mean) / mean * 100.0);
+
+            Some(StatementDelta {
+                queryid: after.queryid,
+                query: after.query,
+                delta_calls,
+                delta_exec_time,
+                delta_rows: counter_delta(rows, after.rows),
+                mean_before,
+                mean_interval,
+                mean_change_pct,
+            })
+        })
+        .collect();
+
+    deltas.sort_by(|a, b| b.delta_exec_time.total_cmp(&a.delta_exec_time));
+    deltas
+}
+
+fn diff_tables(from: Vec<PgStatSnapshotTable>, to: Vec<PgStatSnapshotTable>) -> Vec<TableDelta> {
+    let before: HashMap<(String, String), PgStatSnapshotTable> = from
+        .into_iter()
+        .map(|t| ((t.schemaname.clone(), t.relname.clone()), t))
+        .collect();
+
+    let mut deltas: Vec<TableDelta> = to
+        .into_iter()
+        .map(|after| {
+            let prev = before.get(&(after.schemaname.clone(), after.relname.clone()));
+            let delta = |f: fn(&PgStatSnapshotTable) -> i64| {
+                prev.map_or(f(&after), |prev| counter_delta(f(prev), f(&after)))
+            };
+            let change = |f: fn(&PgStatSnapshotTable) -> i64| f(&after) - prev.map_or(0, f);
+
+            TableDelta {
+                delta_seq_scan: delta(|t| t.seq_scan),
+                delta_idx_scan: delta(|t| t.idx_scan),
+                delta_tup_ins: delta(|t| t.n_tup_ins),
+                delta_tup_upd: delta(|t| t.n_tup_upd),
+                delta_tup_del: delta(|t| t.n_tup_del),
+                delta_live_tup: change(|t| t.n_live_tup),
+                delta_dead_tup: change(|t| t.n_dead_tup),
+                delta_bytes: change(|t| t.total_bytes),
+                schemaname: after.schemaname,
+                relname: after.relname,
+            }
+        })
+        .collect();
+
+    deltas.sort_by(|a, b| {
+        (b.delta_tup_ins + b.delta_tup_upd + b.delta_tup_del)
+            .cmp(&(a.delta_tup_ins + a.delta_tup_upd + a.delta_tup_del))
+    });
+    deltas
 }
 
 pub struct PgFullStats {
@@ -73,3 +391,83 @@ pub struct PgFullStats {
     pub statements: Vec<PgStatStatements>,
     pub sizes: Vec<PgStatTableSize>,
 }
+
+pub struct PgReplicationStatus {
+    pub servers: Vec<PgWalStatus>,
+    pub replicas: Vec<PgReplicationStat>,
+    pub slots: Vec<PgReplicationSlot>,
+    pub archiver: Option<PgArchiverStatus>,
+    /// Bytes per second; `None` until the sampler has two snapshots.
+    pub wal_rate: Option<f64>,
+    pub errors: Vec<String>,
+}
+
+pub struct PgSnapshotHistory {
+    pub snapshots: Vec<PgStatSnapshot>,
+    pub diff: Option<PgSnapshotDiff>,
+}
+
+pub struct PgSnapshotDiff {
+    pub from_id: i64,
+    pub from_taken_at: chrono::DateTime<chrono::Utc>,
+    pub to_id: i64,
+    pub to_taken_at: chrono::DateTime<chrono::Utc>,
+    pub statements: Vec<StatementDelta>,
+    pub regressions: Vec<StatementDelta>,
+    pub tables: Vec<TableDelta>,
+}
+
+#[derive(Clone)]
+pub struct StatementDelta {
+    pub queryid: i64,
+    pub query: String,
+    pub delta_calls: i64,
+    pub delta_exec_time: f64,
+    pub delta_rows: i64,
+    pub mean_before: Option<f64>,
+    pub mean_interval: f64,
+    pub mean_change_pct: Option<f64>,
+}
+
+pub struct TableDelta {
+    pub schemaname: String,
+    pub relname: String,
+    pub delta_seq_scan: i64,
+    pub delta_idx_scan: i64,
+    pub delta_tup_ins: i64,
+    pub delta_tup_upd: i64,
+    pub delta_tup_del: i64,
+    pub delta_live_tup: i64,
+    pub delta_dead_tup: i64,
+    pub delta_bytes: i64,
+}
+
+pub struct ExplainResult {
+    pub name: &'static str,
+    pub sql: String,
+    pub plan: Option<QueryPlan>,
+    pub error: Option<String>,
+}
+
+pub struct QueryPlan {
+    pub planning_time: f64,
+    pub execution_time: f64,
+    pub worst_estimate_factor: f64,
+    pub mismatch: bool,
+    pub nodes: Vec<PlanNodeRow>,
+}
+
+/// A plan node flattened in depth-first order for table rendering.
+pub struct PlanNodeRow {
+    pub depth: usize,
+    pub node_type: String,
+    pub target: Option<String>,
+    pub actual_total_time: f64,
+    pub actual_loops: f64,
+    pub actual_rows: f64,
+    pub plan_rows: f64,
+    pub shared_hit_blocks: i64,
+    pub shared_read_blocks: i64,
+    pub estimate_factor: f64,
+    pub mismatch: bool,
+}
@@ -1,6 +1,14 @@
-use crate::api::models::{PositionAsset, PositionDebt, PositionRatio};
+use crate::api::models::{PositionAsset, PositionBucket, PositionDebt, PositionRatio};
 use crate::core::error::AppResult;
-use crate::repositories::PositionRepository;
+use crate::repositories::{PositionRepository, PositionSeries, TableFilter};
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Ranges needing more buckets than this are rejected.
+const MAX_BUCKETS: i64 = 10_000;
+/// Bucket picked when none is asked for: the finest one within this many.
+const TARGET_BUCKETS: i64 = 1_000;
 
 pub struct PositionService<R: PositionRepository> {
     repo: R,
@@ -11,15 +19,146 @@ impl<R: PositionRepository> PositionService<R> {
         Self { repo }
     }
 
-    pub async fn get_position_assets(&self) -> AppResult<Vec<PositionAsset>> {
-        self.repo.get_position_assets().await.map_err(Into::into)
+    pub async fn get_position_assets(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<PositionAsset>> {
+        self.repo
+            .get_position_assets(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_position_debts(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<PositionDebt>> {
+        self.repo
+            .get_position_debts(exchange, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_position_ratios(
+        &self,
+        exchange: Option<&str>,
+        filter: &TableFilter,
+    ) -> AppResult<Vec<PositionRatio>> {
+        self.repo
+            .get_position_ratios(exchange, filter)
+            .await
+            .map_err(Into::into)
     }
 
-    pub async fn get_position_debts(&self) -> AppResult<Vec<PositionDebt>> {
-        self.repo.get_position_debts().await.map_err(Into::into)
+    pub async fn get_position_history(
+        &self,
+        series: PositionSeries,
+        range: HistoryRange,
+        exchange: Option<&str>,
+        key: Option<&str>,
+    ) -> AppResult<PositionHistory> {
+        let points = self
+            .repo
+            .get_position_history(
+                series,
+                range.from.timestamp(),
+                range.to.timestamp(),
+                range.bucket.secs(),
+                exchange,
+                key,
+            )
+            .await?;
+
+        Ok(PositionHistory {
+            series,
+            from: range.from,
+            to: range.to,
+            bucket: range.bucket,
+            points,
+        })
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Bucket {
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl Bucket {
+    const ALL: [Bucket; 4] = [
+        Bucket::Minute,
+        Bucket::FiveMinutes,
+        Bucket::Hour,
+        Bucket::Day,
+    ];
 
-    pub async fn get_position_ratios(&self) -> AppResult<Vec<PositionRatio>> {
-        self.repo.get_position_ratios().await.map_err(Into::into)
+    pub fn secs(self) -> i64 {
+        match self {
+            Bucket::Minute => 60,
+            Bucket::FiveMinutes => 5 * 60,
+            Bucket::Hour => 60 * 60,
+            Bucket::Day => 24 * 60 * 60,
+        }
     }
 }
+
+/// A validated `[from, to)` window and its bucket width.
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket: Bucket,
+}
+
+impl HistoryRange {
+    /// Defaults to the last 24 hours ending at `now`, and to the finest
+    /// bucket keeping the series within `TARGET_BUCKETS` points.
+    pub fn new(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        bucket: Option<Bucket>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, String> {
+        // Bound as whole epoch seconds.
+        let to = to.unwrap_or(now).trunc_subsecs(0);
+        let from = from.map_or(to - Duration::days(1), |f| f.trunc_subsecs(0));
+        if from >= to {
+            return Err("from must be before to".to_string());
+        }
+
+        let span = (to - from).num_seconds().max(1);
+        let bucket = match bucket {
+            Some(bucket) => bucket,
+            None => Bucket::ALL
+                .into_iter()
+                .find(|b| span / b.secs() <= TARGET_BUCKETS)
+                .unwrap_or(Bucket::Day),
+        };
+        if span / bucket.secs() > MAX_BUCKETS {
+            return Err(format!(
+                "range needs more than {} buckets; use a coarser bucket",
+                MAX_BUCKETS
+            ));
+        }
+
+        Ok(Self { from, to, bucket })
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PositionHistory {
+    pub series: PositionSeries,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket: Bucket,
+    pub points: Vec<PositionBucket>,
+}
@@ -0,0 +1,491 @@
+use crate::api::models::{
+    DebtRatioRange, ErrorCount, NewReport, RateLimitIncidents, Report, ReportEntry, TableBytes,
+};
+use crate::api::templates::{ReportHtmlTemplate, ReportMarkdownTemplate};
+use crate::core::error::{AppError, AppResult};
+use crate::repositories::{BotRepository, PgRepository, ReportRepository};
+use crate::services::bot_service::{BotState, PerformanceGroup, PerformanceRow};
+use crate::services::{BotService, PgService};
+use askama::Template;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info};
+
+/// Reports listed on `/reports`.
+const LIST_LIMIT: i64 = 500;
+/// Distinct error messages listed per report.
+const TOP_ERRORS: i64 = 10;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Failed deliveries retried per check, bounding a check to this many
+/// webhook timeouts.
+const DELIVERY_RETRIES: i64 = 5;
+/// Webhook attempts per report before its delivery is given up.
+const DELIVERY_ATTEMPTS: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    pub const ALL: [ReportPeriod; 2] = [ReportPeriod::Daily, ReportPeriod::Weekly];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "Daily",
+            ReportPeriod::Weekly => "Weekly",
+        }
+    }
+
+    pub fn length(&self) -> ChronoDuration {
+        match self {
+            ReportPeriod::Daily => ChronoDuration::days(1),
+            ReportPeriod::Weekly => ChronoDuration::weeks(1),
+        }
+    }
+
+    /// The latest UTC day, or ISO week, that ended at or before `now`.
+    pub fn last_ended(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let midnight = now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc();
+        match self {
+            ReportPeriod::Daily => (midnight - ChronoDuration::days(1), midnight),
+            ReportPeriod::Weekly => {
+                let monday =
+                    midnight - ChronoDuration::days(now.weekday().num_days_from_monday().into());
+                (monday - ChronoDuration::weeks(1), monday)
+            }
+        }
+    }
+}
+
+impl FromStr for ReportPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Unknown report period {}", s))
+    }
+}
+
+/// What a report says, rendered once into its HTML and Markdown.
+pub struct ReportContent {
+    pub source: String,
+    pub period: ReportPeriod,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    /// Generated after a later period had ended as well. Bot states and
+    /// table sizes are only known as of generation, so such a report leaves
+    /// them out.
+    pub backfilled: bool,
+    /// Bots that exited in the period.
+    pub total: Option<PerformanceRow>,
+    pub by_exchange: Vec<PerformanceRow>,
+    pub by_exit: Vec<PerformanceRow>,
+    pub best_symbols: Vec<PerformanceRow>,
+    pub worst_symbols: Vec<PerformanceRow>,
+    /// All bots at generation time; empty when backfilled.
+    pub state_counts: Vec<(BotState, usize)>,
+    pub stuck: usize,
+    pub top_errors: Vec<ErrorCount>,
+    pub debt_ratios: Vec<DebtRatioRange>,
+    pub rate_limits: Vec<RateLimitIncidents>,
+    /// Largest tables first.
+    pub tables: Vec<TableGrowth>,
+    pub database_bytes: i64,
+    /// Against the previous report of the same period with table sizes;
+    /// `None` for the first, or when backfilled.
+    pub database_growth: Option<i64>,
+}
+
+pub struct TableGrowth {
+    pub schemaname: String,
+    pub relname: String,
+    pub total_size: String,
+    pub total_bytes: i64,
+    /// `None` for a table the previous report did not see.
+    pub growth_bytes: Option<i64>,
+}
+
+impl TableGrowth {
+    pub fn growth(&self) -> String {
+        self.growth_bytes
+            .map_or_else(|| "new".to_string(), |bytes| format_growth(&bytes))
+    }
+}
+
+/// A byte count in the largest fitting binary unit, e.g. `1.5 MiB`.
+pub fn format_size(bytes: &i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes.unsigned_abs() as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let sign = if *bytes < 0 { "-" } else { "" };
+    if unit == 0 {
+        format!("{}{} B", sign, value)
+    } else {
+        format!("{}{:.1} {}", sign, value, UNITS[unit])
+    }
+}
+
+/// Like [`format_size`], always signed.
+pub fn format_growth(bytes: &i64) -> String {
+    let size = format_size(bytes);
+    if *bytes < 0 {
+        size
+    } else {
+        format!("+{}", size)
+    }
+}
+
+/// `text` fit for a Markdown table cell.
+pub fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Body posted to the webhook. `text` carries the Markdown, which Slack and
+/// Mattermost incoming webhooks post as the message.
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    source: &'a str,
+    report_id: i64,
+    period: &'a str,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    html: &'a str,
+}
+
+struct Webhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+pub struct ReportService<R: ReportRepository, B: BotRepository, P: PgRepository> {
+    repo: R,
+    bots: Arc<BotService<B>>,
+    pg: Arc<PgService<P>>,
+    webhook: Option<Webhook>,
+}
+
+impl<R: ReportRepository, B: BotRepository, P: PgRepository> ReportService<R, B, P> {
+    pub fn new(
+        repo: R,
+        bots: Arc<BotService<B>>,
+        pg: Arc<PgService<P>>,
+        webhook_url: Option<String>,
+    ) -> Self {
+        let webhook = webhook_url.and_then(|url| {
+            match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+                Ok(client) => Some(Webhook { url, client }),
+                Err(e) => {
+                    error!("Report webhook disabled: {}", e);
+                    None
+                }
+            }
+        });
+        Self {
+            repo,
+            bots,
+            pg,
+            webhook,
+        }
+    }
+
+    pub fn has_webhook(&self) -> bool {
+        self.webhook.is_some()
+    }
+
+    pub async fn get_reports(&self, period: Option<ReportPeriod>) -> AppResult<Vec<ReportEntry>> {
+        self.repo
+            .get_reports(period.as_ref().map(ReportPeriod::as_str), LIST_LIMIT)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn get_report(&self, id: i64) -> AppResult<Option<Report>> {
+        self.repo.get_report(id).await.map_err(Into::into)
+    }
+
+    /// Generates, stores and delivers reports on each of `periods` once it
+    /// has ended, checking every `interval`. Periods missed while not
+    /// running are caught up on, then failed deliveries retried with
+    /// backoff. Never returns.
+    pub async fn run_scheduler(
+        &self,
+        source: String,
+        periods: Vec<ReportPeriod>,
+        interval: Duration,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            for period in &periods {
+                let missing = match self.missing_periods(*period).await {
+                    Ok(missing) => missing,
+                    Err(e) => {
+                        error!("Failed to look up {} reports: {}", period.as_str(), e);
+                        continue;
+                    }
+                };
+                let count = missing.len();
+                for (i, (start, end)) in missing.into_iter().enumerate() {
+                    let backfilled = i + 1 < count;
+                    match self
+                        .generate(*period, start, end, &source, backfilled)
+                        .await
+                    {
+                        Ok(Some(report)) => {
+                            info!(
+                                "Stored {} report {} for source {}",
+                                period.as_str(),
+                                report.id,
+                                source
+                            );
+                            if let Err(e) = self.deliver(&report, &source).await {
+                                error!("Failed to deliver report {}: {}", report.id, e);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => error!("Failed to generate {} report: {}", period.as_str(), e),
+                    }
+                }
+            }
+
+            if let Err(e) = self.retry_deliveries(&source).await {
+                error!("Failed to retry report deliveries: {}", e);
+            }
+        }
+    }
+
+    /// Ended `period`s after the newest stored one, oldest first; only the
+    /// last ended one when none is stored yet.
+    async fn missing_periods(
+        &self,
+        period: ReportPeriod,
+    ) -> AppResult<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        let (last_start, _) = period.last_ended(Utc::now());
+        let mut start = match self.repo.get_latest_period_start(period.as_str()).await? {
+            Some(latest) => latest + period.length(),
+            None => last_start,
+        };
+
+        let mut missing = Vec::new();
+        while start <= last_start {
+            missing.push((start, start + period.length()));
+            start += period.length();
+        }
+        Ok(missing)
+    }
+
+    async fn retry_deliveries(&self, source: &str) -> AppResult<()> {
+        if !self.has_webhook() {
+            return Ok(());
+        }
+        let due = self
+            .repo
+            .get_failed_deliveries(DELIVERY_RETRIES, DELIVERY_ATTEMPTS)
+            .await?;
+        for report in due {
+            self.deliver(&report, source).await?;
+        }
+        Ok(())
+    }
+
+    /// The report on `period` from `start` to `end`, unless another
+    /// instance stored it first.
+    async fn generate(
+        &self,
+        period: ReportPeriod,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source: &str,
+        backfilled: bool,
+    ) -> AppResult<Option<Report>> {
+        let (content, tables) = self.collect(period, start, end, source, backfilled).await?;
+        let report = NewReport {
+            period: period.as_str().to_string(),
+            period_start: start,
+            period_end: end,
+            html: ReportHtmlTemplate { content: &content }.render()?,
+            markdown: ReportMarkdownTemplate { content: &content }.render()?,
+            tables,
+        };
+
+        match self.repo.create_report(&report).await? {
+            Some(id) => self.get_report(id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn collect(
+        &self,
+        period: ReportPeriod,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source: &str,
+        backfilled: bool,
+    ) -> AppResult<(ReportContent, Vec<TableBytes>)> {
+        let (performance, stats, top_errors, debt_ratios, rate_limits, sizes, previous) = tokio::try_join!(
+            self.bots
+                .get_performance_between(PerformanceGroup::Exchange, start, end),
+            async {
+                match backfilled {
+                    true => Ok(None),
+                    false => self.bots.get_bots_with_stats(None).await.map(Some),
+                }
+            },
+            async {
+                self.repo
+                    .get_top_errors(start, end, TOP_ERRORS)
+                    .await
+                    .map_err(AppError::from)
+            },
+            async {
+                self.repo
+                    .get_debt_ratio_ranges(start, end)
+                    .await
+                    .map_err(AppError::from)
+            },
+            async {
+                self.repo
+                    .get_rate_limit_incidents(start, end)
+                    .await
+                    .map_err(AppError::from)
+            },
+            async {
+                match backfilled {
+                    true => Ok(Vec::new()),
+                    false => self.pg.get_table_sizes().await,
+                }
+            },
+            async {
+                self.repo
+                    .get_previous_table_bytes(period.as_str(), start)
+                    .await
+                    .map_err(AppError::from)
+            },
+        )?;
+
+        let previous: Option<HashMap<(String, String), i64>> = previous.map(|tables| {
+            tables
+                .into_iter()
+                .map(|t| ((t.schemaname, t.relname), t.total_bytes))
+                .collect()
+        });
+        let mut tables: Vec<TableGrowth> = sizes
+            .into_iter()
+            .map(|size| TableGrowth {
+                growth_bytes: previous.as_ref().and_then(|previous| {
+                    previous
+                        .get(&(size.schemaname.clone(), size.relname.clone()))
+                        .map(|before| size.total_bytes - before)
+                }),
+                schemaname: size.schemaname,
+                relname: size.relname,
+                total_size: size.total_size,
+                total_bytes: size.total_bytes,
+            })
+            .collect();
+        tables.sort_by_key(|t| std::cmp::Reverse(t.total_bytes));
+
+        let database_bytes = tables.iter().map(|t| t.total_bytes).sum();
+        let database_growth = previous
+            .as_ref()
+            .filter(|_| !backfilled)
+            .map(|previous| database_bytes - previous.values().sum::<i64>());
+        let stored = tables
+            .iter()
+            .map(|t| TableBytes {
+                schemaname: t.schemaname.clone(),
+                relname: t.relname.clone(),
+                total_bytes: t.total_bytes,
+            })
+            .collect();
+
+        let content = ReportContent {
+            source: source.to_string(),
+            period,
+            start,
+            end,
+            generated_at: Utc::now(),
+            backfilled,
+            total: performance.total(),
+            by_exit: performance.regroup(PerformanceGroup::Exit),
+            by_exchange: performance.rows,
+            best_symbols: performance.best_symbols,
+            worst_symbols: performance.worst_symbols,
+            stuck: stats.as_ref().map_or(0, |stats| {
+                stats.bots.iter().filter(|(_, _, l)| l.stuck).count()
+            }),
+            state_counts: stats.map(|stats| stats.state_counts).unwrap_or_default(),
+            top_errors,
+            debt_ratios,
+            rate_limits,
+            tables,
+            database_bytes,
+            database_growth,
+        };
+        Ok((content, stored))
+    }
+
+    /// Posts `report` to the webhook and records the outcome on it. A
+    /// failed delivery is recorded, not returned.
+    pub async fn deliver(&self, report: &Report, source: &str) -> AppResult<()> {
+        let Some(webhook) = &self.webhook else {
+            return Ok(());
+        };
+
+        let payload = WebhookPayload {
+            text: &report.markdown,
+            source,
+            report_id: report.id,
+            period: &report.period,
+            period_start: report.period_start,
+            period_end: report.period_end,
+            html: &report.html,
+        };
+        let result = webhook
+            .client
+            .post(&webhook.url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        let failure = result.err().map(|e| e.to_string());
+        if let Some(failure) = &failure {
+            error!("Report {} webhook failed: {}", report.id, failure);
+        }
+        self.repo
+            .set_delivery(report.id, failure.as_deref())
+            .await
+            .map_err(Into::into)
+    }
+}
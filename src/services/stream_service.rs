@@ -0,0 +1,510 @@
+use crate::api::models::Bot;
+use crate::core::error::{AppError, AppResult};
+use crate::repositories::stream_repository::RowKey;
+use crate::repositories::{BotRepository, StreamRepository};
+use crate::services::BotService;
+use crate::services::bot_service::BotState;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::MissedTickBehavior;
+use tracing::error;
+
+/// Rows fetched per topic and query.
+const BATCH: i64 = 1000;
+/// Most events replayed to a resuming subscription.
+const MAX_REPLAY: usize = 10_000;
+/// Oldest `since` a subscription may replay from, bounding the rows a
+/// filtered replay scans.
+const MAX_REPLAY_AGE: TimeDelta = TimeDelta::hours(24);
+/// How far behind the newest row each poll reads again. The bot stamps
+/// `updated_at` when its transaction starts, so a row can become visible
+/// after newer ones.
+const LATE_COMMIT_WINDOW: TimeDelta = TimeDelta::seconds(30);
+/// A `ctid` below every real one, to page from the start of a timestamp.
+const FIRST_ROW: &str = "(0,0)";
+/// Events buffered per subscriber before it is told it lagged.
+const CHANNEL_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    Orders,
+    Errors,
+    PositionRatio,
+    BotState,
+}
+
+impl Topic {
+    const ALL: [Topic; 4] = [
+        Topic::Orders,
+        Topic::Errors,
+        Topic::PositionRatio,
+        Topic::BotState,
+    ];
+    /// Topics read from a table; bot states are derived from several.
+    const TABLES: [Topic; 3] = [Topic::Orders, Topic::Errors, Topic::PositionRatio];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Topic::Orders => "orders",
+            Topic::Errors => "errors",
+            Topic::PositionRatio => "position_ratio",
+            Topic::BotState => "bot_state",
+        }
+    }
+
+    /// Whether the topic's rows belong to a symbol.
+    pub fn has_symbol(&self) -> bool {
+        matches!(self, Topic::Orders | Topic::BotState)
+    }
+}
+
+/// Narrows a subscription; unset fields match every event.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TopicFilter {
+    pub exchange: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl TopicFilter {
+    pub fn validate(&self, topic: Topic) -> Result<(), String> {
+        if self.symbol.is_some() && !topic.has_symbol() {
+            return Err(format!(
+                "{} events have no symbol to filter on",
+                topic.as_str()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        let matches = |wanted: &Option<String>, actual: &Option<String>| {
+            wanted
+                .as_deref()
+                .is_none_or(|w| actual.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(w)))
+        };
+        matches(&self.exchange, &event.exchange) && matches(&self.symbol, &event.symbol)
+    }
+}
+
+/// A row that landed in one of the topics' tables, or a bot state change.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamEvent {
+    pub topic: Topic,
+    /// The row's `updated_at`, or when the bot entered its new state.
+    /// Subscribing with this as `since` resumes from the event.
+    pub at: DateTime<Utc>,
+    /// Tells apart events with the same `at`.
+    pub id: String,
+    /// Order of broadcast, to tell a subscription which events predate it.
+    #[serde(skip)]
+    pub seq: u64,
+    #[serde(skip)]
+    pub exchange: Option<String>,
+    #[serde(skip)]
+    pub symbol: Option<String>,
+    pub data: serde_json::Value,
+}
+
+impl StreamEvent {
+    fn new<T: Serialize>(
+        topic: Topic,
+        (at, id): RowKey,
+        exchange: Option<String>,
+        symbol: Option<String>,
+        row: &T,
+    ) -> AppResult<Self> {
+        Ok(Self {
+            topic,
+            at,
+            id,
+            seq: 0,
+            exchange,
+            symbol,
+            data: serde_json::to_value(row)
+                .map_err(|e| AppError::Internal(format!("Event serialization: {}", e)))?,
+        })
+    }
+
+    /// Identifies the event among those of its topic.
+    pub fn key(&self) -> RowKey {
+        (self.at, self.id.clone())
+    }
+}
+
+#[derive(Serialize)]
+struct BotStateChange<'a> {
+    bot: &'a Bot,
+    state: BotState,
+    /// `None` for a replayed state or a bot seen for the first time.
+    previous_state: Option<BotState>,
+    since: DateTime<Utc>,
+    stuck: bool,
+    reason: Option<&'a str>,
+}
+
+/// Bots have no id; the entry order's client oid tells them apart.
+type BotKey = (Option<String>, Option<String>, Option<String>);
+
+fn bot_key(bot: &Bot) -> BotKey {
+    (
+        bot.exchange.clone(),
+        bot.symbol.clone(),
+        bot.entry_client_oid.clone(),
+    )
+}
+
+/// How far the broadcast has got, read as a whole by a new subscription.
+#[derive(Debug, Clone, Copy)]
+pub struct FeedPosition {
+    /// `seq` of the last event broadcast.
+    pub seq: u64,
+    /// Newest time polled for the topic: events up to it were broadcast,
+    /// so a resuming subscriber replays up to it and takes the rest live.
+    pub polled_to: DateTime<Utc>,
+}
+
+/// Why a replay was not sent.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The subscription asked for more than a replay serves; the message
+    /// is meant for the client.
+    Rejected(String),
+    Failed(AppError),
+}
+
+impl From<AppError> for ReplayError {
+    fn from(e: AppError) -> Self {
+        ReplayError::Failed(e)
+    }
+}
+
+struct Feed {
+    seq: u64,
+    polled_to: HashMap<Topic, DateTime<Utc>>,
+}
+
+/// Rows of a table already broadcast and still inside the late commit
+/// window, so reading the window again sends only the new ones.
+struct TablePoll {
+    seen: HashSet<RowKey>,
+    /// Rows before this are not read; the feed starts here after startup
+    /// or an idle spell.
+    floor: DateTime<Utc>,
+}
+
+impl TablePoll {
+    fn new(floor: DateTime<Utc>) -> Self {
+        Self {
+            seen: HashSet::new(),
+            floor,
+        }
+    }
+}
+
+pub struct StreamService<R: StreamRepository, B: BotRepository> {
+    repo: R,
+    bots: Arc<BotService<B>>,
+    heartbeat: Duration,
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+    feed: RwLock<Feed>,
+}
+
+impl<R: StreamRepository, B: BotRepository> StreamService<R, B> {
+    pub fn new(repo: R, bots: Arc<BotService<B>>, heartbeat: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            repo,
+            bots,
+            heartbeat,
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            feed: RwLock::new(Feed {
+                seq: 0,
+                polled_to: Topic::ALL.into_iter().map(|t| (t, now)).collect(),
+            }),
+        }
+    }
+
+    /// How often connections are sent a heartbeat.
+    pub fn heartbeat(&self) -> Duration {
+        self.heartbeat
+    }
+
+    /// A receiver for every event broadcast from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<StreamEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Where the broadcast of `topic` stands, read after
+    /// [`Self::subscribe`] so nothing falls between a replay and the live
+    /// events.
+    pub fn position(&self, topic: Topic) -> FeedPosition {
+        let feed = self.feed.read().unwrap_or_else(|e| e.into_inner());
+        FeedPosition {
+            seq: feed.seq,
+            polled_to: feed.polled_to.get(&topic).copied().unwrap_or_else(Utc::now),
+        }
+    }
+
+    /// Events of `topic` from `since` on and up to `until`, oldest first,
+    /// narrowed by `filter`. Bot states replay as the current state of
+    /// every bot that entered it in that window.
+    pub async fn replay(
+        &self,
+        topic: Topic,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        filter: &TopicFilter,
+    ) -> Result<Vec<StreamEvent>, ReplayError> {
+        if since < Utc::now() - MAX_REPLAY_AGE {
+            return Err(ReplayError::Rejected(format!(
+                "Replays reach back {} hours at most; load older events from the JSON API",
+                MAX_REPLAY_AGE.num_hours()
+            )));
+        }
+        let too_many = || {
+            ReplayError::Rejected(format!(
+                "More than {} events since {}; load older ones from the JSON API",
+                MAX_REPLAY, since
+            ))
+        };
+
+        if topic == Topic::BotState {
+            let mut events = self.bot_state_events(&mut HashMap::new(), false).await?;
+            events.retain(|e| e.at >= since && e.at <= until && filter.matches(e));
+            events.sort_by_key(|e| e.at);
+            if events.len() > MAX_REPLAY {
+                return Err(too_many());
+            }
+            return Ok(events);
+        }
+
+        let mut events = Vec::new();
+        let mut after = (since, FIRST_ROW.to_string());
+        loop {
+            let batch = self.fetch(topic, &after, Some(until), filter).await?;
+            let full = batch.len() as i64 == BATCH;
+            let Some(last) = batch.last() else { break };
+            after = last.key();
+            events.extend(batch);
+            if events.len() > MAX_REPLAY {
+                return Err(too_many());
+            }
+            if !full {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Broadcasts rows landing in the topics' tables every `interval`, and
+    /// bot state changes every `bot_interval`, while anyone is connected.
+    /// Only rows written after startup are sent.
+    pub async fn run_poller(&self, interval: Duration, bot_interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut bot_ticker = tokio::time::interval(bot_interval);
+        bot_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let now = Utc::now();
+        let mut tables: HashMap<Topic, TablePoll> = Topic::TABLES
+            .into_iter()
+            .map(|t| (t, TablePoll::new(now)))
+            .collect();
+        // `None` until the bots are read after startup or an idle spell.
+        let mut bot_states: Option<HashMap<BotKey, BotState>> = None;
+
+        loop {
+            let bots_due = tokio::select! {
+                _ = ticker.tick() => false,
+                _ = bot_ticker.tick() => true,
+            };
+
+            // Without connections nothing is read, and the feed starts over
+            // from now once one opens.
+            if self.sender.receiver_count() == 0 {
+                let now = Utc::now();
+                for poll in tables.values_mut() {
+                    *poll = TablePoll::new(now);
+                }
+                bot_states = None;
+                self.restart(now);
+                continue;
+            }
+
+            if (bots_due || bot_states.is_none())
+                && let Err(e) = self.poll_bot_states(&mut bot_states).await
+            {
+                error!("Failed to poll {} events: {}", Topic::BotState.as_str(), e);
+            }
+            if !bots_due {
+                for (topic, poll) in &mut tables {
+                    if let Err(e) = self.poll_table(*topic, poll).await {
+                        error!("Failed to poll {} events: {}", topic.as_str(), e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn restart(&self, now: DateTime<Utc>) {
+        let mut feed = self.feed.write().unwrap_or_else(|e| e.into_inner());
+        for polled_to in feed.polled_to.values_mut() {
+            *polled_to = now;
+        }
+    }
+
+    /// Sends `events` under the feed lock, so a subscription reading its
+    /// position sees either all of them or none.
+    fn publish(&self, topic: Topic, events: Vec<StreamEvent>, polled_to: Option<DateTime<Utc>>) {
+        let mut feed = self.feed.write().unwrap_or_else(|e| e.into_inner());
+        for mut event in events {
+            feed.seq += 1;
+            event.seq = feed.seq;
+            let newest = feed.polled_to.entry(topic).or_insert(event.at);
+            *newest = (*newest).max(event.at);
+            // Without subscribers there is nobody to tell.
+            let _ = self.sender.send(Arc::new(event));
+        }
+        if let Some(polled_to) = polled_to {
+            feed.polled_to.insert(topic, polled_to);
+        }
+    }
+
+    async fn poll_table(&self, topic: Topic, poll: &mut TablePoll) -> AppResult<()> {
+        let newest = self.position(topic).polled_to;
+        let start = poll.floor.max(newest - LATE_COMMIT_WINDOW);
+        let mut after = (start, FIRST_ROW.to_string());
+        loop {
+            let batch = self
+                .fetch(topic, &after, None, &TopicFilter::default())
+                .await?;
+            let full = batch.len() as i64 == BATCH;
+            let Some(last) = batch.last() else { break };
+            after = last.key();
+            let fresh = batch
+                .into_iter()
+                .filter(|e| poll.seen.insert(e.key()))
+                .collect();
+            self.publish(topic, fresh, None);
+            if !full {
+                break;
+            }
+        }
+
+        let horizon = self.position(topic).polled_to - LATE_COMMIT_WINDOW;
+        poll.seen.retain(|(at, _)| *at >= horizon);
+        Ok(())
+    }
+
+    async fn poll_bot_states(
+        &self,
+        known: &mut Option<HashMap<BotKey, BotState>>,
+    ) -> AppResult<()> {
+        let polled_at = Utc::now();
+        let events = match known {
+            Some(known) => self.bot_state_events(known, true).await?,
+            None => {
+                // Bots read afresh are news only if they entered their
+                // state since the feed last looked.
+                let previous = self.position(Topic::BotState).polled_to;
+                let mut states = HashMap::new();
+                let mut events = self.bot_state_events(&mut states, false).await?;
+                events.retain(|e| e.at > previous);
+                *known = Some(states);
+                events
+            }
+        };
+        self.publish(Topic::BotState, events, Some(polled_at));
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        topic: Topic,
+        after: &RowKey,
+        until: Option<DateTime<Utc>>,
+        filter: &TopicFilter,
+    ) -> AppResult<Vec<StreamEvent>> {
+        let exchange = filter.exchange.as_deref();
+        match topic {
+            Topic::Orders => self
+                .repo
+                .get_orders_since(after, until, exchange, filter.symbol.as_deref(), BATCH)
+                .await?
+                .into_iter()
+                .map(|o| {
+                    let key = (o.row.updated_at, o.row_id);
+                    let (exchange, symbol) = (o.row.exchange.clone(), o.row.symbol.clone());
+                    StreamEvent::new(topic, key, Some(exchange), Some(symbol), &o.row)
+                })
+                .collect(),
+            Topic::Errors => self
+                .repo
+                .get_errors_since(after, until, exchange, BATCH)
+                .await?
+                .into_iter()
+                .map(|e| {
+                    let key = (e.row.updated_at, e.row_id);
+                    StreamEvent::new(topic, key, Some(e.row.exchange.clone()), None, &e.row)
+                })
+                .collect(),
+            Topic::PositionRatio => self
+                .repo
+                .get_position_ratios_since(after, until, exchange, BATCH)
+                .await?
+                .into_iter()
+                .map(|r| {
+                    let key = (r.row.updated_at, r.row_id);
+                    StreamEvent::new(topic, key, Some(r.row.exchange.clone()), None, &r.row)
+                })
+                .collect(),
+            Topic::BotState => Ok(Vec::new()),
+        }
+    }
+
+    /// Current states of all bots, updating `known`. With `changes_only`,
+    /// only bots whose state differs from `known` are returned.
+    async fn bot_state_events(
+        &self,
+        known: &mut HashMap<BotKey, BotState>,
+        changes_only: bool,
+    ) -> AppResult<Vec<StreamEvent>> {
+        let stats = self.bots.get_bots_with_stats(None).await?;
+
+        let mut events = Vec::new();
+        for (_, bot, lifecycle) in &stats.bots {
+            let previous = known.insert(bot_key(bot), lifecycle.state);
+            if changes_only && previous == Some(lifecycle.state) {
+                continue;
+            }
+            let change = BotStateChange {
+                bot,
+                state: lifecycle.state,
+                previous_state: previous.filter(|_| changes_only),
+                since: lifecycle.since,
+                stuck: lifecycle.stuck,
+                reason: lifecycle.reason.as_deref(),
+            };
+            let id = format!(
+                "{}/{}/{}/{}",
+                bot.exchange.as_deref().unwrap_or_default(),
+                bot.symbol.as_deref().unwrap_or_default(),
+                bot.entry_client_oid.as_deref().unwrap_or_default(),
+                lifecycle.state.as_str()
+            );
+            events.push(StreamEvent::new(
+                Topic::BotState,
+                (lifecycle.since, id),
+                bot.exchange.clone(),
+                bot.symbol.clone(),
+                &change,
+            )?);
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::api::models::{
+    NewSymbolChange, SymbolCatalogEntry, SymbolCatalogSnapshot, SymbolChange,
+};
+use crate::core::error::AppResult;
+use crate::repositories::{SymbolChangeFilter, SymbolChangeRepository};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info};
+use utoipa::ToSchema;
+
+pub const CHANGE_KINDS: &[&str] = &["added", "removed", "changed"];
+const CHANGELOG_LIMIT: i64 = 1000;
+
+pub struct SymbolChangeService<R: SymbolChangeRepository> {
+    repo: R,
+}
+
+impl<R: SymbolChangeRepository> SymbolChangeService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// Snapshots the catalog every `interval` and drops snapshots older
+    /// than `retention`. Never returns.
+    pub async fn run_tracker(&self, interval: Duration, retention: Duration) {
+        let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            match self.track().await {
+                Ok(Some(snapshot)) => info!("Stored symbol catalog snapshot {}", snapshot.id),
+                Ok(None) => debug!("Symbol catalog unchanged"),
+                Err(e) => error!("Failed to snapshot symbol catalog: {}", e),
+            }
+
+            let cutoff = chrono::Utc::now() - retention;
+            match self.repo.delete_snapshots_before(cutoff).await {
+                Ok(0) => {}
+                Ok(n) => debug!("Pruned {} symbol catalog snapshots", n),
+                Err(e) => error!("Failed to prune symbol catalog snapshots: {}", e),
+            }
+        }
+    }
+
+    /// Diffs the live catalog against the latest snapshot and stores a new
+    /// snapshot if anything changed. The first snapshot is a baseline and
+    /// records no changes.
+    pub async fn track(&self) -> AppResult<Option<SymbolCatalogSnapshot>> {
+        let catalog = self.repo.get_catalog().await?;
+
+        let changes = match self.repo.get_latest_snapshot().await? {
+            Some(latest) => {
+                let previous = self.repo.get_snapshot_entries(latest.id).await?;
+                let changes = diff_catalogs(&previous, &catalog);
+                if changes.is_empty() {
+                    return Ok(None);
+                }
+                changes
+            }
+            None => Vec::new(),
+        };
+
+        let snapshot = self.repo.save_snapshot(&catalog, &changes).await?;
+        Ok(Some(snapshot))
+    }
+
+    pub async fn get_changelog(
+        &self,
+        symbol: Option<String>,
+        field: Option<String>,
+        kind: Option<String>,
+    ) -> AppResult<SymbolChangelog> {
+        let filter = SymbolChangeFilter {
+            symbol,
+            field,
+            kind,
+            limit: CHANGELOG_LIMIT,
+        };
+
+        Ok(SymbolChangelog {
+            latest: self.repo.get_latest_snapshot().await?,
+            changes: self.repo.get_changes(&filter).await?,
+        })
+    }
+}
+
+/// Added and removed symbols, plus one change per differing column of the
+/// symbols present in both catalogs.
+fn diff_catalogs(
+    before: &[SymbolCatalogEntry],
+    after: &[SymbolCatalogEntry],
+) -> Vec<NewSymbolChange> {
+    let key = |e: &SymbolCatalogEntry| (e.exchange.clone(), e.symbol.clone());
+    let before: BTreeMap<_, _> = before.iter().map(|e| (key(e), e)).collect();
+    let after: BTreeMap<_, _> = after.iter().map(|e| (key(e), e)).collect();
+
+    let mut changes = Vec::new();
+
+    for (exchange, symbol) in before.keys().filter(|k| !after.contains_key(*k)) {
+        changes.push(NewSymbolChange {
+            exchange: exchange.clone(),
+            symbol: symbol.clone(),
+            kind: "removed",
+            field: None,
+            old_value: None,
+            new_value: None,
+        });
+    }
+
+    for ((exchange, symbol), next) in &after {
+        let Some(prev) = before.get(&(exchange.clone(), symbol.clone())) else {
+            changes.push(NewSymbolChange {
+                exchange: exchange.clone(),
+                symbol: symbol.clone(),
+                kind: "added",
+                field: None,
+                old_value: None,
+                new_value: None,
+            });
+            continue;
+        };
+
+        let fields: BTreeSet<&String> = prev.fields.keys().chain(next.fields.keys()).collect();
+        for field in fields {
+            let old = prev.fields.get(field);
+            let new = next.fields.get(field);
+            if old != new {
+                changes.push(NewSymbolChange {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    kind: "changed",
+                    field: Some(field.clone()),
+                    old_value: old.and_then(display_value),
+                    new_value: new.and_then(display_value),
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+fn display_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SymbolChangelog {
+    pub latest: Option<SymbolCatalogSnapshot>,
+    pub changes: Vec<SymbolChange>,
+}
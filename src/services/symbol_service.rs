@@ -1,35 +1,261 @@
 use crate::api::models::Symbol;
+use crate::config::{RuleOp, UniverseConfig, UniverseRule};
 use crate::core::error::AppResult;
 use crate::repositories::SymbolRepository;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Window for `min_liquidity`.
+const LIQUIDITY_WINDOW_HOURS: i64 = 24;
 
 pub struct SymbolService<R: SymbolRepository> {
     repo: R,
+    universes: Vec<UniverseConfig>,
 }
 
 impl<R: SymbolRepository> SymbolService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+    pub fn new(repo: R, universes: Vec<UniverseConfig>) -> Self {
+        Self { repo, universes }
     }
 
     pub async fn get_all_symbols(&self) -> AppResult<Vec<Symbol>> {
         self.repo.get_all_symbols().await.map_err(Into::into)
     }
 
-    pub async fn get_tradeable_symbols(&self) -> AppResult<Vec<Symbol>> {
-        self.repo.get_tradeable_symbols().await.map_err(Into::into)
+    /// Symbols named `term`, or with it as base currency.
+    pub async fn search_symbols(&self, term: &str, limit: usize) -> AppResult<Vec<Symbol>> {
+        Ok(self
+            .get_all_symbols()
+            .await?
+            .into_iter()
+            .filter(|s| {
+                s.symbol.eq_ignore_ascii_case(term) || s.base_currency.eq_ignore_ascii_case(term)
+            })
+            .take(limit)
+            .collect())
+    }
+
+    pub fn universe(&self, name: &str) -> Option<&UniverseConfig> {
+        self.universes.iter().find(|u| u.name == name)
+    }
+
+    /// Splits the catalog into members and excluded symbols of `universe`,
+    /// each with the checks that decided it.
+    pub async fn evaluate_universe(
+        &self,
+        universe: &UniverseConfig,
+    ) -> AppResult<UniverseEvaluation> {
+        let symbols = self.get_all_symbols().await?;
+        let volumes = self.get_volumes(universe).await?;
+
+        let mut evaluation = UniverseEvaluation {
+            members: Vec::new(),
+            excluded: Vec::new(),
+        };
+        for symbol in symbols {
+            let verdict = judge(universe, &symbol, &volumes);
+            if verdict.member {
+                evaluation
+                    .members
+                    .push((evaluation.members.len() + 1, symbol));
+            } else {
+                evaluation.excluded.push((symbol, verdict));
+            }
+        }
+
+        Ok(evaluation)
     }
 
-    pub async fn get_symbols_with_index(&self, tradeable: bool) -> AppResult<Vec<(usize, Symbol)>> {
-        let symbols = if tradeable {
-            self.get_tradeable_symbols().await?
-        } else {
-            self.get_all_symbols().await?
+    pub async fn get_universe_summaries(&self) -> AppResult<Vec<UniverseSummary>> {
+        let mut summaries = Vec::with_capacity(self.universes.len());
+        for universe in &self.universes {
+            let evaluation = self.evaluate_universe(universe).await?;
+            summaries.push(UniverseSummary {
+                name: universe.name.clone(),
+                description: universe.description.clone(),
+                members: evaluation.members.len(),
+                excluded: evaluation.excluded.len(),
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// Every check of `universe` against one symbol; `None` if the symbol
+    /// is not in the catalog.
+    pub async fn explain(
+        &self,
+        universe: &UniverseConfig,
+        symbol: &str,
+    ) -> AppResult<Option<(Symbol, Verdict)>> {
+        let Some(symbol) = self
+            .get_all_symbols()
+            .await?
+            .into_iter()
+            .find(|s| s.symbol.eq_ignore_ascii_case(symbol))
+        else {
+            return Ok(None);
         };
 
-        Ok(symbols
+        let volumes = self.get_volumes(universe).await?;
+        let verdict = judge(universe, &symbol, &volumes);
+        Ok(Some((symbol, verdict)))
+    }
+
+    async fn get_volumes(&self, universe: &UniverseConfig) -> AppResult<HashMap<String, f64>> {
+        if universe.min_liquidity.is_none() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(self
+            .repo
+            .get_quote_volumes(LIQUIDITY_WINDOW_HOURS)
+            .await?
             .into_iter()
-            .enumerate()
-            .map(|(i, v)| (i + 1, v))
+            .map(|v| (format!("{}:{}", v.exchange, v.symbol), v.quote_volume))
             .collect())
     }
 }
+
+/// The exclude list wins, then the include list; otherwise every rule and
+/// the liquidity floor must pass. All rules are evaluated so the verdict
+/// lists every reason a symbol is excluded.
+fn judge(universe: &UniverseConfig, symbol: &Symbol, volumes: &HashMap<String, f64>) -> Verdict {
+    if universe.exclude.contains(&symbol.symbol) {
+        return Verdict::listed("exclude list", false);
+    }
+    if universe.include.contains(&symbol.symbol) {
+        return Verdict::listed("include list", true);
+    }
+
+    let fields = match serde_json::to_value(symbol) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Default::default(),
+    };
+
+    let mut checks: Vec<RuleCheck> = universe
+        .rules
+        .iter()
+        .map(|rule| {
+            let actual = fields.get(&rule.field).unwrap_or(&Value::Null);
+            RuleCheck {
+                rule: rule.to_string(),
+                actual: display_value(actual),
+                passed: rule_matches(rule, actual),
+            }
+        })
+        .collect();
+
+    if let Some(min) = universe.min_liquidity {
+        let volume = volumes
+            .get(&format!("{}:{}", symbol.exchange, symbol.symbol))
+            .copied()
+            .unwrap_or(0.0);
+        checks.push(RuleCheck {
+            rule: format!("{}h quote volume >= {}", LIQUIDITY_WINDOW_HOURS, min),
+            actual: format!("{:.2}", volume),
+            passed: volume >= min,
+        });
+    }
+
+    Verdict {
+        member: checks.iter().all(|c| c.passed),
+        checks,
+    }
+}
+
+fn rule_matches(rule: &UniverseRule, actual: &Value) -> bool {
+    let in_list = || {
+        rule.value
+            .as_array()
+            .is_some_and(|values| values.iter().any(|v| values_equal(actual, v)))
+    };
+    let compare = || number(actual).zip(number(&rule.value));
+
+    match rule.op {
+        RuleOp::Eq => values_equal(actual, &rule.value),
+        RuleOp::Ne => !values_equal(actual, &rule.value),
+        RuleOp::In => in_list(),
+        RuleOp::NotIn => !in_list(),
+        RuleOp::Gt => compare().is_some_and(|(a, b)| a > b),
+        RuleOp::Gte => compare().is_some_and(|(a, b)| a >= b),
+        RuleOp::Lt => compare().is_some_and(|(a, b)| a < b),
+        RuleOp::Lte => compare().is_some_and(|(a, b)| a <= b),
+    }
+}
+
+/// Numeric when both sides parse as numbers, so `"0.10"` equals `0.1`.
+fn values_equal(actual: &Value, expected: &Value) -> bool {
+    match (number(actual), number(expected)) {
+        (Some(a), Some(b)) => a == b,
+        _ => actual == expected,
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub struct UniverseSummary {
+    pub name: String,
+    pub description: String,
+    pub members: usize,
+    pub excluded: usize,
+}
+
+pub struct UniverseEvaluation {
+    pub members: Vec<(usize, Symbol)>,
+    pub excluded: Vec<(Symbol, Verdict)>,
+}
+
+impl UniverseEvaluation {
+    /// Changes whenever a symbol joins or leaves, including on liquidity
+    /// alone, for the members page's ETag.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        for (_, symbol) in &self.members {
+            (&symbol.exchange, &symbol.symbol).hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+pub struct Verdict {
+    pub member: bool,
+    pub checks: Vec<RuleCheck>,
+}
+
+impl Verdict {
+    fn listed(list: &str, member: bool) -> Self {
+        Self {
+            member,
+            checks: vec![RuleCheck {
+                rule: list.to_string(),
+                actual: "listed".to_string(),
+                passed: member,
+            }],
+        }
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &RuleCheck> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+}
+
+pub struct RuleCheck {
+    pub rule: String,
+    pub actual: String,
+    pub passed: bool,
+}
@@ -0,0 +1,141 @@
+use crate::api::models::TableView;
+use crate::api::table::{Column, TableQuery};
+use crate::core::error::AppResult;
+use crate::repositories::ViewRepository;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const MAX_NAME_LEN: usize = 100;
+const MAX_QUERY_LEN: usize = 2000;
+
+pub struct ViewService<R: ViewRepository> {
+    repo: R,
+}
+
+impl<R: ViewRepository> ViewService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    pub async fn get_views(&self, page: &str) -> AppResult<Vec<TableView>> {
+        self.repo.get_views(page).await.map_err(Into::into)
+    }
+
+    pub async fn save_view(&self, view: &NewView) -> AppResult<TableView> {
+        self.repo
+            .save_view(&view.page, &view.name, &view.query, view.is_default)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn delete_view(&self, id: i64) -> AppResult<Option<TableView>> {
+        self.repo.delete_view(id).await.map_err(Into::into)
+    }
+
+    pub async fn set_default(&self, id: i64, is_default: bool) -> AppResult<Option<TableView>> {
+        self.repo
+            .set_default(id, is_default)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// How a table page is shown: the saved view named in the query, else the
+/// query itself, else the page's default view when the query is empty.
+pub struct ResolvedView {
+    pub query: TableQuery,
+    pub views: Vec<TableView>,
+    /// Name of the saved view applied, if any.
+    pub active: Option<String>,
+}
+
+impl ResolvedView {
+    pub fn new(query: TableQuery, views: Vec<TableView>) -> Result<Self, String> {
+        let saved = match query.view.as_deref().map(str::trim) {
+            Some("") => None,
+            Some(name) => Some(
+                views
+                    .iter()
+                    .find(|v| v.name == name)
+                    .ok_or_else(|| format!("unknown view {}", name))?,
+            ),
+            None if query.is_empty() => views.iter().find(|v| v.is_default),
+            None => None,
+        };
+
+        match saved {
+            Some(view) => Ok(Self {
+                query: TableQuery::parse(&view.query)?,
+                active: Some(view.name.clone()),
+                views,
+            }),
+            None => Ok(Self {
+                query,
+                views,
+                active: None,
+            }),
+        }
+    }
+
+    /// Changes whenever the page would render differently for the same
+    /// data, for the page's ETag.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.query.to_query_string().hash(&mut hasher);
+        self.active.hash(&mut hasher);
+        for view in &self.views {
+            (view.id, &view.name, &view.query, view.is_default).hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+/// The save-view form posted from a table page; `query` is the page's
+/// current query string.
+#[derive(Deserialize)]
+pub struct ViewForm {
+    pub page: String,
+    pub name: String,
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub is_default: Option<String>,
+}
+
+pub struct NewView {
+    pub page: String,
+    pub name: String,
+    pub query: String,
+    pub is_default: bool,
+}
+
+impl ViewForm {
+    /// Checks the query against the page's `columns`, so a saved view
+    /// always renders.
+    pub fn into_view(self, columns: &'static [Column]) -> Result<NewView, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(format!("name is longer than {} bytes", MAX_NAME_LEN));
+        }
+        if self.query.len() > MAX_QUERY_LEN {
+            return Err(format!("query is longer than {} bytes", MAX_QUERY_LEN));
+        }
+        // Views store only columns, sort and filter, never another view.
+        let query = TableQuery {
+            view: None,
+            ..TableQuery::parse(&self.query)?
+        };
+        query.check(columns)?;
+
+        Ok(NewView {
+            page: self.page,
+            name,
+            query: query.to_query_string(),
+            is_default: self.is_default.is_some(),
+        })
+    }
+}